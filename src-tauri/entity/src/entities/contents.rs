@@ -27,8 +27,27 @@ pub enum ContentType {
     Text = 0,
     #[sea_orm(num_value = 1)]
     Image = 1,
+    /// PDF or DOCX document stored in cache, data is its file name
+    #[sea_orm(num_value = 2)]
+    Document = 2,
+    /// Plain text or source code file stored in cache, data is its file name
+    #[sea_orm(num_value = 3)]
+    File = 3,
+    /// Recorded or attached audio stored in cache, data is its file name
+    #[sea_orm(num_value = 4)]
+    Audio = 4,
 }
 
+impl ContentType {
+    /// Whether the data of the content is the name of a file stored in cache
+    pub fn is_file(&self) -> bool {
+        *self != ContentType::Text
+    }
+}
+
+/// Mimetype of text contents holding the transcript of an audio content
+pub const MIMETYPE_TRANSCRIPT: &str = "text/x-transcript";
+
 #[derive(Clone, Default, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
 #[sea_orm(table_name = "contents")]
 pub struct Model {
@@ -30,6 +30,24 @@ pub struct Model {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(skip_deserializing)]
     pub last_message_at: Option<DateTimeLocal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub folder_id: Option<i32>,
+    #[serde(skip_deserializing)]
+    pub pinned: bool,
+    #[serde(skip_deserializing)]
+    pub archived: bool,
+    /// Version of a prompt pinned by the conversation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_deserializing)]
+    pub prompt_version_id: Option<i32>,
+    /// Preset the conversation was created from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_deserializing)]
+    pub preset_id: Option<i32>,
+    /// Whether the subject was set by the user, in which case it isn't replaced by a generated title
+    #[serde(skip_deserializing)]
+    pub subject_edited: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -70,8 +88,28 @@ pub struct ConversationDetailsDTO {
     pub options: Option<String>,
     pub created_at: DateTimeLocal,
     pub updated_at: Option<DateTimeLocal>,
+    pub last_message_at: Option<DateTimeLocal>,
     pub message_count: Option<i32>,
     pub model_provider: Option<String>,
+    pub folder_id: Option<i32>,
+    pub pinned: bool,
+    pub archived: bool,
+    pub prompt_version_id: Option<i32>,
+    pub preset_id: Option<i32>,
+}
+
+/// Criteria to filter the list of conversations, archived conversations are only listed if asked for
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationFilter {
+    /// Only list conversations of a folder
+    pub folder_id: Option<i32>,
+    /// Only list conversations having all of these tags
+    #[serde(default)]
+    pub tag_ids: Vec<i32>,
+    pub pinned: Option<bool>,
+    #[serde(default)]
+    pub archived: bool,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -101,6 +139,12 @@ impl From<UpdateConversationDTO> for ActiveModel {
             updated_at: NotSet,
             deleted_at: NotSet,
             last_message_at: NotSet,
+            folder_id: NotSet,
+            pinned: NotSet,
+            archived: NotSet,
+            prompt_version_id: NotSet,
+            preset_id: NotSet,
+            subject_edited: NotSet,
         }
     }
 }
@@ -391,4 +435,60 @@ impl Default for GoogleOptions {
             top_p: Some(1.0),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Key of [ImageGenerationOptions] in a conversation's options.
+/// Conversations having it reply with generated images instead of text.
+pub const IMAGE_GENERATION_OPTIONS_KEY: &str = "imageGeneration";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageGenerationOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>, // e.g. 1024x1024, 1792x1024, auto
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>, // standard, hd for DALL·E 3; low, medium, high for gpt-image models
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<u8>, // min: 1, max: 10, default: 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>, // vivid, natural, DALL·E 3 only
+}
+
+impl Options for ImageGenerationOptions {}
+
+impl Default for ImageGenerationOptions {
+    fn default() -> Self {
+        ImageGenerationOptions {
+            size: None,
+            quality: None,
+            n: Some(1),
+            style: None,
+        }
+    }
+}
+
+impl ImageGenerationOptions {
+    /// Image generation options of a conversation, None if the conversation generates text
+    pub fn from_conversation_options(options: &str) -> Option<Self> {
+        serde_json::from_str::<serde_json::Value>(options)
+            .ok()
+            .and_then(|value| value.get(IMAGE_GENERATION_OPTIONS_KEY).cloned())
+            .and_then(|value| serde_json::from_value(value).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_generation_options_from_conversation_options() {
+        let options = ImageGenerationOptions::from_conversation_options(
+            r#"{"contextLength":2,"imageGeneration":{"size":"1024x1024","n":2}}"#,
+        )
+        .expect("Image generation options are parsed");
+        assert_eq!(options.size.as_deref(), Some("1024x1024"));
+        assert_eq!(options.n, Some(2));
+        assert!(ImageGenerationOptions::from_conversation_options(r#"{"contextLength":2}"#).is_none());
+    }
+}
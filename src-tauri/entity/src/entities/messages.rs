@@ -58,6 +58,13 @@ pub struct Model {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(skip_deserializing)]
     pub deleted_at: Option<DateTimeLocal>,
+    /// Number of the current version of the message, incremented on each update
+    #[serde(skip_deserializing)]
+    pub revision: i32,
+    /// Revision of the prompt a bot reply was generated from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_deserializing)]
+    pub prompt_revision: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -130,6 +137,11 @@ pub struct MessageDTO {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(skip_deserializing)]
     pub deleted_at: Option<DateTimeLocal>,
+    #[serde(skip_deserializing)]
+    pub revision: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_deserializing)]
+    pub prompt_revision: Option<i32>,
     pub content: Vec<ContentDTO>,
 }
 
@@ -161,6 +173,8 @@ impl From<(Model, Vec<super::contents::Model>)> for MessageDTO {
             created_at: message.created_at,
             updated_at: message.updated_at,
             deleted_at: message.deleted_at,
+            revision: message.revision,
+            prompt_revision: message.prompt_revision,
             content: contents.into_iter().map(|content| content.into()).collect(),
         }
     }
@@ -240,6 +254,8 @@ mod tests {
             created_at: Local::now(),
             updated_at: None,
             deleted_at: None,
+            revision: 1,
+            prompt_revision: None,
         };
 
         assert_eq!(Some("Hello".to_string()), dto.get_text());
@@ -261,6 +277,8 @@ mod tests {
             created_at: Local::now(),
             updated_at: None,
             deleted_at: None,
+            revision: 1,
+            prompt_revision: None,
         };
 
         assert_eq!(None, dto_no_text.get_text());
@@ -281,6 +299,8 @@ mod tests {
             created_at: now,
             updated_at: None,
             deleted_at: None,
+            revision: 1,
+            prompt_revision: None,
         };
 
         let contents = vec![
@@ -306,6 +326,7 @@ mod tests {
         assert_eq!(now, dto.created_at);
         assert_eq!(None, dto.updated_at);
         assert_eq!(None, dto.deleted_at);
+        assert_eq!(1, dto.revision);
         assert_eq!(1, dto.content.len());
         assert_eq!(ContentType::Text, dto.content[0].r#type);
         assert_eq!("Test content", dto.content[0].data);
@@ -327,6 +348,8 @@ mod tests {
             created_at: now,
             updated_at: None,
             deleted_at: None,
+            revision: 1,
+            prompt_revision: None,
         };
 
         let active_model: ActiveModel = dto.into_active_model();
@@ -2,9 +2,21 @@
 
 pub mod prelude;
 
+pub mod attachments;
+pub mod chunks;
+pub mod collections;
 pub mod contents;
+pub mod conversation_collections;
+pub mod conversation_tags;
 pub mod conversations;
+pub mod documents;
+pub mod folders;
+pub mod message_embeddings;
+pub mod message_revisions;
 pub mod messages;
 pub mod models;
+pub mod presets;
+pub mod prompt_versions;
 pub mod prompts;
 pub mod settings;
+pub mod tags;
@@ -79,6 +79,9 @@ pub struct Model {
     #[serde(skip_deserializing)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub deleted_at: Option<DateTimeLocal>,
+    /// Options merged into new conversations of the model, as JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_options: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -108,4 +111,6 @@ pub struct NewModel {
     pub alias: String,
     pub provider: String,
     pub config: String,
+    #[serde(default)]
+    pub default_options: Option<String>,
 }
@@ -1,8 +1,20 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.14
 
+pub use super::attachments::Entity as Attachments;
+pub use super::chunks::Entity as Chunks;
+pub use super::collections::Entity as Collections;
 pub use super::contents::Entity as Contents;
+pub use super::conversation_collections::Entity as ConversationCollections;
+pub use super::conversation_tags::Entity as ConversationTags;
 pub use super::conversations::Entity as Conversations;
+pub use super::documents::Entity as Documents;
+pub use super::folders::Entity as Folders;
+pub use super::message_embeddings::Entity as MessageEmbeddings;
+pub use super::message_revisions::Entity as MessageRevisions;
 pub use super::messages::Entity as Messages;
 pub use super::models::Entity as Models;
+pub use super::presets::Entity as Presets;
+pub use super::prompt_versions::Entity as PromptVersions;
 pub use super::prompts::Entity as Prompts;
 pub use super::settings::Entity as Settings;
+pub use super::tags::Entity as Tags;
@@ -1,6 +1,6 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.14
 
-use sea_orm::entity::prelude::*;
+use sea_orm::{entity::prelude::*, ActiveValue, FromJsonQueryResult, IntoActiveValue};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
@@ -11,19 +11,85 @@ pub struct Model {
     pub id: i32,
     pub alias: String,
     pub content: String,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub tags: PromptTags,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub usage_count: i32,
+    #[serde(default)]
+    pub last_used_at: Option<DateTimeLocal>,
+    /// Current version, incremented each time the alias or content changes
+    #[serde(default)]
+    pub version: i32,
     pub created_at: DateTimeLocal,
     pub updated_at: Option<DateTimeLocal>,
     pub deleted_at: Option<DateTimeLocal>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(has_many = "super::prompt_versions::Entity")]
+    PromptVersions,
+}
+
+impl Related<super::prompt_versions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PromptVersions.def()
+    }
+}
 
 impl ActiveModelBehavior for ActiveModel {}
 
+/// Tags of a prompt, stored as a JSON array
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct PromptTags(pub Vec<String>);
+
+impl IntoActiveValue<PromptTags> for PromptTags {
+    fn into_active_value(self) -> ActiveValue<PromptTags> {
+        ActiveValue::Set(self)
+    }
+}
+
 #[derive(DeriveIntoActiveModel, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct NewPrompt {
     pub alias: String,
     pub content: String,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub tags: PromptTags,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Order of listed prompts
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PromptSort {
+    #[default]
+    Created,
+    Alias,
+    /// Most used first
+    Popularity,
+    /// Most recently used first
+    LastUsed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_prompt_without_library_fields() {
+        let new_prompt: NewPrompt =
+            serde_json::from_str(r#"{"alias":"a","content":"{Question}"}"#).unwrap();
+        assert_eq!(PromptTags::default(), new_prompt.tags);
+        assert_eq!(None, new_prompt.category);
+        let tags: PromptTags = serde_json::from_str(r#"["x","y"]"#).unwrap();
+        assert_eq!(vec!["x".to_string(), "y".to_string()], tags.0);
+    }
 }
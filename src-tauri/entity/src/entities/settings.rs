@@ -3,11 +3,34 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::conversations::{DEFAULT_CONTEXT_LENGTH, DEFAULT_MAX_TOKENS};
+
 pub const SETTING_NETWORK_PROXY: &str = "network:proxy";
 pub const SETTING_MODELS_CONTEXT_LENGTH: &str = "models:context_length";
 pub const SETTING_MODELS_MAX_TOKENS: &str = "models:max_tokens";
 pub const SETTING_USER_DEFAULT_MODEL: &str = "user:default_model";
+pub const SETTING_USER_ENTER_TO_SEND: &str = "user:enter_to_send";
 pub const SETTING_DISPLAY_LANGUAGE: &str = "display:language";
+pub const SETTING_DISPLAY_THEME: &str = "display:darkmode";
+pub const SETTING_DISPLAY_SIDEBAR_PINNED: &str = "display:is_sidebar_pinned";
+pub const SETTING_DISPLAY_WIDE_SCREEN: &str = "display:is_wide_screen";
+pub const SETTING_PROFILE_NAME: &str = "profile:name";
+pub const SETTING_KNOWLEDGE_TOP_K: &str = "knowledge:top_k";
+pub const SETTING_SEARCH_EMBEDDING_MODEL: &str = "search:embedding_model";
+pub const SETTING_AUDIO_TRANSCRIPTION_MODEL: &str = "audio:transcription_model";
+pub const SETTING_TTS_MODEL: &str = "tts:model";
+pub const SETTING_TTS_VOICE: &str = "tts:voice";
+pub const SETTING_TTS_FORMAT: &str = "tts:format";
+pub const SETTING_TTS_SPEED: &str = "tts:speed";
+pub const SETTING_CACHE_GC_ON_STARTUP: &str = "cache:gc_on_startup";
+pub const SETTING_TRASH_RETENTION_DAYS: &str = "trash:retention_days";
+pub const SETTING_TITLE_AUTO: &str = "title:auto";
+pub const SETTING_TITLE_MODEL: &str = "title:model";
+
+pub const DEFAULT_KNOWLEDGE_TOP_K: usize = 4;
+pub const DEFAULT_TTS_VOICE: &str = "alloy";
+pub const DEFAULT_TTS_FORMAT: &str = "mp3";
+pub const DEFAULT_TTS_SPEED: f32 = 1.0;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Deserialize, Serialize)]
 #[sea_orm(table_name = "settings")]
@@ -23,7 +46,7 @@ pub enum Relation {}
 
 impl ActiveModelBehavior for ActiveModel {}
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProxySetting {
     pub on: bool,
@@ -33,3 +56,319 @@ pub struct ProxySetting {
     pub username: Option<String>,
     pub password: Option<String>,
 }
+
+/// Type of the value of a setting, values are always stored as strings
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SettingType {
+    Text,
+    /// `true` or `false`
+    Boolean,
+    /// Integer within an inclusive range
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    /// Number within an inclusive range
+    Number {
+        min: f64,
+        max: f64,
+    },
+    /// One of a fixed list of values
+    Choice(&'static [&'static str]),
+    /// Id of a model, blank when none is set
+    ModelId,
+    /// JSON of a [ProxySetting]
+    Proxy,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SettingSchema {
+    pub key: &'static str,
+    pub value_type: SettingType,
+}
+
+/// Every setting the app knows about, settings with other keys are rejected
+pub const SETTING_SCHEMAS: &[SettingSchema] = &[
+    SettingSchema {
+        key: SETTING_NETWORK_PROXY,
+        value_type: SettingType::Proxy,
+    },
+    SettingSchema {
+        key: SETTING_MODELS_CONTEXT_LENGTH,
+        value_type: SettingType::Integer {
+            min: 1,
+            max: u16::MAX as i64,
+        },
+    },
+    SettingSchema {
+        key: SETTING_MODELS_MAX_TOKENS,
+        value_type: SettingType::Integer {
+            min: 1,
+            max: u32::MAX as i64,
+        },
+    },
+    SettingSchema {
+        key: SETTING_USER_DEFAULT_MODEL,
+        value_type: SettingType::ModelId,
+    },
+    SettingSchema {
+        key: SETTING_USER_ENTER_TO_SEND,
+        value_type: SettingType::Boolean,
+    },
+    SettingSchema {
+        key: SETTING_DISPLAY_LANGUAGE,
+        value_type: SettingType::Text,
+    },
+    SettingSchema {
+        key: SETTING_DISPLAY_THEME,
+        value_type: SettingType::Choice(&["system", "light", "dark"]),
+    },
+    SettingSchema {
+        key: SETTING_DISPLAY_SIDEBAR_PINNED,
+        value_type: SettingType::Boolean,
+    },
+    SettingSchema {
+        key: SETTING_DISPLAY_WIDE_SCREEN,
+        value_type: SettingType::Boolean,
+    },
+    SettingSchema {
+        key: SETTING_PROFILE_NAME,
+        value_type: SettingType::Text,
+    },
+    SettingSchema {
+        key: SETTING_KNOWLEDGE_TOP_K,
+        value_type: SettingType::Integer { min: 1, max: 100 },
+    },
+    SettingSchema {
+        key: SETTING_SEARCH_EMBEDDING_MODEL,
+        value_type: SettingType::ModelId,
+    },
+    SettingSchema {
+        key: SETTING_AUDIO_TRANSCRIPTION_MODEL,
+        value_type: SettingType::ModelId,
+    },
+    SettingSchema {
+        key: SETTING_TTS_MODEL,
+        value_type: SettingType::ModelId,
+    },
+    SettingSchema {
+        key: SETTING_TTS_VOICE,
+        value_type: SettingType::Text,
+    },
+    SettingSchema {
+        key: SETTING_TTS_FORMAT,
+        value_type: SettingType::Choice(&["mp3", "opus", "aac", "flac", "wav", "pcm"]),
+    },
+    SettingSchema {
+        key: SETTING_TTS_SPEED,
+        value_type: SettingType::Number {
+            min: 0.25,
+            max: 4.0,
+        },
+    },
+    SettingSchema {
+        key: SETTING_CACHE_GC_ON_STARTUP,
+        value_type: SettingType::Boolean,
+    },
+    SettingSchema {
+        // 0 keeps deleted items until they are purged manually
+        key: SETTING_TRASH_RETENTION_DAYS,
+        value_type: SettingType::Integer { min: 0, max: 36500 },
+    },
+    SettingSchema {
+        key: SETTING_TITLE_AUTO,
+        value_type: SettingType::Boolean,
+    },
+    SettingSchema {
+        // blank to generate titles with the model of the conversation
+        key: SETTING_TITLE_MODEL,
+        value_type: SettingType::ModelId,
+    },
+];
+
+pub fn schema_of(key: &str) -> Option<&'static SettingSchema> {
+    SETTING_SCHEMAS.iter().find(|schema| schema.key == key)
+}
+
+/// Check the value of a setting against its schema
+pub fn validate(key: &str, value: &str) -> Result<(), String> {
+    let schema = schema_of(key).ok_or(format!("Unknown setting {}", key))?;
+    // the value isn't echoed as it may hold credentials, eg. of the proxy
+    let invalid =
+        |expected: String| format!("Invalid value of setting {}: expected {}", key, expected);
+    match schema.value_type {
+        SettingType::Text => Ok(()),
+        SettingType::Boolean => match value {
+            "true" | "false" => Ok(()),
+            _ => Err(invalid("true or false".to_string())),
+        },
+        SettingType::Integer { min, max } => match value.trim().parse::<i64>() {
+            Ok(number) if number >= min && number <= max => Ok(()),
+            _ => Err(invalid(format!("an integer from {} to {}", min, max))),
+        },
+        SettingType::Number { min, max } => match value.trim().parse::<f64>() {
+            Ok(number) if number >= min && number <= max => Ok(()),
+            _ => Err(invalid(format!("a number from {} to {}", min, max))),
+        },
+        SettingType::Choice(choices) => {
+            if choices.contains(&value) {
+                Ok(())
+            } else {
+                Err(invalid(format!("one of {}", choices.join(", "))))
+            }
+        }
+        SettingType::ModelId => match parse_model_id(value) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(invalid("the id of a model".to_string())),
+        },
+        SettingType::Proxy => serde_json::from_str::<ProxySetting>(value)
+            .map(|_| ())
+            .map_err(|err| invalid(format!("a proxy setting ({})", err))),
+    }
+}
+
+/// Id of a model set in a setting, None if blank
+fn parse_model_id(value: &str) -> Result<Option<i32>, std::num::ParseIntError> {
+    if value.trim().is_empty() {
+        return Ok(None);
+    }
+    value.trim().parse::<i32>().map(Some)
+}
+
+/// Typed values of the settings used by the backend, defaults for those which aren't set
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub proxy: Option<ProxySetting>,
+    /// BCP 47 tag of the language of the app, None if not set yet
+    pub display_language: Option<String>,
+    pub context_length: u16,
+    pub max_tokens: u32,
+    pub knowledge_top_k: usize,
+    pub search_embedding_model: Option<i32>,
+    pub audio_transcription_model: Option<i32>,
+    pub tts_model: Option<i32>,
+    pub tts_voice: String,
+    pub tts_format: String,
+    pub tts_speed: f32,
+    pub cache_gc_on_startup: bool,
+    /// None if deleted items are kept until they are purged manually
+    pub trash_retention_days: Option<i64>,
+    /// Whether conversations are titled by a model after the first reply
+    pub auto_title: bool,
+    /// None to title conversations with their own model
+    pub title_model: Option<i32>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            proxy: None,
+            display_language: None,
+            context_length: DEFAULT_CONTEXT_LENGTH,
+            max_tokens: DEFAULT_MAX_TOKENS,
+            knowledge_top_k: DEFAULT_KNOWLEDGE_TOP_K,
+            search_embedding_model: None,
+            audio_transcription_model: None,
+            tts_model: None,
+            tts_voice: DEFAULT_TTS_VOICE.to_string(),
+            tts_format: DEFAULT_TTS_FORMAT.to_string(),
+            tts_speed: DEFAULT_TTS_SPEED,
+            cache_gc_on_startup: false,
+            trash_retention_days: None,
+            auto_title: true,
+            title_model: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Set a typed value from a stored one.
+    /// An invalid value is rejected and the current one kept, settings only used by the frontend are
+    /// validated but not kept.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        validate(key, value)?;
+        let value = value.trim();
+        match key {
+            SETTING_NETWORK_PROXY => self.proxy = serde_json::from_str(value).ok(),
+            SETTING_DISPLAY_LANGUAGE => {
+                self.display_language = Some(value.to_string()).filter(|lang| !lang.is_empty())
+            }
+            SETTING_MODELS_CONTEXT_LENGTH => {
+                self.context_length = value.parse().unwrap_or(DEFAULT_CONTEXT_LENGTH)
+            }
+            SETTING_MODELS_MAX_TOKENS => {
+                self.max_tokens = value.parse().unwrap_or(DEFAULT_MAX_TOKENS)
+            }
+            SETTING_KNOWLEDGE_TOP_K => {
+                self.knowledge_top_k = value.parse().unwrap_or(DEFAULT_KNOWLEDGE_TOP_K)
+            }
+            SETTING_SEARCH_EMBEDDING_MODEL => {
+                self.search_embedding_model = parse_model_id(value).unwrap_or_default()
+            }
+            SETTING_AUDIO_TRANSCRIPTION_MODEL => {
+                self.audio_transcription_model = parse_model_id(value).unwrap_or_default()
+            }
+            SETTING_TTS_MODEL => self.tts_model = parse_model_id(value).unwrap_or_default(),
+            SETTING_TTS_VOICE => {
+                self.tts_voice = if value.is_empty() {
+                    DEFAULT_TTS_VOICE.to_string()
+                } else {
+                    value.to_string()
+                }
+            }
+            SETTING_TTS_FORMAT => self.tts_format = value.to_string(),
+            SETTING_TTS_SPEED => self.tts_speed = value.parse().unwrap_or(DEFAULT_TTS_SPEED),
+            SETTING_CACHE_GC_ON_STARTUP => self.cache_gc_on_startup = value == "true",
+            SETTING_TRASH_RETENTION_DAYS => {
+                self.trash_retention_days = value.parse::<i64>().ok().filter(|days| *days > 0)
+            }
+            SETTING_TITLE_AUTO => self.auto_title = value == "true",
+            SETTING_TITLE_MODEL => self.title_model = parse_model_id(value).unwrap_or_default(),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(SETTING_MODELS_CONTEXT_LENGTH, "10").is_ok());
+        assert!(validate(SETTING_MODELS_CONTEXT_LENGTH, "0").is_err());
+        assert!(validate(SETTING_MODELS_CONTEXT_LENGTH, "70000").is_err());
+        assert!(validate(SETTING_MODELS_MAX_TOKENS, "many").is_err());
+        assert!(validate(SETTING_TTS_SPEED, "1.5").is_ok());
+        assert!(validate(SETTING_TTS_SPEED, "5").is_err());
+        assert!(validate(SETTING_DISPLAY_THEME, "dark").is_ok());
+        assert!(validate(SETTING_DISPLAY_THEME, "blue").is_err());
+        assert!(validate(SETTING_CACHE_GC_ON_STARTUP, "yes").is_err());
+        assert!(validate(SETTING_TTS_MODEL, "").is_ok());
+        assert!(validate(SETTING_TTS_MODEL, "gpt").is_err());
+        assert!(validate(SETTING_NETWORK_PROXY, "{}").is_err());
+        assert!(validate(
+            SETTING_NETWORK_PROXY,
+            r#"{"on":true,"server":"127.0.0.1:8080","http":true,"https":false}"#
+        )
+        .is_ok());
+        assert!(validate("display:unknown", "1").is_err());
+    }
+
+    #[test]
+    fn test_settings() {
+        let mut settings = Settings::default();
+        settings.set(SETTING_MODELS_MAX_TOKENS, " 1024").unwrap();
+        settings.set(SETTING_TTS_MODEL, "3").unwrap();
+        settings.set(SETTING_TRASH_RETENTION_DAYS, "0").unwrap();
+        settings.set(SETTING_PROFILE_NAME, "Me").unwrap();
+        assert!(settings.set(SETTING_MODELS_CONTEXT_LENGTH, "-1").is_err());
+        assert_eq!(1024, settings.max_tokens);
+        assert_eq!(Some(3), settings.tts_model);
+        assert_eq!(None, settings.trash_retention_days);
+        assert_eq!(DEFAULT_CONTEXT_LENGTH, settings.context_length);
+        settings.set(SETTING_TTS_MODEL, "").unwrap();
+        assert_eq!(None, settings.tts_model);
+    }
+}
@@ -0,0 +1,55 @@
+//! Shared types of imports of prompts, presets, etc.
+
+use serde::{Deserialize, Serialize};
+
+/// What to do with an imported item whose name is already used
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportConflict {
+    #[default]
+    Skip,
+    /// Import it under a new name, e.g. "COSTAR (2)"
+    Rename,
+    /// Replace the existing item
+    Overwrite,
+}
+
+/// Result of an import
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub imported: usize,
+    pub renamed: usize,
+    pub overwritten: usize,
+    pub skipped: usize,
+}
+
+/// First name derived from `name` which isn't taken, by appending a number to it
+pub fn unique_name<F>(name: &str, is_taken: F) -> String
+where
+    F: Fn(&str) -> bool,
+{
+    if !is_taken(name) {
+        return name.to_string();
+    }
+    let mut n = 2;
+    loop {
+        let candidate = format!("{} ({})", name, n);
+        if !is_taken(&candidate) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_name() {
+        let taken = ["COSTAR", "COSTAR (2)"];
+        assert_eq!("COSTAR (3)", unique_name("COSTAR", |a| taken.contains(&a)));
+        assert_eq!("COSTAR FR", unique_name("COSTAR FR", |a| taken.contains(&a)));
+    }
+}
@@ -1 +1,3 @@
 pub mod entities;
+pub mod import;
+pub mod pagination;
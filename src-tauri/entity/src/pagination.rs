@@ -0,0 +1,66 @@
+use sea_orm::prelude::DateTimeLocal;
+use serde::{Deserialize, Serialize};
+
+/// Number of items of a page when the caller doesn't ask for a size
+pub const DEFAULT_PAGE_SIZE: u64 = 50;
+/// Max number of items of a page, larger sizes are capped
+pub const MAX_PAGE_SIZE: u64 = 200;
+
+/// Page size to use for a requested size
+pub fn clamp_page_size(page_size: Option<u64>) -> u64 {
+    page_size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+}
+
+/// A page of items, with the cursor to pass to get the next page if there is one
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T, C> {
+    pub items: Vec<T>,
+    /// Number of items across all pages
+    pub total: u64,
+    pub next_cursor: Option<C>,
+}
+
+/// Position of a conversation in the list, which is sorted by pin, last activity and id
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationCursor {
+    pub pinned: bool,
+    /// Time of the last message, or creation time of conversations without one
+    pub last_message_at: DateTimeLocal,
+    pub id: i32,
+}
+
+/// Split the extra item fetched to know if there is a next page, returning the items of the page
+/// and whether more items follow
+pub fn split_extra<T>(mut items: Vec<T>, page_size: u64, from_end: bool) -> (Vec<T>, bool) {
+    if items.len() as u64 <= page_size {
+        return (items, false);
+    }
+    if from_end {
+        items.remove(0);
+    } else {
+        items.truncate(page_size as usize);
+    }
+    (items, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp_page_size() {
+        assert_eq!(clamp_page_size(None), DEFAULT_PAGE_SIZE);
+        assert_eq!(clamp_page_size(Some(0)), 1);
+        assert_eq!(clamp_page_size(Some(10)), 10);
+        assert_eq!(clamp_page_size(Some(10_000)), MAX_PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_split_extra() {
+        assert_eq!(split_extra(vec![1, 2], 2, false), (vec![1, 2], false));
+        assert_eq!(split_extra(vec![1, 2, 3], 2, false), (vec![1, 2], true));
+        assert_eq!(split_extra(vec![1, 2, 3], 2, true), (vec![2, 3], true));
+    }
+}
@@ -10,6 +10,26 @@ mod m20240101_100001_seed_settings;
 mod m20240101_100002_seed_prompts;
 mod m20240820_000001_conversations_add_last_message_at;
 mod m20250214_000001_messages_add_reasoning_fields;
+mod m20261018_000001_create_collections;
+mod m20261018_000002_create_documents;
+mod m20261018_000003_create_chunks;
+mod m20261018_000004_create_conversation_collections;
+mod m20261018_000005_create_message_embeddings;
+mod m20261018_000006_create_attachments;
+mod m20261018_000007_create_folders;
+mod m20261018_000008_create_tags;
+mod m20261018_000009_create_conversation_tags;
+mod m20261018_000010_conversations_add_organization_fields;
+mod m20261018_000011_create_message_revisions;
+mod m20261018_000012_messages_add_revision_fields;
+mod m20261018_000013_prompts_add_library_fields;
+mod m20261018_000014_create_prompt_versions;
+mod m20261018_000015_add_prompt_version_fields;
+mod m20261018_000016_create_presets;
+mod m20261018_000017_conversations_add_preset_id;
+mod m20261018_000018_models_add_default_options;
+mod m20261018_000019_conversations_add_subject_edited;
+mod m20261018_000020_message_revisions_add_prompt_revision;
 
 
 pub struct Migrator;
@@ -28,6 +48,26 @@ impl MigratorTrait for Migrator {
             Box::new(m20240101_100002_seed_prompts::Migration),
             Box::new(m20240820_000001_conversations_add_last_message_at::Migration),
             Box::new(m20250214_000001_messages_add_reasoning_fields::Migration),
+            Box::new(m20261018_000001_create_collections::Migration),
+            Box::new(m20261018_000002_create_documents::Migration),
+            Box::new(m20261018_000003_create_chunks::Migration),
+            Box::new(m20261018_000004_create_conversation_collections::Migration),
+            Box::new(m20261018_000005_create_message_embeddings::Migration),
+            Box::new(m20261018_000006_create_attachments::Migration),
+            Box::new(m20261018_000007_create_folders::Migration),
+            Box::new(m20261018_000008_create_tags::Migration),
+            Box::new(m20261018_000009_create_conversation_tags::Migration),
+            Box::new(m20261018_000010_conversations_add_organization_fields::Migration),
+            Box::new(m20261018_000011_create_message_revisions::Migration),
+            Box::new(m20261018_000012_messages_add_revision_fields::Migration),
+            Box::new(m20261018_000013_prompts_add_library_fields::Migration),
+            Box::new(m20261018_000014_create_prompt_versions::Migration),
+            Box::new(m20261018_000015_add_prompt_version_fields::Migration),
+            Box::new(m20261018_000016_create_presets::Migration),
+            Box::new(m20261018_000017_conversations_add_preset_id::Migration),
+            Box::new(m20261018_000018_models_add_default_options::Migration),
+            Box::new(m20261018_000019_conversations_add_subject_edited::Migration),
+            Box::new(m20261018_000020_message_revisions_add_prompt_revision::Migration),
         ]
     }
 }
@@ -1,7 +1,7 @@
 use sea_orm_migration::prelude::*;
 
 #[derive(DeriveIden)]
-enum Prompts {
+pub enum Prompts {
     Table,
     Id,
     Alias,
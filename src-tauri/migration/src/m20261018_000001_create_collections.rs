@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveIden)]
+pub enum Collections {
+    Table,
+    Id,
+    Name,
+    Description,
+    ModelId,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Collections::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Collections::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Collections::Name).string().not_null())
+                    .col(ColumnDef::new(Collections::Description).string().null())
+                    .col(ColumnDef::new(Collections::ModelId).integer().not_null())
+                    .col(
+                        ColumnDef::new(Collections::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(Collections::UpdatedAt).timestamp().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_collections_models")
+                            .from(Collections::Table, Collections::ModelId)
+                            .to(
+                                super::m20240101_000001_create_models::Models::Table,
+                                super::m20240101_000001_create_models::Models::Id,
+                            )
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Collections::Table).to_owned())
+            .await
+    }
+}
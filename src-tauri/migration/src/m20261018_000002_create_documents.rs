@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveIden)]
+pub enum Documents {
+    Table,
+    Id,
+    CollectionId,
+    Name,
+    Path,
+    Mimetype,
+    ChunkCount,
+    CreatedAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Documents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Documents::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Documents::CollectionId).integer().not_null())
+                    .col(ColumnDef::new(Documents::Name).string().not_null())
+                    .col(ColumnDef::new(Documents::Path).string().not_null())
+                    .col(ColumnDef::new(Documents::Mimetype).string().null())
+                    .col(
+                        ColumnDef::new(Documents::ChunkCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(Documents::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_documents_collections")
+                            .from(Documents::Table, Documents::CollectionId)
+                            .to(
+                                super::m20261018_000001_create_collections::Collections::Table,
+                                super::m20261018_000001_create_collections::Collections::Id,
+                            )
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Documents::Table).to_owned())
+            .await
+    }
+}
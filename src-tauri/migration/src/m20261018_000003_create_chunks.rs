@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveIden)]
+pub enum Chunks {
+    Table,
+    Id,
+    DocumentId,
+    CollectionId,
+    Position,
+    Text,
+    Embedding,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Chunks::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Chunks::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Chunks::DocumentId).integer().not_null())
+                    .col(ColumnDef::new(Chunks::CollectionId).integer().not_null())
+                    .col(ColumnDef::new(Chunks::Position).integer().not_null())
+                    .col(ColumnDef::new(Chunks::Text).text().not_null())
+                    .col(ColumnDef::new(Chunks::Embedding).binary().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_chunks_documents")
+                            .from(Chunks::Table, Chunks::DocumentId)
+                            .to(
+                                super::m20261018_000002_create_documents::Documents::Table,
+                                super::m20261018_000002_create_documents::Documents::Id,
+                            )
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("IDX_chunks_collection_id")
+                    .table(Chunks::Table)
+                    .col(Chunks::CollectionId)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Chunks::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveIden)]
+pub enum ConversationCollections {
+    Table,
+    ConversationId,
+    CollectionId,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ConversationCollections::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ConversationCollections::ConversationId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ConversationCollections::CollectionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(ConversationCollections::ConversationId)
+                            .col(ConversationCollections::CollectionId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_conversation_collections_conversations")
+                            .from(
+                                ConversationCollections::Table,
+                                ConversationCollections::ConversationId,
+                            )
+                            .to(
+                                super::m20240101_000003_create_conversations::Conversations::Table,
+                                super::m20240101_000003_create_conversations::Conversations::Id,
+                            )
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_conversation_collections_collections")
+                            .from(
+                                ConversationCollections::Table,
+                                ConversationCollections::CollectionId,
+                            )
+                            .to(
+                                super::m20261018_000001_create_collections::Collections::Table,
+                                super::m20261018_000001_create_collections::Collections::Id,
+                            )
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ConversationCollections::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
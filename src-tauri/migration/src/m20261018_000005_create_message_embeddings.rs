@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveIden)]
+pub enum MessageEmbeddings {
+    Table,
+    MessageId,
+    ModelId,
+    Embedding,
+    CreatedAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MessageEmbeddings::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MessageEmbeddings::MessageId)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(MessageEmbeddings::ModelId).integer().not_null())
+                    .col(ColumnDef::new(MessageEmbeddings::Embedding).binary().not_null())
+                    .col(
+                        ColumnDef::new(MessageEmbeddings::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_message_embeddings_messages")
+                            .from(MessageEmbeddings::Table, MessageEmbeddings::MessageId)
+                            .to(
+                                super::m20240101_000004_create_messages::Messages::Table,
+                                super::m20240101_000004_create_messages::Messages::Id,
+                            )
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("IDX_message_embeddings_model_id")
+                    .table(MessageEmbeddings::Table)
+                    .col(MessageEmbeddings::ModelId)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MessageEmbeddings::Table).to_owned())
+            .await
+    }
+}
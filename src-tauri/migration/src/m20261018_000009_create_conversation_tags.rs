@@ -0,0 +1,64 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveIden)]
+pub enum ConversationTags {
+    Table,
+    ConversationId,
+    TagId,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ConversationTags::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ConversationTags::ConversationId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ConversationTags::TagId).integer().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(ConversationTags::ConversationId)
+                            .col(ConversationTags::TagId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_conversation_tags_conversations")
+                            .from(ConversationTags::Table, ConversationTags::ConversationId)
+                            .to(
+                                super::m20240101_000003_create_conversations::Conversations::Table,
+                                super::m20240101_000003_create_conversations::Conversations::Id,
+                            )
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_conversation_tags_tags")
+                            .from(ConversationTags::Table, ConversationTags::TagId)
+                            .to(
+                                super::m20261018_000008_create_tags::Tags::Table,
+                                super::m20261018_000008_create_tags::Tags::Id,
+                            )
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ConversationTags::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,74 @@
+use super::m20240101_000003_create_conversations::Conversations;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const FOLDER_ID_COL_NAME: &str = "folder_id";
+const PINNED_COL_NAME: &str = "pinned";
+const ARCHIVED_COL_NAME: &str = "archived";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite can't add a foreign key to an existing table,
+        // conversations are moved out of a folder when it's deleted
+        if !manager.has_column("conversations", FOLDER_ID_COL_NAME).await? {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Conversations::Table)
+                        .add_column(ColumnDef::new(Alias::new(FOLDER_ID_COL_NAME)).integer().null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+        if !manager.has_column("conversations", PINNED_COL_NAME).await? {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Conversations::Table)
+                        .add_column(
+                            ColumnDef::new(Alias::new(PINNED_COL_NAME))
+                                .boolean()
+                                .not_null()
+                                .default(false),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+        if !manager.has_column("conversations", ARCHIVED_COL_NAME).await? {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Conversations::Table)
+                        .add_column(
+                            ColumnDef::new(Alias::new(ARCHIVED_COL_NAME))
+                                .boolean()
+                                .not_null()
+                                .default(false),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for col_name in [FOLDER_ID_COL_NAME, PINNED_COL_NAME, ARCHIVED_COL_NAME] {
+            if manager.has_column("conversations", col_name).await? {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(Conversations::Table)
+                            .drop_column(Alias::new(col_name))
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveIden)]
+pub enum MessageRevisions {
+    Table,
+    Id,
+    MessageId,
+    Revision,
+    Contents,
+    Reasoning,
+    PromptToken,
+    CompletionToken,
+    ReasoningToken,
+    TotalToken,
+    CreatedAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MessageRevisions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MessageRevisions::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(MessageRevisions::MessageId).integer().not_null())
+                    .col(ColumnDef::new(MessageRevisions::Revision).integer().not_null())
+                    .col(ColumnDef::new(MessageRevisions::Contents).text().not_null())
+                    .col(ColumnDef::new(MessageRevisions::Reasoning).text().null())
+                    .col(ColumnDef::new(MessageRevisions::PromptToken).integer().null())
+                    .col(ColumnDef::new(MessageRevisions::CompletionToken).integer().null())
+                    .col(ColumnDef::new(MessageRevisions::ReasoningToken).integer().null())
+                    .col(ColumnDef::new(MessageRevisions::TotalToken).integer().null())
+                    .col(
+                        ColumnDef::new(MessageRevisions::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_message_revisions_messages")
+                            .from(MessageRevisions::Table, MessageRevisions::MessageId)
+                            .to(
+                                super::m20240101_000004_create_messages::Messages::Table,
+                                super::m20240101_000004_create_messages::Messages::Id,
+                            )
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .index(
+                        Index::create()
+                            .name("UQ_message_revisions_message_id_revision")
+                            .col(MessageRevisions::MessageId)
+                            .col(MessageRevisions::Revision)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MessageRevisions::Table).to_owned())
+            .await
+    }
+}
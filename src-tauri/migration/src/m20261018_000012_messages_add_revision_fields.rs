@@ -0,0 +1,56 @@
+use super::m20240101_000004_create_messages::Messages;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const REVISION_COL_NAME: &str = "revision";
+const PROMPT_REVISION_COL_NAME: &str = "prompt_revision";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if !manager.has_column("messages", REVISION_COL_NAME).await? {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Messages::Table)
+                        .add_column(
+                            ColumnDef::new(Alias::new(REVISION_COL_NAME))
+                                .integer()
+                                .not_null()
+                                .default(1),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+        if !manager.has_column("messages", PROMPT_REVISION_COL_NAME).await? {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Messages::Table)
+                        .add_column(ColumnDef::new(Alias::new(PROMPT_REVISION_COL_NAME)).integer().null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for col_name in [REVISION_COL_NAME, PROMPT_REVISION_COL_NAME] {
+            if manager.has_column("messages", col_name).await? {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(Messages::Table)
+                            .drop_column(Alias::new(col_name))
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use super::m20240101_000005_create_prompts::Prompts;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const CATEGORY_COL_NAME: &str = "category";
+const TAGS_COL_NAME: &str = "tags";
+const DESCRIPTION_COL_NAME: &str = "description";
+const USAGE_COUNT_COL_NAME: &str = "usage_count";
+const LAST_USED_AT_COL_NAME: &str = "last_used_at";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let columns = [
+            ColumnDef::new(Alias::new(CATEGORY_COL_NAME))
+                .string()
+                .null()
+                .to_owned(),
+            // JSON array of strings
+            ColumnDef::new(Alias::new(TAGS_COL_NAME))
+                .json()
+                .not_null()
+                .default("[]")
+                .to_owned(),
+            ColumnDef::new(Alias::new(DESCRIPTION_COL_NAME))
+                .text()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Alias::new(USAGE_COUNT_COL_NAME))
+                .integer()
+                .not_null()
+                .default(0)
+                .to_owned(),
+            ColumnDef::new(Alias::new(LAST_USED_AT_COL_NAME))
+                .timestamp()
+                .null()
+                .to_owned(),
+        ];
+        for mut column in columns {
+            let col_name = column.get_column_name();
+            if !manager.has_column("prompts", &col_name).await? {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(Prompts::Table)
+                            .add_column(&mut column)
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for col_name in [
+            CATEGORY_COL_NAME,
+            TAGS_COL_NAME,
+            DESCRIPTION_COL_NAME,
+            USAGE_COUNT_COL_NAME,
+            LAST_USED_AT_COL_NAME,
+        ] {
+            if manager.has_column("prompts", col_name).await? {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(Prompts::Table)
+                            .drop_column(Alias::new(col_name))
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000005_create_prompts::Prompts;
+
+#[derive(DeriveIden)]
+pub enum PromptVersions {
+    Table,
+    Id,
+    PromptId,
+    Version,
+    Alias,
+    Content,
+    CreatedAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PromptVersions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PromptVersions::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(PromptVersions::PromptId).integer().not_null())
+                    .col(ColumnDef::new(PromptVersions::Version).integer().not_null())
+                    .col(ColumnDef::new(PromptVersions::Alias).string().not_null())
+                    .col(ColumnDef::new(PromptVersions::Content).text().not_null())
+                    .col(
+                        ColumnDef::new(PromptVersions::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_prompt_versions_prompts")
+                            .from(PromptVersions::Table, PromptVersions::PromptId)
+                            .to(Prompts::Table, Prompts::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .index(
+                        Index::create()
+                            .name("UQ_prompt_versions_prompt_id_version")
+                            .col(PromptVersions::PromptId)
+                            .col(PromptVersions::Version)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // existing prompts start their history at version 1
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "INSERT INTO prompt_versions (prompt_id, version, alias, content, created_at) \
+                 SELECT id, 1, alias, content, COALESCE(updated_at, created_at) FROM prompts",
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PromptVersions::Table).to_owned())
+            .await
+    }
+}
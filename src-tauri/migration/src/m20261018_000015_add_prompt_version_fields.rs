@@ -0,0 +1,77 @@
+use super::m20240101_000003_create_conversations::Conversations;
+use super::m20240101_000005_create_prompts::Prompts;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const VERSION_COL_NAME: &str = "version";
+const PROMPT_VERSION_ID_COL_NAME: &str = "prompt_version_id";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // current version of a prompt
+        if !manager.has_column("prompts", VERSION_COL_NAME).await? {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Prompts::Table)
+                        .add_column(
+                            ColumnDef::new(Alias::new(VERSION_COL_NAME))
+                                .integer()
+                                .not_null()
+                                .default(1),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+        // SQLite can't add a foreign key to an existing table, pinned versions are checked when rendered
+        if !manager
+            .has_column("conversations", PROMPT_VERSION_ID_COL_NAME)
+            .await?
+        {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Conversations::Table)
+                        .add_column(
+                            ColumnDef::new(Alias::new(PROMPT_VERSION_ID_COL_NAME))
+                                .integer()
+                                .null(),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.has_column("prompts", VERSION_COL_NAME).await? {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Prompts::Table)
+                        .drop_column(Alias::new(VERSION_COL_NAME))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        if manager
+            .has_column("conversations", PROMPT_VERSION_ID_COL_NAME)
+            .await?
+        {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Conversations::Table)
+                        .drop_column(Alias::new(PROMPT_VERSION_ID_COL_NAME))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
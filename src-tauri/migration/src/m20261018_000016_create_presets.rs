@@ -0,0 +1,88 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000001_create_models::Models;
+use super::m20240101_000005_create_prompts::Prompts;
+
+#[derive(DeriveIden)]
+pub enum Presets {
+    Table,
+    Id,
+    Name,
+    Description,
+    ModelId,
+    SystemPrompt,
+    PromptId,
+    Options,
+    CollectionIds,
+    Tools,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Presets::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Presets::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Presets::Name).string().not_null())
+                    .col(ColumnDef::new(Presets::Description).text().null())
+                    .col(ColumnDef::new(Presets::ModelId).integer().null())
+                    .col(ColumnDef::new(Presets::SystemPrompt).text().null())
+                    .col(ColumnDef::new(Presets::PromptId).integer().null())
+                    .col(ColumnDef::new(Presets::Options).text().null())
+                    // JSON array of ids of collections
+                    .col(
+                        ColumnDef::new(Presets::CollectionIds)
+                            .json()
+                            .not_null()
+                            .default("[]"),
+                    )
+                    // JSON array of names of tools
+                    .col(ColumnDef::new(Presets::Tools).json().not_null().default("[]"))
+                    .col(
+                        ColumnDef::new(Presets::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(Presets::UpdatedAt).timestamp().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_presets_models")
+                            .from(Presets::Table, Presets::ModelId)
+                            .to(Models::Table, Models::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_presets_prompts")
+                            .from(Presets::Table, Presets::PromptId)
+                            .to(Prompts::Table, Prompts::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Presets::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,39 @@
+use super::m20240101_000001_create_models::Models;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const DEFAULT_OPTIONS_COL_NAME: &str = "default_options";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // options merged into new conversations of the model, as JSON
+        if !manager.has_column("models", DEFAULT_OPTIONS_COL_NAME).await? {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Models::Table)
+                        .add_column(ColumnDef::new(Alias::new(DEFAULT_OPTIONS_COL_NAME)).text().null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.has_column("models", DEFAULT_OPTIONS_COL_NAME).await? {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Models::Table)
+                        .drop_column(Alias::new(DEFAULT_OPTIONS_COL_NAME))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
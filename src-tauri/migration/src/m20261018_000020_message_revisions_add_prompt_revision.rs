@@ -0,0 +1,39 @@
+use super::m20261018_000011_create_message_revisions::MessageRevisions;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const PROMPT_REVISION_COL_NAME: &str = "prompt_revision";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // revision of the prompt a bot message answered, restored along with the message
+        if !manager.has_column("message_revisions", PROMPT_REVISION_COL_NAME).await? {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(MessageRevisions::Table)
+                        .add_column(ColumnDef::new(Alias::new(PROMPT_REVISION_COL_NAME)).integer().null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.has_column("message_revisions", PROMPT_REVISION_COL_NAME).await? {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(MessageRevisions::Table)
+                        .drop_column(Alias::new(PROMPT_REVISION_COL_NAME))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -1,40 +1,69 @@
-use std::time::Instant;
+use std::{collections::HashMap, time::Instant};
 
 use entity::entities::{
-    contents::{ContentType, Model as Content},
+    collections::{Model as Collection, NewCollection},
+    contents::{ContentDTO, ContentType, Model as Content},
     conversations::{
-        ConversationDTO, ConversationDetailsDTO, GenericOptions, Model as Conversation,
-        NewConversationDTO, UpdateConversationDTO, DEFAULT_CONTEXT_LENGTH, DEFAULT_MAX_TOKENS,
+        ConversationDTO, ConversationDetailsDTO, ConversationFilter, GenericOptions,
+        Model as Conversation, NewConversationDTO, UpdateConversationDTO,
     },
-    messages::MessageDTO,
+    documents::Model as Document,
+    folders::{Model as Folder, NewFolder},
+    message_revisions::MessageRevisionDTO,
+    messages::{MessageDTO, Roles},
     models::{GenericConfig, Model, NewModel},
-    prompts::{Model as Prompt, NewPrompt},
-    settings::{
-        Model as Setting, ProxySetting, SETTING_MODELS_CONTEXT_LENGTH, SETTING_MODELS_MAX_TOKENS,
-        SETTING_NETWORK_PROXY,
-    },
+    presets::{Model as Preset, NewPreset},
+    prompt_versions::Model as PromptVersion,
+    prompts::{Model as Prompt, NewPrompt, PromptSort},
+    settings::{Model as Setting, ProxySetting, SETTING_SEARCH_EMBEDDING_MODEL},
+    tags::{Model as Tag, NewTag},
 };
+use entity::import::{ImportConflict, ImportReport};
+use entity::pagination::{clamp_page_size, ConversationCursor, Page};
 
 use serde_json::json;
 use sysinfo::System;
 
-use tauri::{Emitter, Listener, State};
+use tauri::{Emitter, Listener, Manager, State};
 use tokio_stream::StreamExt;
 
 use crate::{
     errors::CommandError::{self, ApiError, DbError},
-    log_utils::{debug, error, info, trace},
+    log_utils::{debug, error, info, trace, warn},
     services::{
+        attachments,
         db::Repository,
+        diff::{self, DiffLine},
+        gc::{self, GcReport},
+        image_generation,
+        knowledge,
         llm::{
-            chat::{BotReply, GlobalSettings}, client::LLMClient, models::RemoteModel
+            chat::{BotReply, GlobalSettings},
+            client::LLMClient,
+            diagnostics::{self, ConnectionDiagnostic},
+            models::{RemoteModel, RemoteModelsCache},
         },
+        presets, prompt_library,
+        search::{self, MessageIndexer, SemanticSearchResult, DEFAULT_SEARCH_LIMIT},
+        template::{self, TemplateVariable},
+        titling::{self, ConversationTitle},
+        transcription,
+        trash::{self, PurgeReport, Trash},
+        tts,
+        workspaces::{self, Workspace, WorkspaceList},
     },
     utils::is_stream_enabled
 };
 
 type CommandResult<T = ()> = Result<T, CommandError>;
 
+/// Event emitted to the frontend with the setting written by `upsert_setting`
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+/// Event emitted to the frontend with the workspace switched to, whose data replaces the current one
+pub const WORKSPACE_SWITCHED_EVENT: &str = "workspace-switched";
+/// Event emitted to the frontend with the title generated for a conversation after its first reply
+pub const CONVERSATION_TITLED_EVENT: &str = "conversation-titled";
+
 #[tauri::command]
 pub async fn create_model(
     new_model: NewModel,
@@ -66,6 +95,19 @@ pub async fn update_model(model: Model, repo: State<'_, Repository>) -> CommandR
     Ok(result)
 }
 
+#[tauri::command]
+pub async fn update_model_default_options(
+    model_id: i32,
+    default_options: Option<String>,
+    repo: State<'_, Repository>,
+) -> CommandResult<Model> {
+    let result = repo
+        .update_model_default_options(model_id, default_options)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn delete_model(model_id: i32, repo: State<'_, Repository>) -> CommandResult<Model> {
     let now = Instant::now();
@@ -81,27 +123,26 @@ pub async fn delete_model(model_id: i32, repo: State<'_, Repository>) -> Command
 #[tauri::command]
 pub async fn list_remote_models(
     config: GenericConfig,
+    force_refresh: Option<bool>,
     repo: State<'_, Repository>,
 ) -> CommandResult<Vec<RemoteModel>> {
     let now = Instant::now();
-    let proxy_setting = repo
-        .get_setting(SETTING_NETWORK_PROXY)
-        .await
-        .map(|setting| {
-            if let Ok(p_setting) = serde_json::from_str::<ProxySetting>(&setting.value) {
-                Some(p_setting)
-            } else {
-                None
-            }
-        })
-        .unwrap_or(None);
-    let init_client_result = LLMClient::new(config, proxy_setting);
+    if !force_refresh.unwrap_or(false) {
+        if let Some(cached) = RemoteModelsCache::global().get(&config) {
+            let elapsed = now.elapsed();
+            log::info!("[Timer][commands::list_remote_models]: {:.2?} (cached)", elapsed);
+            return Ok(cached);
+        }
+    }
+    let proxy_setting = repo.get_settings().await.proxy.clone();
+    let init_client_result = LLMClient::new(config.clone(), proxy_setting);
     match init_client_result {
         Ok(client) => {
             let result = client
                 .models()
                 .await
                 .map_err(|message| ApiError { message })?;
+            RemoteModelsCache::global().insert(&config, result.clone());
             let elapsed = now.elapsed();
             log::info!("[Timer][commands::list_remote_models]: {:.2?}", elapsed);
             Ok(result)
@@ -110,6 +151,20 @@ pub async fn list_remote_models(
     }
 }
 
+#[tauri::command]
+pub async fn test_model_config(
+    config: GenericConfig,
+    repo: State<'_, Repository>,
+) -> CommandResult<ConnectionDiagnostic> {
+    let now = Instant::now();
+    let proxy_setting = repo.get_settings().await.proxy.clone();
+    let client = LLMClient::new(config, proxy_setting).map_err(|message| ApiError { message })?;
+    let result = diagnostics::test_client(&client).await;
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::test_model_config]: {:.2?}", elapsed);
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn list_settings(repo: State<'_, Repository>) -> CommandResult<Vec<Setting>> {
     let result = repo
@@ -122,12 +177,72 @@ pub async fn list_settings(repo: State<'_, Repository>) -> CommandResult<Vec<Set
 #[tauri::command]
 pub async fn upsert_setting(
     setting: Setting,
+    app_handle: tauri::AppHandle,
     repo: State<'_, Repository>,
 ) -> CommandResult<Setting> {
     let result = repo
         .upsert_setting(setting)
         .await
         .map_err(|message| DbError { message })?;
+    if result.key == SETTING_SEARCH_EMBEDDING_MODEL {
+        // index messages with the new model
+        MessageIndexer::global().notify();
+    }
+    if let Err(err) = app_handle.emit(SETTINGS_CHANGED_EVENT, &result) {
+        error(
+            "upsert_setting",
+            &format!("Failed to emit {}: {}", SETTINGS_CHANGED_EVENT, err),
+        );
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn list_workspaces() -> CommandResult<WorkspaceList> {
+    let result = workspaces::list_workspaces().map_err(|message| ApiError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn create_workspace(name: String) -> CommandResult<Workspace> {
+    let result = workspaces::create_workspace(&name).map_err(|message| ApiError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn rename_workspace(workspace_id: u32, name: String) -> CommandResult<Workspace> {
+    let result = workspaces::rename_workspace(workspace_id, &name)
+        .map_err(|message| ApiError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn switch_workspace(
+    workspace_id: u32,
+    app_handle: tauri::AppHandle,
+    repo: State<'_, Repository>,
+) -> CommandResult<Workspace> {
+    let now = Instant::now();
+    let result = workspaces::switch_workspace(&repo, workspace_id)
+        .await
+        .map_err(|message| ApiError { message })?;
+    // index pending messages of the workspace
+    MessageIndexer::global().notify();
+    if let Err(err) = app_handle.emit(WORKSPACE_SWITCHED_EVENT, &result) {
+        error(
+            "switch_workspace",
+            &format!("Failed to emit {}: {}", WORKSPACE_SWITCHED_EVENT, err),
+        );
+    }
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::switch_workspace]: {:.2?}", elapsed);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn delete_workspace(workspace_id: u32) -> CommandResult<Workspace> {
+    let result =
+        workspaces::delete_workspace(workspace_id).map_err(|message| ApiError { message })?;
     Ok(result)
 }
 
@@ -170,11 +285,12 @@ pub async fn create_blank_conversation(
 
 #[tauri::command]
 pub async fn list_conversations(
+    filter: Option<ConversationFilter>,
     repo: State<'_, Repository>,
 ) -> CommandResult<Vec<ConversationDetailsDTO>> {
     let now = Instant::now();
     let result = repo
-        .list_conversations()
+        .list_conversations(filter.unwrap_or_default())
         .await
         .map_err(|message| DbError { message })?;
     let elapsed = now.elapsed();
@@ -182,6 +298,27 @@ pub async fn list_conversations(
     Ok(result)
 }
 
+#[tauri::command]
+pub async fn list_conversations_page(
+    filter: Option<ConversationFilter>,
+    cursor: Option<ConversationCursor>,
+    page_size: Option<u64>,
+    repo: State<'_, Repository>,
+) -> CommandResult<Page<ConversationDetailsDTO, ConversationCursor>> {
+    let now = Instant::now();
+    let result = repo
+        .list_conversations_page(
+            filter.unwrap_or_default(),
+            cursor,
+            clamp_page_size(page_size),
+        )
+        .await
+        .map_err(|message| DbError { message })?;
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::list_conversations_page]: {:.2?}", elapsed);
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn delete_conversation(
     conversation_id: i32,
@@ -197,6 +334,144 @@ pub async fn delete_conversation(
     Ok(result)
 }
 
+#[tauri::command]
+pub async fn move_conversation(
+    conversation_id: i32,
+    folder_id: Option<i32>,
+    repo: State<'_, Repository>,
+) -> CommandResult<()> {
+    repo.move_conversation(conversation_id, folder_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn pin_conversation(
+    conversation_id: i32,
+    pinned: bool,
+    repo: State<'_, Repository>,
+) -> CommandResult<()> {
+    repo.set_conversation_pinned(conversation_id, pinned)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn archive_conversation(
+    conversation_id: i32,
+    archived: bool,
+    repo: State<'_, Repository>,
+) -> CommandResult<()> {
+    repo.set_conversation_archived(conversation_id, archived)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn create_folder(
+    new_folder: NewFolder,
+    repo: State<'_, Repository>,
+) -> CommandResult<Folder> {
+    let result = repo
+        .create_folder(new_folder)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn list_folders(repo: State<'_, Repository>) -> CommandResult<Vec<Folder>> {
+    let result = repo
+        .list_folders()
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn rename_folder(
+    folder_id: i32,
+    name: String,
+    repo: State<'_, Repository>,
+) -> CommandResult<Folder> {
+    let result = repo
+        .rename_folder(folder_id, name)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn delete_folder(folder_id: i32, repo: State<'_, Repository>) -> CommandResult<()> {
+    repo.delete_folder(folder_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn create_tag(new_tag: NewTag, repo: State<'_, Repository>) -> CommandResult<Tag> {
+    let result = repo
+        .create_tag(new_tag)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn list_tags(repo: State<'_, Repository>) -> CommandResult<Vec<Tag>> {
+    let result = repo
+        .list_tags()
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn update_tag(tag: Tag, repo: State<'_, Repository>) -> CommandResult<Tag> {
+    let result = repo
+        .update_tag(tag)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn delete_tag(tag_id: i32, repo: State<'_, Repository>) -> CommandResult<()> {
+    repo.delete_tag(tag_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_conversation_tags(
+    conversation_id: i32,
+    tag_ids: Vec<i32>,
+    repo: State<'_, Repository>,
+) -> CommandResult<Vec<Tag>> {
+    let result = repo
+        .set_conversation_tags(conversation_id, tag_ids)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn list_conversation_tags(
+    conversation_id: i32,
+    repo: State<'_, Repository>,
+) -> CommandResult<Vec<Tag>> {
+    let result = repo
+        .list_conversation_tags(conversation_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn get_options(
     conversation_id: i32,
@@ -237,7 +512,7 @@ pub async fn update_subject(
 ) -> CommandResult<String> {
     let now = Instant::now();
     let result = repo
-        .update_conversation_subject(conversation_id, subject)
+        .update_conversation_subject(conversation_id, subject, false)
         .await
         .map_err(|message| DbError { message })?;
     let elapsed = now.elapsed();
@@ -282,6 +557,7 @@ pub async fn update_conversation(
 #[tauri::command]
 pub async fn create_message(
     message: MessageDTO,
+    app_handle: tauri::AppHandle,
     repo: State<'_, Repository>,
 ) -> CommandResult<MessageDTO> {
     let now = Instant::now();
@@ -291,11 +567,106 @@ pub async fn create_message(
         .await
         .map_err(|message| DbError { message })?;
     log::info!("create_message: result = {:?}", result);
+    MessageIndexer::global().notify();
+    // a new bot reply is saved once fully received, title the conversation in the background
+    if Roles::from(result.role) == Roles::Bot {
+        let message = result.clone();
+        tauri::async_runtime::spawn(async move {
+            let repo = app_handle.state::<Repository>();
+            match titling::title_after_first_reply(&repo, &message).await {
+                Ok(Some(subject)) => {
+                    let payload = ConversationTitle {
+                        conversation_id: message.conversation_id,
+                        subject,
+                    };
+                    if let Err(err) = app_handle.emit(CONVERSATION_TITLED_EVENT, &payload) {
+                        error(
+                            "create_message",
+                            &format!("Failed to emit {}: {}", CONVERSATION_TITLED_EVENT, err),
+                        );
+                    }
+                }
+                Ok(None) => {}
+                Err(err) => warn(
+                    "create_message",
+                    &format!(
+                        "Failed to title conversation {}: {}",
+                        message.conversation_id, err
+                    ),
+                ),
+            }
+        });
+    }
     let elapsed = now.elapsed();
     log::info!("[Timer][commands::create_message]: {:.2?}", elapsed);
     Ok(result)
 }
 
+#[tauri::command]
+pub async fn import_attachment(
+    path: String,
+    repo: State<'_, Repository>,
+) -> CommandResult<ContentDTO> {
+    let now = Instant::now();
+    let result = attachments::import_attachment(&repo, &path)
+        .await
+        .map_err(|message| ApiError { message })?;
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::import_attachment]: {:.2?}", elapsed);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn store_attachment(
+    file_name: String,
+    mimetype: String,
+    repo: State<'_, Repository>,
+) -> CommandResult<ContentDTO> {
+    let now = Instant::now();
+    let attachment = attachments::store_cached_file(&repo, &file_name, &mimetype)
+        .await
+        .map_err(|message| ApiError { message })?;
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::store_attachment]: {:.2?}", elapsed);
+    Ok(ContentDTO {
+        r#type: ContentType::Image,
+        mimetype: Some(attachment.mimetype),
+        data: attachment.file_name,
+    })
+}
+
+#[tauri::command]
+pub async fn transcribe_message_audio(
+    message_id: i32,
+    repo: State<'_, Repository>,
+) -> CommandResult<MessageDTO> {
+    let now = Instant::now();
+    let proxy_setting = repo.get_settings().await.proxy.clone();
+    let result = transcription::transcribe_message(&repo, message_id, proxy_setting)
+        .await
+        .map_err(|message| ApiError { message })?;
+    // the transcript makes the message searchable
+    MessageIndexer::global().notify();
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::transcribe_message_audio]: {:.2?}", elapsed);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn get_message_speech(
+    message_id: i32,
+    repo: State<'_, Repository>,
+) -> CommandResult<String> {
+    let now = Instant::now();
+    let proxy_setting = repo.get_settings().await.proxy.clone();
+    let path = tts::synthesize_message(&repo, message_id, proxy_setting)
+        .await
+        .map_err(|message| ApiError { message })?;
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::get_message_speech]: {:.2?}", elapsed);
+    Ok(path.to_string_lossy().to_string())
+}
+
 #[tauri::command]
 pub async fn list_messages(
     conversation_id: i32,
@@ -311,6 +682,23 @@ pub async fn list_messages(
     Ok(result)
 }
 
+#[tauri::command]
+pub async fn list_messages_page(
+    conversation_id: i32,
+    before_message_id: Option<i32>,
+    page_size: Option<u64>,
+    repo: State<'_, Repository>,
+) -> CommandResult<Page<MessageDTO, i32>> {
+    let now = Instant::now();
+    let result = repo
+        .list_messages_page(conversation_id, before_message_id, clamp_page_size(page_size))
+        .await
+        .map_err(|message| DbError { message })?;
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::list_messages_page]: {:.2?}", elapsed);
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn get_system_message(
     conversation_id: i32,
@@ -337,21 +725,62 @@ pub async fn update_message(
         .update_message(message)
         .await
         .map_err(|message| DbError { message })?;
+    // the message's embedding is deleted on update, let the indexer re-index it
+    MessageIndexer::global().notify();
     let elapsed = now.elapsed();
     log::info!("[Timer][commands::update_message]: {:.2?}", elapsed);
     Ok(result)
 }
 
+#[tauri::command]
+pub async fn list_message_revisions(
+    message_id: i32,
+    repo: State<'_, Repository>,
+) -> CommandResult<Vec<MessageRevisionDTO>> {
+    let now = Instant::now();
+    let result = repo
+        .list_message_revisions(message_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::list_message_revisions]: {:.2?}", elapsed);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn restore_message_revision(
+    message_id: i32,
+    revision: i32,
+    repo: State<'_, Repository>,
+) -> CommandResult<MessageDTO> {
+    let now = Instant::now();
+    let result = repo
+        .restore_message_revision(message_id, revision)
+        .await
+        .map_err(|message| DbError { message })?;
+    MessageIndexer::global().notify();
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::restore_message_revision]: {:.2?}", elapsed);
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn hard_delete_messages(
     conversation_id: i32,
     repo: State<'_, Repository>,
 ) -> CommandResult<()> {
     let now = Instant::now();
+    let file_names = repo
+        .list_content_files(Some(conversation_id))
+        .await
+        .map_err(|message| DbError { message })?;
     repo.hard_delete_messages(conversation_id)
         .await
         .map_err(|message| DbError { message })?;
-    // TODO: delete all media files associated with these messages
+    // files in cache are removed on a best effort basis, leftovers are collected by the cache gc
+    if let Err(err) = gc::remove_unreferenced_files(&repo, file_names).await {
+        log::warn!("hard_delete_messages: {}", err);
+    }
     let elapsed = now.elapsed();
     log::info!("[Timer][commands::delete_messages]: {:.2?}", elapsed);
     Ok(())
@@ -363,61 +792,113 @@ pub async fn hard_delete_message(
     repo: State<'_, Repository>,
 ) -> CommandResult<MessageDTO> {
     let now = Instant::now();
+    let file_names = message
+        .content
+        .iter()
+        .filter(|item| item.r#type.is_file())
+        .map(|item| item.data.clone())
+        .collect();
     let result = repo
         .hard_delete_message(message)
         .await
         .map_err(|message| DbError { message })?;
-    // TODO: delete all media files associated with this messge
+    // its embedding is deleted by cascade, its files in cache are removed unless shared
+    if let Err(err) = gc::remove_unreferenced_files(&repo, file_names).await {
+        log::warn!("hard_delete_message: {}", err);
+    }
+    if let Some(message_id) = result.id {
+        tts::remove_cached_speech(message_id);
+    }
     let elapsed = now.elapsed();
     log::info!("[Timer][commands::hard_delete_message]: {:.2?}", elapsed);
     Ok(result)
 }
 
 #[tauri::command]
-pub async fn call_bot(
-    conversation_id: i32,
-    tag: String,
-    before_message_id: Option<i32>,
-    window: tauri::Window,
-    repo: State<'_, Repository>,
-) -> CommandResult<()> {
+pub async fn collect_cache_garbage(repo: State<'_, Repository>) -> CommandResult<GcReport> {
     let now = Instant::now();
-    // Retrieve options, config and settings
-    let options = repo
-        .get_conversation_options(conversation_id)
-        .await
-        .map_err(|message| DbError { message })?;
-    let config = repo
-        .get_conversation_config(conversation_id)
+    let result = gc::collect_cache_garbage(&repo)
         .await
         .map_err(|message| DbError { message })?;
-    let proxy_setting = repo
-        .get_setting(SETTING_NETWORK_PROXY)
-        .await
-        .map(|setting| {
-            if let Ok(p_setting) = serde_json::from_str::<ProxySetting>(&setting.value) {
-                Some(p_setting)
-            } else {
-                None
-            }
-        })
-        .unwrap_or(None);
-    let ctx_length_setting: u16 = repo
-        .get_setting(SETTING_MODELS_CONTEXT_LENGTH)
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::collect_cache_garbage]: {:.2?}", elapsed);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn list_trash(repo: State<'_, Repository>) -> CommandResult<Trash> {
+    let now = Instant::now();
+    let result = trash::list_trash(&repo)
         .await
-        .map(|setting| match setting.value.parse::<u16>() {
-            Ok(value) => value,
-            Err(_) => DEFAULT_CONTEXT_LENGTH,
-        })
-        .unwrap_or(DEFAULT_CONTEXT_LENGTH);
-    let max_token_setting: u32 = repo
-        .get_setting(SETTING_MODELS_MAX_TOKENS)
+        .map_err(|message| DbError { message })?;
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::list_trash]: {:.2?}", elapsed);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn restore_conversation(
+    conversation_id: i32,
+    repo: State<'_, Repository>,
+) -> CommandResult<ConversationDTO> {
+    let now = Instant::now();
+    let result = repo
+        .restore_conversation(conversation_id)
         .await
-        .map(|setting| match setting.value.parse::<u32>() {
-            Ok(value) => value,
-            Err(_) => DEFAULT_MAX_TOKENS,
-        })
-        .unwrap_or(DEFAULT_MAX_TOKENS);
+        .map_err(|message| DbError { message })?;
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::restore_conversation]: {:.2?}", elapsed);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn restore_model(model_id: i32, repo: State<'_, Repository>) -> CommandResult<Model> {
+    let now = Instant::now();
+    let result = repo
+        .restore_model(model_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::restore_model]: {:.2?}", elapsed);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn purge_trash(
+    older_than: Option<chrono::DateTime<chrono::Local>>,
+    repo: State<'_, Repository>,
+) -> CommandResult<PurgeReport> {
+    let now = Instant::now();
+    let result = trash::purge_trash(&repo, older_than)
+        .await
+        .map_err(|message| DbError { message })?;
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::purge_trash]: {:.2?}", elapsed);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn call_bot(
+    conversation_id: i32,
+    tag: String,
+    before_message_id: Option<i32>,
+    window: tauri::Window,
+    repo: State<'_, Repository>,
+) -> CommandResult<()> {
+    let now = Instant::now();
+    // Retrieve options, config and settings
+    let options = repo
+        .get_conversation_options(conversation_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    let config = repo
+        .get_conversation_config(conversation_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    let settings = repo.get_settings().await;
+    let proxy_setting = settings.proxy.clone();
+    let ctx_length_setting = settings.context_length;
+    let max_token_setting = settings.max_tokens;
     // Try to retrieve the context length from the conversation's options.
     // If unsuccessful, fall back to the default context length setting.
     let context_length = serde_json::from_str(&options.options)
@@ -446,6 +927,15 @@ pub async fn call_bot(
     if let Some(sys_m) = sys_message {
         context.insert(0, sys_m);
     }
+    // Inject relevant chunks of the conversation's knowledge collections
+    knowledge::augment_context(
+        &repo,
+        conversation_id,
+        &mut context,
+        settings.knowledge_top_k,
+        proxy_setting.clone(),
+    )
+    .await;
     log::info!("bot calling context: {:?}", context);
     // delegate to one-off or stream function to send request
     let is_stream_enabled = is_stream_enabled(&options);
@@ -479,6 +969,39 @@ pub async fn call_bot(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn generate_image(
+    conversation_id: i32,
+    before_message_id: Option<i32>,
+    repo: State<'_, Repository>,
+) -> CommandResult<MessageDTO> {
+    let now = Instant::now();
+    let proxy_setting = repo.get_settings().await.proxy.clone();
+    let result =
+        image_generation::generate_reply(&repo, conversation_id, before_message_id, proxy_setting)
+            .await
+            .map_err(|message| ApiError { message })?;
+    MessageIndexer::global().notify();
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::generate_image]: {:.2?}", elapsed);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn semantic_search(
+    query: String,
+    limit: Option<usize>,
+    repo: State<'_, Repository>,
+) -> CommandResult<Vec<SemanticSearchResult>> {
+    let now = Instant::now();
+    let result = search::semantic_search(&repo, &query, limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+        .await
+        .map_err(|message| ApiError { message })?;
+    let elapsed = now.elapsed();
+    log::info!("[Timer][commands::semantic_search]: {:.2?}", elapsed);
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn create_prompt(
     new_prompt: NewPrompt,
@@ -497,14 +1020,157 @@ pub async fn create_prompt(
 }
 
 #[tauri::command]
-pub async fn list_prompts(repo: State<'_, Repository>) -> CommandResult<Vec<Prompt>> {
+pub async fn list_prompts(
+    category: Option<String>,
+    sort: Option<PromptSort>,
+    repo: State<'_, Repository>,
+) -> CommandResult<Vec<Prompt>> {
+    let result = repo
+        .list_prompts(category, sort.unwrap_or_default())
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn list_prompt_versions(
+    prompt_id: i32,
+    repo: State<'_, Repository>,
+) -> CommandResult<Vec<PromptVersion>> {
+    let result = repo
+        .list_prompt_versions(prompt_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn get_prompt_version(
+    prompt_version_id: i32,
+    repo: State<'_, Repository>,
+) -> CommandResult<PromptVersion> {
     let result = repo
-        .list_prompts()
+        .get_prompt_version_by_id(prompt_version_id)
         .await
         .map_err(|message| DbError { message })?;
     Ok(result)
 }
 
+/// Line diff between two versions of a prompt
+#[tauri::command]
+pub async fn diff_prompt_versions(
+    prompt_id: i32,
+    from_version: i32,
+    to_version: i32,
+    repo: State<'_, Repository>,
+) -> CommandResult<Vec<DiffLine>> {
+    let from = repo
+        .get_prompt_version(prompt_id, from_version)
+        .await
+        .map_err(|message| DbError { message })?;
+    let to = repo
+        .get_prompt_version(prompt_id, to_version)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(diff::diff_lines(&from.content, &to.content))
+}
+
+#[tauri::command]
+pub async fn rollback_prompt(
+    prompt_id: i32,
+    version: i32,
+    repo: State<'_, Repository>,
+) -> CommandResult<Prompt> {
+    let result = repo
+        .rollback_prompt(prompt_id, version)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn pin_prompt_version(
+    conversation_id: i32,
+    prompt_version_id: Option<i32>,
+    repo: State<'_, Repository>,
+) -> CommandResult<()> {
+    repo.set_conversation_prompt_version(conversation_id, prompt_version_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_prompt_categories(repo: State<'_, Repository>) -> CommandResult<Vec<String>> {
+    let result = repo
+        .list_prompt_categories()
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn record_prompt_usage(
+    prompt_id: i32,
+    repo: State<'_, Repository>,
+) -> CommandResult<()> {
+    repo.record_prompt_usage(prompt_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn export_prompts(
+    prompt_ids: Vec<i32>,
+    path: String,
+    repo: State<'_, Repository>,
+) -> CommandResult<usize> {
+    let result = prompt_library::export_prompts(&repo, prompt_ids, &path)
+        .await
+        .map_err(|message| ApiError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn import_prompts(
+    path: String,
+    on_conflict: Option<ImportConflict>,
+    repo: State<'_, Repository>,
+) -> CommandResult<ImportReport> {
+    let result = prompt_library::import_prompts(&repo, &path, on_conflict.unwrap_or_default())
+        .await
+        .map_err(|message| ApiError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn get_prompt_variables(
+    prompt_id: i32,
+    repo: State<'_, Repository>,
+) -> CommandResult<Vec<TemplateVariable>> {
+    let result = template::get_prompt_variables(&repo, prompt_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn render_prompt(
+    prompt_id: i32,
+    version: Option<i32>,
+    values: HashMap<String, String>,
+    model_id: Option<i32>,
+    conversation_id: Option<i32>,
+    repo: State<'_, Repository>,
+) -> CommandResult<String> {
+    let result =
+        template::render_prompt(&repo, prompt_id, version, values, model_id, conversation_id)
+            .await
+            .map_err(|message| ApiError { message })?;
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn update_prompt(prompt: Prompt, repo: State<'_, Repository>) -> CommandResult<Prompt> {
     let result = repo
@@ -523,6 +1189,192 @@ pub async fn delete_prompt(prompt_id: i32, repo: State<'_, Repository>) -> Comma
     Ok(result)
 }
 
+#[tauri::command]
+pub async fn create_preset(
+    new_preset: NewPreset,
+    repo: State<'_, Repository>,
+) -> CommandResult<Preset> {
+    let result = repo
+        .create_preset(new_preset)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn list_presets(repo: State<'_, Repository>) -> CommandResult<Vec<Preset>> {
+    let result = repo
+        .list_presets()
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn update_preset(preset: Preset, repo: State<'_, Repository>) -> CommandResult<Preset> {
+    let result = repo
+        .update_preset(preset)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn delete_preset(preset_id: i32, repo: State<'_, Repository>) -> CommandResult<()> {
+    repo.delete_preset(preset_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn create_conversation_from_preset(
+    preset_id: i32,
+    subject: Option<String>,
+    repo: State<'_, Repository>,
+) -> CommandResult<Conversation> {
+    let now = Instant::now();
+    let result = repo
+        .create_conversation_from_preset(preset_id, subject)
+        .await
+        .map_err(|message| DbError { message })?;
+    // the system message makes the conversation searchable
+    MessageIndexer::global().notify();
+    let elapsed = now.elapsed();
+    log::info!(
+        "[Timer][commands::create_conversation_from_preset]: {:.2?}",
+        elapsed
+    );
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn export_presets(
+    preset_ids: Vec<i32>,
+    path: String,
+    repo: State<'_, Repository>,
+) -> CommandResult<usize> {
+    let result = presets::export_presets(&repo, preset_ids, &path)
+        .await
+        .map_err(|message| ApiError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn import_presets(
+    path: String,
+    on_conflict: Option<ImportConflict>,
+    repo: State<'_, Repository>,
+) -> CommandResult<ImportReport> {
+    let result = presets::import_presets(&repo, &path, on_conflict.unwrap_or_default())
+        .await
+        .map_err(|message| ApiError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn create_collection(
+    new_collection: NewCollection,
+    repo: State<'_, Repository>,
+) -> CommandResult<Collection> {
+    // make sure the embedding model exists
+    repo.get_model(new_collection.model_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    let result = repo
+        .create_collection(new_collection)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn list_collections(repo: State<'_, Repository>) -> CommandResult<Vec<Collection>> {
+    let result = repo
+        .list_collections()
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn delete_collection(collection_id: i32, repo: State<'_, Repository>) -> CommandResult<()> {
+    repo.delete_collection(collection_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn add_documents_to_collection(
+    collection_id: i32,
+    paths: Vec<String>,
+    repo: State<'_, Repository>,
+) -> CommandResult<Vec<Document>> {
+    let log_tag = "add_documents_to_collection";
+    let now = Instant::now();
+    let collection = repo
+        .get_collection(collection_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    let proxy_setting = repo.get_settings().await.proxy.clone();
+    let mut result = vec![];
+    for path in paths {
+        let document = knowledge::ingest_document(&repo, &collection, &path, proxy_setting.clone())
+            .await
+            .map_err(|message| ApiError { message })?;
+        result.push(document);
+    }
+    let elapsed = now.elapsed();
+    info(log_tag, &format!("[Timer]: {:.2?}", elapsed));
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn list_documents(
+    collection_id: i32,
+    repo: State<'_, Repository>,
+) -> CommandResult<Vec<Document>> {
+    let result = repo
+        .list_documents(collection_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn delete_document(document_id: i32, repo: State<'_, Repository>) -> CommandResult<()> {
+    repo.delete_document(document_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_conversation_collections(
+    conversation_id: i32,
+    collection_ids: Vec<i32>,
+    repo: State<'_, Repository>,
+) -> CommandResult<Vec<Collection>> {
+    let result = repo
+        .set_conversation_collections(conversation_id, collection_ids)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn list_conversation_collections(
+    conversation_id: i32,
+    repo: State<'_, Repository>,
+) -> CommandResult<Vec<Collection>> {
+    let result = repo
+        .list_conversation_collections(conversation_id)
+        .await
+        .map_err(|message| DbError { message })?;
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn get_sys_info() -> CommandResult<serde_json::Value> {
     let mut sys = System::new_all();
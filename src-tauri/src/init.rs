@@ -1,8 +1,13 @@
 use std::fs;
 
 use crate::core::handle::Handle;
+use crate::services::cache;
 use crate::services::db::Builder as RepoBuilder;
 use crate::services::db::Repository;
+use crate::services::gc;
+use crate::services::search::MessageIndexer;
+use crate::services::trash;
+use crate::services::workspaces::{self, ActiveWorkspace, WorkspaceList};
 use crate::utils::convert_locale_region_to_script;
 use entity::entities::settings::Model as Setting;
 use entity::entities::settings::SETTING_DISPLAY_LANGUAGE;
@@ -17,6 +22,10 @@ pub fn init(app: &App) -> Result<(), String> {
     init_cache_dir(app)?;
     // Init settings
     init_settings(app)?;
+    // Start message indexer
+    init_indexer(app)?;
+    // Purge expired trash and collect garbage in cache dir
+    init_cache_gc(app)?;
 
     Ok(())
 }
@@ -41,7 +50,7 @@ fn init_db(app: &App) -> Result<(), String> {
         _app_data_dir.is_dir()
     );
     if !_app_data_dir.exists() {
-        if let Ok(()) = fs::create_dir(_app_data_dir) {
+        if let Ok(()) = fs::create_dir(&_app_data_dir) {
             log::info!("App data directory created at {}", &app_data_dir_str);
         } else {
             log::error!(
@@ -53,9 +62,20 @@ fn init_db(app: &App) -> Result<(), String> {
     }
 
     log::info!("App data path: {}", &app_data_dir_str);
+    // Open the database of the active workspace
+    let workspace_list = WorkspaceList::load(&_app_data_dir);
+    let workspace_dir = workspaces::workspace_dir(&_app_data_dir, workspace_list.active_id);
+    let db_path = workspaces::prepare_workspace_dir(&workspace_dir)?;
+    log::info!("Workspace path: {}", workspace_dir.display());
+    ActiveWorkspace::global().set(workspace_dir);
     // Init repo
     let repo = RepoBuilder::default()
-        .set_db_url(get_sqlite_path(&app_data_dir_str))
+        .set_db_url(
+            db_path
+                .to_str()
+                .expect("Workspace path is not a valid string!")
+                .to_string(),
+        )
         .build()?;
     // Run migrations
     repo.migrate()?;
@@ -66,10 +86,9 @@ fn init_db(app: &App) -> Result<(), String> {
 }
 
 // Initialize the cache dir for files such as images, pdfs, etc.
-fn init_cache_dir(app: &App) -> Result<(), String> {
-    // get app data path
-    let mut cache_dir = app.path().app_data_dir().expect("App data path does't exist!");
-    cache_dir.push("cache");
+fn init_cache_dir(_app: &App) -> Result<(), String> {
+    // cache dir of the active workspace
+    let cache_dir = cache::get_cache_dir()?;
     let cache_dir_str = cache_dir
         .to_str()
         .expect("Cache path is not a valid string!")
@@ -118,7 +137,35 @@ fn init_settings(app: &App) -> Result<(), String> {
     Ok(())
 }
 
-// Get the path where the database file should be located.
-fn get_sqlite_path(app_data_dir: &str) -> String {
-    app_data_dir.to_string() + "/database.sqlite"
+// Start the background task that embeds messages for semantic search
+fn init_indexer(app: &App) -> Result<(), String> {
+    let handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let repo = handle.state::<Repository>();
+        MessageIndexer::global().run(&repo).await;
+    });
+
+    Ok(())
+}
+
+// Purge items deleted longer ago than the trash retention period, then remove orphaned files of
+// the cache dir in the background, if enabled in settings
+fn init_cache_gc(app: &App) -> Result<(), String> {
+    let handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let repo = handle.state::<Repository>();
+        // the files of the cache dir are matched against the database of the same workspace
+        let _workspace = repo.hold_workspace().await;
+        if let Err(err) = trash::purge_expired_trash(&repo).await {
+            log::warn!("Failed to purge trash: {}", err);
+        }
+        if !gc::is_gc_on_startup_enabled(&repo).await {
+            return;
+        }
+        if let Err(err) = gc::collect_cache_garbage(&repo).await {
+            log::warn!("Failed to collect cache garbage: {}", err);
+        }
+    });
+
+    Ok(())
 }
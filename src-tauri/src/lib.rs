@@ -37,30 +37,91 @@ pub fn run() {
             commands::create_model,
             commands::list_models,
             commands::update_model,
+            commands::update_model_default_options,
             commands::delete_model,
             commands::list_remote_models,
+            commands::test_model_config,
             commands::list_settings,
             commands::upsert_setting,
+            commands::list_workspaces,
+            commands::create_workspace,
+            commands::rename_workspace,
+            commands::switch_workspace,
+            commands::delete_workspace,
             commands::create_conversation,
             commands::create_blank_conversation,
             commands::list_conversations,
+            commands::list_conversations_page,
             commands::delete_conversation,
+            commands::move_conversation,
+            commands::pin_conversation,
+            commands::archive_conversation,
+            commands::create_folder,
+            commands::list_folders,
+            commands::rename_folder,
+            commands::delete_folder,
+            commands::create_tag,
+            commands::list_tags,
+            commands::update_tag,
+            commands::delete_tag,
+            commands::set_conversation_tags,
+            commands::list_conversation_tags,
             commands::update_conversation,
             commands::get_options,
             commands::update_options,
             commands::update_subject,
             commands::update_conversation_model,
             commands::create_message,
+            commands::import_attachment,
+            commands::store_attachment,
+            commands::transcribe_message_audio,
+            commands::get_message_speech,
             commands::list_messages,
+            commands::list_messages_page,
             commands::get_system_message,
             commands::update_message,
+            commands::list_message_revisions,
+            commands::restore_message_revision,
             commands::hard_delete_messages,
             commands::hard_delete_message,
+            commands::collect_cache_garbage,
+            commands::list_trash,
+            commands::restore_conversation,
+            commands::restore_model,
+            commands::purge_trash,
             commands::call_bot,
+            commands::generate_image,
+            commands::semantic_search,
             commands::create_prompt,
             commands::list_prompts,
+            commands::list_prompt_versions,
+            commands::get_prompt_version,
+            commands::diff_prompt_versions,
+            commands::rollback_prompt,
+            commands::pin_prompt_version,
+            commands::list_prompt_categories,
+            commands::record_prompt_usage,
+            commands::export_prompts,
+            commands::import_prompts,
+            commands::get_prompt_variables,
+            commands::render_prompt,
             commands::update_prompt,
             commands::delete_prompt,
+            commands::create_preset,
+            commands::list_presets,
+            commands::update_preset,
+            commands::delete_preset,
+            commands::create_conversation_from_preset,
+            commands::export_presets,
+            commands::import_presets,
+            commands::create_collection,
+            commands::list_collections,
+            commands::delete_collection,
+            commands::add_documents_to_collection,
+            commands::list_documents,
+            commands::delete_document,
+            commands::set_conversation_collections,
+            commands::list_conversation_collections,
             commands::get_sys_info,
         ])
         .plugin(
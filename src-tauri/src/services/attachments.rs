@@ -0,0 +1,398 @@
+use std::{io::Cursor, path::Path};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use entity::entities::{
+    attachments::{Model as Attachment, NewAttachment},
+    contents::{ContentDTO, ContentType},
+    messages::MessageDTO,
+    models::Providers,
+};
+use image::{imageops::FilterType, DynamicImage, ImageFormat};
+use sha2::{Digest, Sha256};
+
+use crate::log_utils::warn;
+
+use super::{cache, db::Repository, documents};
+
+/// Max size of a file that can be attached to a message
+pub const MAX_ATTACHMENT_BYTES: u64 = 32 * 1024 * 1024;
+/// Max number of characters of an attachment sent as text
+pub const MAX_ATTACHMENT_TEXT_CHARS: usize = 400_000;
+/// Max size of a PDF sent as a native document block to Claude
+const CLAUDE_MAX_DOCUMENT_BYTES: u64 = 32 * 1024 * 1024;
+/// Max size of a PDF sent inline to Gemini
+const GOOGLE_MAX_DOCUMENT_BYTES: u64 = 20 * 1024 * 1024;
+/// Extension of the file storing the extracted text of an attachment
+const TEXT_SIDECAR_EXTENSION: &str = "txt";
+/// Sub-directory of the cache dir storing images pre-encoded for providers
+pub const VARIANTS_DIR: &str = "variants";
+/// Length of the hex encoded hash prefixing the names of stored files
+const HASH_LEN: usize = 64;
+
+/// Mimetype of a supported audio file, inferred from its extension
+pub fn get_audio_mimetype(path: &Path) -> Option<&'static str> {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "mp3" | "mpga" => Some("audio/mpeg"),
+        "wav" => Some("audio/wav"),
+        "m4a" | "mp4" => Some("audio/mp4"),
+        "ogg" | "oga" => Some("audio/ogg"),
+        "webm" => Some("audio/webm"),
+        "flac" => Some("audio/flac"),
+        _ => None,
+    }
+}
+
+/// Store a local file in the cache dir, extracting its text unless it's an audio file.
+/// The text is stored next to the file so that it isn't extracted again every time the message is sent.
+pub async fn import_attachment(repo: &Repository, path: &str) -> Result<ContentDTO, String> {
+    let path = Path::new(path);
+    let size = std::fs::metadata(path)
+        .map_err(|_| format!("Failed to read file {}", path.display()))?
+        .len();
+    if size > MAX_ATTACHMENT_BYTES {
+        return Err(format!(
+            "File {} exceeds the size limit of {} MB",
+            path.display(),
+            MAX_ATTACHMENT_BYTES / 1024 / 1024
+        ));
+    }
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or(format!("Invalid file path: {}", path.display()))?;
+    let bytes =
+        std::fs::read(path).map_err(|_| format!("Failed to read file {}", path.display()))?;
+    if let Some(mimetype) = get_audio_mimetype(path) {
+        let attachment = store_bytes(repo, bytes, &name, mimetype).await?;
+        return Ok(ContentDTO {
+            r#type: ContentType::Audio,
+            mimetype: Some(attachment.mimetype),
+            data: attachment.file_name,
+        });
+    }
+    let extracted = documents::extract_text(path)?;
+    let mimetype = extracted.mimetype.unwrap_or("text/plain".to_string());
+    let attachment = store_bytes(repo, bytes, &name, &mimetype).await?;
+    let mut cache_path = cache::get_cache_dir()?;
+    cache_path.push(sidecar_name(&attachment.file_name));
+    std::fs::write(cache_path, extracted.text)
+        .map_err(|_| format!("Failed to write text of file {} to cache", extracted.name))?;
+    Ok(ContentDTO {
+        r#type: if documents::is_rich_document(&mimetype) {
+            ContentType::Document
+        } else {
+            ContentType::File
+        },
+        mimetype: Some(mimetype),
+        data: attachment.file_name,
+    })
+}
+
+/// Move a file written to the cache dir by the frontend to its content-addressed location
+pub async fn store_cached_file(
+    repo: &Repository,
+    file_name: &str,
+    mimetype: &str,
+) -> Result<Attachment, String> {
+    let bytes = cache::read_bytes(file_name)?;
+    let attachment = store_bytes(repo, bytes, file_name, mimetype).await?;
+    if attachment.file_name != file_name {
+        let mut path = cache::get_cache_dir()?;
+        path.push(file_name);
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(attachment)
+}
+
+/// Store a file in the cache dir by the hash of its content.
+/// Identical files attached before are reused rather than stored again.
+pub async fn store_bytes(
+    repo: &Repository,
+    bytes: Vec<u8>,
+    name: &str,
+    mimetype: &str,
+) -> Result<Attachment, String> {
+    let hash = format!("{:x}", Sha256::digest(&bytes));
+    if let Some(attachment) = repo.get_attachment(&hash).await? {
+        let mut path = cache::get_cache_dir()?;
+        path.push(&attachment.file_name);
+        if !path.exists() {
+            // the file has been collected while its attachment was unreferenced
+            cache::write_bytes(&attachment.file_name, &bytes)?;
+        }
+        return Ok(attachment);
+    }
+    let file_name = format!("{}_{}", hash, display_name(name));
+    cache::write_bytes(&file_name, &bytes)?;
+    let (width, height) = if mimetype.starts_with("image/") {
+        match image::load_from_memory(&bytes) {
+            Ok(image) => (Some(image.width() as i32), Some(image.height() as i32)),
+            Err(_) => (None, None),
+        }
+    } else {
+        (None, None)
+    };
+    repo.create_attachment(NewAttachment {
+        hash,
+        file_name,
+        mimetype: mimetype.to_string(),
+        size: bytes.len() as i64,
+        width,
+        height,
+    })
+    .await
+}
+
+/// Hash of the content of a file stored by [store_bytes]
+pub fn hash_of(file_name: &str) -> Option<&str> {
+    file_name
+        .split_once('_')
+        .map(|(prefix, _)| prefix)
+        .filter(|prefix| prefix.len() == HASH_LEN && prefix.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Longest edge of images sent to a provider, larger images are downscaled before being sent
+pub fn max_image_edge(provider: &Providers) -> u32 {
+    match provider {
+        Providers::Claude => 1568,
+        Providers::Google => 3072,
+        Providers::Ollama => 1024,
+        _ => 2048,
+    }
+}
+
+/// Mimetype and base64 data of an image as sent to a provider, downscaled to fit its limit.
+/// The encoded variant is kept in cache so that the image isn't re-encoded on every turn.
+pub fn read_image(
+    file_name: &str,
+    mimetype: Option<&str>,
+    provider: &Providers,
+) -> Result<(String, String), String> {
+    let log_tag = "attachments::read_image";
+    let mut variant_path = cache::get_cache_dir()?;
+    variant_path.push(VARIANTS_DIR);
+    variant_path.push(variant_name(file_name, max_image_edge(provider)));
+    if let Ok(variant) = std::fs::read_to_string(&variant_path) {
+        if let Some((mimetype, data)) = variant.split_once('\n') {
+            return Ok((mimetype.to_string(), data.to_string()));
+        }
+    }
+    let bytes = cache::read_bytes(file_name)?;
+    let (mimetype, bytes) = match downscale(&bytes, max_image_edge(provider)) {
+        Some(downscaled) => downscaled,
+        None => {
+            let mimetype = match mimetype {
+                Some(mimetype) => mimetype.to_string(),
+                None => infer::get(&bytes)
+                    .map(|kind| kind.mime_type())
+                    .unwrap_or("application/octet-stream")
+                    .to_string(),
+            };
+            (mimetype, bytes)
+        }
+    };
+    let data = STANDARD.encode(bytes);
+    let written = variant_path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&variant_path, format!("{}\n{}", mimetype, data)));
+    if let Err(err) = written {
+        warn(
+            log_tag,
+            format!("Failed to cache variant of {}: {}", file_name, err),
+        );
+    }
+    Ok((mimetype, data))
+}
+
+/// Name of the variant of an image fitting in `max_edge` pixels
+fn variant_name(file_name: &str, max_edge: u32) -> String {
+    format!("{}@{}.b64", file_name, max_edge)
+}
+
+/// Name of the image a variant has been encoded from
+pub fn variant_source(variant_name: &str) -> Option<&str> {
+    variant_name.rsplit_once('@').map(|(source, _)| source)
+}
+
+/// Downscale an image so that its longest edge fits in `max_edge` pixels.
+/// Returns None if the image already fits or can't be decoded.
+fn downscale(bytes: &[u8], max_edge: u32) -> Option<(String, Vec<u8>)> {
+    let image = image::load_from_memory(bytes).ok()?;
+    if image.width().max(image.height()) <= max_edge {
+        return None;
+    }
+    let resized = image.resize(max_edge, max_edge, FilterType::Triangle);
+    let mut buffer = Cursor::new(Vec::new());
+    if resized.color().has_alpha() {
+        resized.write_to(&mut buffer, ImageFormat::Png).ok()?;
+        Some(("image/png".to_string(), buffer.into_inner()))
+    } else {
+        DynamicImage::ImageRgb8(resized.to_rgb8())
+            .write_to(&mut buffer, ImageFormat::Jpeg)
+            .ok()?;
+        Some(("image/jpeg".to_string(), buffer.into_inner()))
+    }
+}
+
+/// Name of the file storing the extracted text of an attachment
+pub fn sidecar_name(file_name: &str) -> String {
+    format!("{}.{}", file_name, TEXT_SIDECAR_EXTENSION)
+}
+
+/// Name of the attachment whose extracted text may be stored in a file
+pub fn sidecar_source(file_name: &str) -> Option<&str> {
+    file_name
+        .strip_suffix(TEXT_SIDECAR_EXTENSION)
+        .and_then(|name| name.strip_suffix('.'))
+}
+
+/// Original name of an attachment stored in cache
+pub fn display_name(file_name: &str) -> &str {
+    match file_name.split_once('_') {
+        Some((prefix, name))
+            if prefix.chars().all(|c| c.is_ascii_digit()) || hash_of(file_name).is_some() =>
+        {
+            name
+        }
+        _ => file_name,
+    }
+}
+
+/// Text of an attachment stored in cache, extracting it again if it hasn't been stored
+pub fn read_text(file_name: &str) -> Result<String, String> {
+    let mut path = cache::get_cache_dir()?;
+    path.push(file_name);
+    match std::fs::read_to_string(path.with_file_name(sidecar_name(file_name))) {
+        Ok(text) => Ok(text),
+        Err(_) => documents::extract_text(&path).map(|extracted| extracted.text),
+    }
+}
+
+/// Size in bytes of an attachment stored in cache
+pub fn file_size(file_name: &str) -> Result<u64, String> {
+    let mut path = cache::get_cache_dir()?;
+    path.push(file_name);
+    std::fs::metadata(path)
+        .map(|metadata| metadata.len())
+        .map_err(|_| format!("Failed to open file {} in cache", file_name))
+}
+
+/// Text part representing an attachment, for providers that don't accept documents natively
+pub fn to_text_part(item: &ContentDTO) -> String {
+    let name = display_name(&item.data);
+    match read_text(&item.data) {
+        Ok(text) => format!("<file name=\"{}\">\n{}\n</file>", name, text),
+        Err(err) => {
+            warn("attachments::to_text_part", err);
+            format!("<file name=\"{}\">\n[unreadable]\n</file>", name)
+        }
+    }
+}
+
+/// Max size of a PDF the provider accepts as a native document, None if it only accepts text
+pub fn native_document_limit(provider: &str) -> Option<u64> {
+    match Providers::from(provider) {
+        Providers::Claude => Some(CLAUDE_MAX_DOCUMENT_BYTES),
+        Providers::Google => Some(GOOGLE_MAX_DOCUMENT_BYTES),
+        _ => None,
+    }
+}
+
+/// Whether the attachment is sent to the provider as a native document block
+pub fn is_native_document(item: &ContentDTO, provider: &str) -> bool {
+    item.r#type == ContentType::Document
+        && item.mimetype.as_deref() == Some("application/pdf")
+        && native_document_limit(provider).is_some()
+}
+
+/// Make sure the attachments of messages are within the limits of the provider before sending them.
+/// Files missing from cache are skipped, they are sent as unreadable like in [to_text_part].
+pub fn check_limits(messages: &[MessageDTO], provider: &str) -> Result<(), String> {
+    let log_tag = "attachments::check_limits";
+    for item in messages.iter().flat_map(|message| message.content.iter()) {
+        if item.r#type == ContentType::Audio {
+            // audio is sent inline to Gemini
+            if !matches!(Providers::from(provider), Providers::Google) {
+                continue;
+            }
+            match file_size(&item.data) {
+                Ok(size) if size > GOOGLE_MAX_DOCUMENT_BYTES => {
+                    return Err(format!(
+                        "Audio {} exceeds the size limit of {} MB of {}",
+                        display_name(&item.data),
+                        GOOGLE_MAX_DOCUMENT_BYTES / 1024 / 1024,
+                        provider
+                    ));
+                }
+                Ok(_) => {}
+                Err(err) => warn(log_tag, err),
+            }
+            continue;
+        }
+        if item.r#type != ContentType::Document && item.r#type != ContentType::File {
+            continue;
+        }
+        let name = display_name(&item.data);
+        if is_native_document(item, provider) {
+            let limit = native_document_limit(provider).unwrap_or(MAX_ATTACHMENT_BYTES);
+            match file_size(&item.data) {
+                Ok(size) if size > limit => {
+                    return Err(format!(
+                        "Document {} exceeds the size limit of {} MB of {}",
+                        name,
+                        limit / 1024 / 1024,
+                        provider
+                    ));
+                }
+                Ok(_) => {}
+                Err(err) => warn(log_tag, err),
+            }
+        } else {
+            match read_text(&item.data) {
+                Ok(text) if text.chars().count() > MAX_ATTACHMENT_TEXT_CHARS => {
+                    return Err(format!(
+                        "Text of {} exceeds the limit of {} characters",
+                        name, MAX_ATTACHMENT_TEXT_CHARS
+                    ));
+                }
+                Ok(_) => {}
+                Err(err) => warn(log_tag, err),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_name() {
+        assert_eq!(display_name("1729238400000_report_v2.pdf"), "report_v2.pdf");
+        assert_eq!(display_name("report_v2.pdf"), "report_v2.pdf");
+        let stored = format!("{}_report_v2.pdf", "ab".repeat(32));
+        assert_eq!(display_name(&stored), "report_v2.pdf");
+        assert_eq!(hash_of(&stored), Some("ab".repeat(32).as_str()));
+    }
+
+    #[test]
+    fn test_variant_source() {
+        let name = variant_name("ab_photo@2x.png", 1568);
+        assert_eq!(variant_source(&name), Some("ab_photo@2x.png"));
+    }
+
+    #[test]
+    fn test_sidecar_source() {
+        assert_eq!(
+            sidecar_source(&sidecar_name("1_notes.txt")),
+            Some("1_notes.txt")
+        );
+        assert_eq!(sidecar_source("1_report.pdf"), None);
+    }
+}
@@ -5,23 +5,13 @@ use std::{io::Read, path::PathBuf};
 
 use crate::core::handle::Handle;
 
-pub fn read_as_data_url(file_name: &str, mimetype: Option<&str>) -> Result<String, String> {
-    let (mime, data) = read_as_base64_with_mime(file_name, mimetype)?;
-    let result = format!("data:{};base64,{}", mime, data);
-    Ok(result)
-}
+use super::workspaces::{self, ActiveWorkspace};
 
 pub fn read_as_base64_with_mime(
     file_name: &str,
     mimetype: Option<&str>,
 ) -> Result<(String, String), String> {
-    let mut file_path = get_cache_dir()?;
-    file_path.push(file_name);
-    let mut data = vec![];
-    std::fs::File::open(file_path)
-        .map_err(|_| format!("Failed to open file {} in cache", file_name))?
-        .read_to_end(&mut data)
-        .map_err(|_| format!("Failed to read file {} in cache", file_name))?;
+    let data = read_bytes(file_name)?;
     // Infer MIME from binary data
     let mime = match mimetype {
         Some(m) => m,
@@ -33,7 +23,31 @@ pub fn read_as_base64_with_mime(
     Ok((mime.to_string(), base64_str))
 }
 
+pub fn read_bytes(file_name: &str) -> Result<Vec<u8>, String> {
+    let mut file_path = get_cache_dir()?;
+    file_path.push(file_name);
+    let mut data = vec![];
+    std::fs::File::open(file_path)
+        .map_err(|_| format!("Failed to open file {} in cache", file_name))?
+        .read_to_end(&mut data)
+        .map_err(|_| format!("Failed to read file {} in cache", file_name))?;
+    Ok(data)
+}
+
+pub fn write_bytes(file_name: &str, data: &[u8]) -> Result<(), String> {
+    let mut file_path = get_cache_dir()?;
+    std::fs::create_dir_all(&file_path)
+        .map_err(|_| "Failed to create cache dir".to_string())?;
+    file_path.push(file_name);
+    std::fs::write(file_path, data)
+        .map_err(|_| format!("Failed to write file {} to cache", file_name))
+}
+
 pub fn get_cache_dir() -> Result<PathBuf, String> {
+    // cache dir of the active workspace
+    if let Some(workspace_dir) = ActiveWorkspace::global().dir() {
+        return Ok(workspaces::cache_dir(&workspace_dir));
+    }
     // get stored app handle
     let app_handle = Handle::global()
         .app_handle
@@ -1,38 +1,113 @@
-use entity::entities::contents::{self, ActiveModel as ActiveContent, Model as Content};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use entity::entities::attachments::{self, Model as Attachment, NewAttachment};
+use entity::entities::chunks::{self, Model as Chunk, NewChunk};
+use entity::entities::collections::{self, Model as Collection, NewCollection};
+use entity::entities::contents::{self, ActiveModel as ActiveContent, ContentDTO, Model as Content};
+use entity::entities::conversation_collections;
+use entity::entities::conversation_tags;
 use entity::entities::conversations::{
     self, ActiveModel as ActiveConversation, AzureOptions, ClaudeOptions, ConversationDTO,
-    ConversationDetailsDTO, GenericOptions, Model as Conversation, OllamaOptions, OpenAIOptions,
-    UpdateConversationDTO,
+    ConversationDetailsDTO, ConversationFilter, GenericOptions, ImageGenerationOptions,
+    Model as Conversation, OllamaOptions, OpenAIOptions, UpdateConversationDTO,
+    IMAGE_GENERATION_OPTIONS_KEY,
 };
+use entity::entities::documents::{self, Model as Document, NewDocument};
+use entity::entities::folders::{self, Model as Folder, NewFolder};
+use entity::entities::message_embeddings::{self, Model as MessageEmbedding};
+use entity::entities::message_revisions::{self, MessageRevisionDTO};
 use entity::entities::messages::{
     self, ActiveModel as ActiveMessage, MessageDTO, Model as Message,
 };
 use entity::entities::models::{self, GenericConfig, Model, NewModel, Providers};
-use entity::entities::prompts::{self, Model as Prompt, NewPrompt};
-use entity::entities::settings::{self, Model as Setting};
-use log::{error, info};
+use entity::entities::presets::{self, Model as Preset, NewPreset};
+use entity::entities::prompt_versions::{self, Model as PromptVersion};
+use entity::entities::prompts::{self, Model as Prompt, NewPrompt, PromptSort};
+use entity::entities::settings::{self, Model as Setting, Settings};
+use entity::entities::tags::{self, Model as Tag, NewTag};
+use entity::import::{unique_name, ImportConflict, ImportReport};
+use entity::pagination::{split_extra, ConversationCursor, Page};
+use log::{error, info, warn};
 use migration::{Migrator, MigratorTrait};
 use sea_orm::entity::ModelTrait;
 use sea_orm::{
     sea_query, ActiveModelTrait,
     ActiveValue::{self, Set},
-    ColumnTrait, Database, DatabaseConnection, EntityTrait, RelationTrait, TransactionTrait,
+    ColumnTrait, Condition, ConnectionTrait, Database, DatabaseConnection, EntityTrait,
+    RelationTrait, TransactionTrait,
 };
 use sea_orm::{
-    DbErr, IntoActiveModel, JoinType, LoaderTrait, Order, QueryFilter, QueryOrder, QuerySelect,
+    DbErr, IntoActiveModel, JoinType, LoaderTrait, Order, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect,
 };
 use sqlx::migrate::MigrateDatabase;
 
 type Db = sqlx::sqlite::Sqlite;
 
 pub struct Repository {
-    connection: DatabaseConnection,
+    /// Replaced when switching workspace
+    connection: RwLock<DatabaseConnection>,
+    /// Typed settings, cleared when a setting is written
+    settings: RwLock<Option<Arc<Settings>>>,
+    /// Bumped whenever the cached settings are cleared, so that settings read before aren't cached
+    settings_generation: AtomicU64,
+    /// Held by background jobs so that the workspace isn't switched while they run
+    workspace: tokio::sync::RwLock<()>,
 }
 
 impl Repository {
+    fn connection(&self) -> DatabaseConnection {
+        self.connection
+            .read()
+            .expect("Failed to lock database connection")
+            .clone()
+    }
+
+    /**
+     * Use the database of another repository from now on, eg. that of another workspace
+     */
+    pub fn replace(&self, other: Repository) {
+        let connection = other
+            .connection
+            .into_inner()
+            .expect("Failed to lock database connection");
+        *self
+            .connection
+            .write()
+            .expect("Failed to lock database connection") = connection;
+        self.clear_settings();
+    }
+
+    /**
+     * Clear the cached settings, settings being loaded meanwhile won't be cached
+     */
+    fn clear_settings(&self) {
+        let mut settings = self.settings.write().expect("Failed to lock settings");
+        self.settings_generation.fetch_add(1, Ordering::SeqCst);
+        *settings = None;
+    }
+
+    /**
+     * Keep using the current workspace until the guard is dropped, so that a background job
+     * reads from and writes to the same database and cache dir
+     */
+    pub async fn hold_workspace(&self) -> tokio::sync::RwLockReadGuard<'_, ()> {
+        self.workspace.read().await
+    }
+
+    /**
+     * Wait for background jobs holding the workspace to end, then prevent new ones from starting
+     * until the guard is dropped
+     */
+    pub async fn lock_workspace(&self) -> tokio::sync::RwLockWriteGuard<'_, ()> {
+        self.workspace.write().await
+    }
+
     pub fn migrate(&self) -> Result<(), String> {
         tauri::async_runtime::block_on(async move {
-            Migrator::up(&self.connection, None).await.map_err(|err| {
+            Migrator::up(&self.connection(), None).await.map_err(|err| {
                 error!("Failed to migrate database: {:?}", err);
                 format!("Failed to migrate database! Reason: {}", err)
             })?;
@@ -44,10 +119,12 @@ impl Repository {
     /**
      * Insert a new model
      */
-    pub async fn create_model(&self, new_model: NewModel) -> Result<Model, String> {
+    pub async fn create_model(&self, mut new_model: NewModel) -> Result<Model, String> {
+        new_model.default_options =
+            checked_default_options(&new_model.provider, new_model.default_options)?;
         let mut active_model = new_model.into_active_model();
         active_model.created_at = Set(Some(chrono::Local::now()));
-        let result = active_model.insert(&self.connection).await.map_err(|err| {
+        let result = active_model.insert(&self.connection()).await.map_err(|err| {
             error!("{}", err);
             "Failed to create model".to_string()
         })?;
@@ -61,7 +138,7 @@ impl Repository {
     pub async fn list_models(&self) -> Result<Vec<Model>, String> {
         let result: Vec<Model> = models::Entity::find()
             .filter(models::Column::DeletedAt.is_null())
-            .all(&self.connection)
+            .all(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -75,7 +152,7 @@ impl Repository {
      */
     pub async fn get_model(&self, model_id: i32) -> Result<Model, String> {
         let result = models::Entity::find_by_id(model_id)
-            .one(&self.connection)
+            .one(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -93,19 +170,39 @@ impl Repository {
         active_model.reset(models::Column::Alias); // mark alias as dirty
         active_model.reset(models::Column::Config); // mark config as dirty
         active_model.updated_at = Set(Some(chrono::Local::now()));
-        let result = active_model.update(&self.connection).await.map_err(|err| {
+        let result = active_model.update(&self.connection()).await.map_err(|err| {
             error!("{}", err);
             "Failed to update model".to_string()
         })?;
         Ok(result)
     }
 
+    /**
+     * Set the options merged into new conversations of a model, or clear them with `None`
+     */
+    pub async fn update_model_default_options(
+        &self,
+        model_id: i32,
+        default_options: Option<String>,
+    ) -> Result<Model, String> {
+        let model = self.get_model(model_id).await?;
+        let default_options = checked_default_options(&model.provider, default_options)?;
+        let mut active_model: models::ActiveModel = model.into();
+        active_model.default_options = Set(default_options);
+        active_model.updated_at = Set(Some(chrono::Local::now()));
+        let result = active_model.update(&self.connection()).await.map_err(|err| {
+            error!("{}", err);
+            format!("Failed to update default options of model with id {}", model_id)
+        })?;
+        Ok(result)
+    }
+
     /**
      * Soft delete a model
      */
     pub async fn delete_model(&self, model_id: i32) -> Result<Model, String> {
         let model = models::Entity::find_by_id(model_id)
-            .one(&self.connection)
+            .one(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -115,7 +212,7 @@ impl Repository {
         let mut active_model: models::ActiveModel = model.into();
         // Perform soft delete
         active_model.deleted_at = Set(Some(chrono::Local::now()));
-        let result = active_model.update(&self.connection).await.map_err(|err| {
+        let result = active_model.update(&self.connection()).await.map_err(|err| {
             error!("{}", err);
             "Failed to delete model".to_string()
         })?;
@@ -127,7 +224,7 @@ impl Repository {
      */
     pub async fn list_settings(&self) -> Result<Vec<settings::Model>, String> {
         let result = settings::Entity::find()
-            .all(&self.connection)
+            .all(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -137,9 +234,11 @@ impl Repository {
     }
 
     /**
-     * Update a setting, insert if it doesn't exist
+     * Update a setting, insert if it doesn't exist.
+     * The value is checked against the schema of the setting.
      */
     pub async fn upsert_setting(&self, setting: Setting) -> Result<Setting, String> {
+        settings::validate(&setting.key, &setting.value)?;
         let active_model: settings::ActiveModel = setting.clone().into();
         let _ = settings::Entity::insert(active_model)
             .on_conflict(
@@ -147,21 +246,52 @@ impl Repository {
                     .update_column(settings::Column::Value)
                     .to_owned(),
             )
-            .exec(&self.connection)
+            .exec(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
                 "Failed to upsert setting".to_string()
             })?;
+        self.clear_settings();
         Ok(setting)
     }
 
+    /**
+     * Get the typed settings, loaded from the database on the first call after a setting is written
+     */
+    pub async fn get_settings(&self) -> Arc<Settings> {
+        if let Some(settings) = self.settings.read().expect("Failed to lock settings").clone() {
+            return settings;
+        }
+        let generation = self.settings_generation.load(Ordering::SeqCst);
+        let mut result = Settings::default();
+        match self.list_settings().await {
+            Ok(list) => {
+                for setting in list {
+                    // stored before settings were validated
+                    if let Err(err) = result.set(&setting.key, &setting.value) {
+                        warn!("Ignoring setting {}: {}", setting.key, err);
+                    }
+                }
+            }
+            // not cached so that it is loaded again next time
+            Err(_) => return Arc::new(result),
+        }
+        let result = Arc::new(result);
+        let mut settings = self.settings.write().expect("Failed to lock settings");
+        // a setting written while loading makes the loaded settings stale
+        if self.settings_generation.load(Ordering::SeqCst) == generation {
+            *settings = Some(result.clone());
+        }
+        result
+    }
+
     /**
      * Get a setting by key
      */
     pub async fn get_setting(&self, key: &str) -> Option<Setting> {
         settings::Entity::find_by_id(key)
-            .one(&self.connection)
+            .one(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -181,34 +311,18 @@ impl Repository {
         active_model.id = ActiveValue::NotSet;
         if let Some(model_id) = conversation.model_id {
             let model = self.get_model(model_id).await?;
-            match model.provider.into() {
-                Providers::Azure => {
-                    let options_str = serde_json::to_string(&AzureOptions::default())
-                        .unwrap_or(String::default());
-                    active_model.options = Set(Some(options_str));
-                }
-                Providers::Claude => {
-                    let options_str = serde_json::to_string(&ClaudeOptions::default())
-                        .unwrap_or(String::default());
-                    active_model.options = Set(Some(options_str));
-                }
-                Providers::Ollama => {
-                    let options_str = serde_json::to_string(&OllamaOptions::default())
-                        .unwrap_or(String::default());
-                    active_model.options = Set(Some(options_str));
-                }
-                _ => {
-                    let options_str = serde_json::to_string(&OpenAIOptions::default())
-                        .unwrap_or(String::default());
-                    active_model.options = Set(Some(options_str));
-                }
-            }
+            // options of the conversation > default options of the model > defaults of the provider
+            let options = provider_options(
+                &model.provider,
+                &[model.default_options.as_deref(), conversation.options.as_deref()],
+            );
+            active_model.options = Set(Some(options));
         }
 
         active_model.created_at = Set(chrono::Local::now());
         // Set last message at to created at, so new conversation is shown at the top of the list
         active_model.last_message_at = Set(Some(chrono::Local::now()));
-        let result: Conversation = active_model.insert(&self.connection).await.map_err(|err| {
+        let result: Conversation = active_model.insert(&self.connection()).await.map_err(|err| {
             error!("{}", err);
             "Failed to create conversation".to_owned()
         })?;
@@ -226,34 +340,17 @@ impl Repository {
             .model_id
             .ok_or("Model id is missing".to_owned())?;
         let model = self.get_model(mode_id).await?;
+        let options = provider_options(
+            &model.provider,
+            &[model.default_options.as_deref(), conversation.options.as_deref()],
+        );
         let result = self
-            .connection
+            .connection()
             .transaction::<_, (Conversation, Message, Content), DbErr>(|txn| {
                 Box::pin(async move {
                     let mut conv_am: ActiveConversation = conversation.into();
                     conv_am.id = ActiveValue::NotSet;
-                    match model.provider.into() {
-                        Providers::Azure => {
-                            let options_str = serde_json::to_string(&AzureOptions::default())
-                                .unwrap_or(String::default());
-                            conv_am.options = Set(Some(options_str));
-                        }
-                        Providers::Claude => {
-                            let options_str = serde_json::to_string(&ClaudeOptions::default())
-                                .unwrap_or(String::default());
-                            conv_am.options = Set(Some(options_str));
-                        }
-                        Providers::Ollama => {
-                            let options_str = serde_json::to_string(&OllamaOptions::default())
-                                .unwrap_or(String::default());
-                            conv_am.options = Set(Some(options_str));
-                        }
-                        _ => {
-                            let options_str = serde_json::to_string(&OpenAIOptions::default())
-                                .unwrap_or(String::default());
-                            conv_am.options = Set(Some(options_str));
-                        }
-                    }
+                    conv_am.options = Set(Some(options));
                     conv_am.created_at = Set(chrono::Local::now());
                     // Set last message at to created at, so new conversation is shown at the top of the list
                     conv_am.last_message_at = Set(Some(chrono::Local::now()));
@@ -286,20 +383,23 @@ impl Repository {
     }
 
     /**
-     * List all conversations
+     * List conversations matching a filter, pinned ones first
      */
-    pub async fn list_conversations(&self) -> Result<Vec<ConversationDetailsDTO>, String> {
-        let result = conversations::Entity::find()
-            .filter(conversations::Column::DeletedAt.is_null())
+    pub async fn list_conversations(
+        &self,
+        filter: ConversationFilter,
+    ) -> Result<Vec<ConversationDetailsDTO>, String> {
+        let result = filter_conversations(filter)
             .join(JoinType::LeftJoin, conversations::Relation::Messages.def())
             .join(JoinType::LeftJoin, conversations::Relation::Models.def())
             .column_as(models::Column::Provider, "model_provider")
             .column_as(messages::Column::Id.count(), "message_count")
             .group_by(conversations::Column::Id)
+            .order_by(conversations::Column::Pinned, Order::Desc)
             .order_by(conversations::Column::LastMessageAt, Order::Desc)
             .order_by(conversations::Column::CreatedAt, Order::Desc)
             .into_model::<ConversationDetailsDTO>()
-            .all(&self.connection)
+            .all(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -308,6 +408,69 @@ impl Repository {
         Ok(result)
     }
 
+    /**
+     * List a page of conversations matching a filter, starting after a cursor.
+     * Conversations are sorted by pin, then by last activity, most recent first.
+     */
+    pub async fn list_conversations_page(
+        &self,
+        filter: ConversationFilter,
+        cursor: Option<ConversationCursor>,
+        page_size: u64,
+    ) -> Result<Page<ConversationDetailsDTO, ConversationCursor>, String> {
+        let query = filter_conversations(filter);
+        let total = query.clone().count(&self.connection()).await.map_err(|err| {
+            error!("{}", err);
+            "Failed to count conversations".to_string()
+        })?;
+        let mut query = query
+            .join(JoinType::LeftJoin, conversations::Relation::Messages.def())
+            .join(JoinType::LeftJoin, conversations::Relation::Models.def())
+            .column_as(models::Column::Provider, "model_provider")
+            .column_as(messages::Column::Id.count(), "message_count")
+            .group_by(conversations::Column::Id);
+        if let Some(cursor) = cursor {
+            // row values compare the sort keys in order, all of them being descending
+            query = query.filter(sea_query::Expr::cust_with_values(
+                format!("({}, {}, conversations.id) < (?, ?, ?)", CONVERSATION_PINNED_SQL, CONVERSATION_ACTIVITY_SQL),
+                [
+                    sea_query::Value::from(cursor.pinned),
+                    sea_query::Value::from(cursor.last_message_at),
+                    sea_query::Value::from(cursor.id),
+                ],
+            ));
+        }
+        let items = query
+            .order_by(sea_query::Expr::cust(CONVERSATION_PINNED_SQL), Order::Desc)
+            .order_by(sea_query::Expr::cust(CONVERSATION_ACTIVITY_SQL), Order::Desc)
+            .order_by(conversations::Column::Id, Order::Desc)
+            .limit(page_size + 1)
+            .into_model::<ConversationDetailsDTO>()
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to list conversations".to_string()
+            })?;
+        let (items, has_more) = split_extra(items, page_size, false);
+        let next_cursor = if has_more {
+            items.last().map(|conversation| ConversationCursor {
+                pinned: conversation.pinned,
+                last_message_at: conversation
+                    .last_message_at
+                    .unwrap_or(conversation.created_at),
+                id: conversation.id,
+            })
+        } else {
+            None
+        };
+        Ok(Page {
+            items,
+            total,
+            next_cursor,
+        })
+    }
+
     /**
      * Soft delete a conversation
      */
@@ -316,7 +479,7 @@ impl Repository {
         conversation_id: i32,
     ) -> Result<ConversationDTO, String> {
         let conv = conversations::Entity::find_by_id(conversation_id)
-            .one(&self.connection)
+            .one(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -329,7 +492,7 @@ impl Repository {
         let mut active_model: conversations::ActiveModel = conv.into();
         // Perform soft delete
         active_model.deleted_at = Set(Some(chrono::Local::now()));
-        let result = active_model.update(&self.connection).await.map_err(|err| {
+        let result = active_model.update(&self.connection()).await.map_err(|err| {
             error!("{}", err);
             format!(
                 "Failed to delete conversation with id = {}",
@@ -352,7 +515,7 @@ impl Repository {
             .join(JoinType::InnerJoin, conversations::Relation::Models.def())
             .column(models::Column::Provider)
             .into_model::<GenericOptions>()
-            .one(&self.connection)
+            .one(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -381,7 +544,7 @@ impl Repository {
             .column(models::Column::Provider)
             .column(models::Column::Config)
             .into_model::<GenericConfig>()
-            .one(&self.connection)
+            .one(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -406,7 +569,7 @@ impl Repository {
     ) -> Result<GenericOptions, String> {
         // Get conversation model
         let conversation = conversations::Entity::find_by_id(conversation_id)
-            .one(&self.connection)
+            .one(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -426,7 +589,7 @@ impl Repository {
             .select_only()
             .column(models::Column::Provider)
             .into_tuple()
-            .one(&self.connection)
+            .one(&self.connection())
             .await
             .map_err(|_| {
                 format!(
@@ -435,56 +598,11 @@ impl Repository {
                 )
             })?
             .unwrap_or(Providers::Unknown.into());
-        // Validate & set options string of active model
-        let options_str;
-        match provider.clone().into() {
-            Providers::Azure => {
-                // Deserialize & serialize the options as validation
-                let azure_options: AzureOptions = serde_json::from_str(&options)
-                    .unwrap_or_else(|err| {
-                        // record error and return default
-                        error!("db::update_conversation_options: Error deserializing Azure options: {}", err);
-                        AzureOptions::default()
-                    });
-                options_str = serde_json::to_string(&azure_options).unwrap_or(String::default());
-                c_am.options = Set(Some(options_str.clone()));
-            }
-            Providers::Claude => {
-                // Deserialize & serialize the options as validation
-                let claude_options: ClaudeOptions = serde_json::from_str(&options)
-                    .unwrap_or_else(|err| {
-                        // record error and return default
-                        error!("db::update_conversation_options: Error deserializing Claude options: {}", err);
-                        ClaudeOptions::default()
-                    });
-                options_str = serde_json::to_string(&claude_options).unwrap_or(String::default());
-                c_am.options = Set(Some(options_str.clone()));
-            }
-            Providers::Ollama => {
-                // Deserialize & serialize the options as validation
-                let ollama_options: OllamaOptions = serde_json::from_str(&options)
-                    .unwrap_or_else(|err| {
-                        // record error and return default
-                        error!("db::update_conversation_options: Error deserializing Ollama options: {}", err);
-                        OllamaOptions::default()
-                    });
-                options_str = serde_json::to_string(&ollama_options).unwrap_or(String::default());
-                c_am.options = Set(Some(options_str.clone()));
-            }
-            _ => {
-                // Deserialize & serialize the options as validation
-                let openai_options: OpenAIOptions = serde_json::from_str(&options)
-                    .unwrap_or_else(|err| {
-                        // record error and return default
-                        error!("db::update_conversation_options: Error deserializing OpenAI options: {}", err);
-                        OpenAIOptions::default()
-                    });
-                options_str = serde_json::to_string(&openai_options).unwrap_or(String::default());
-                c_am.options = Set(Some(options_str.clone()));
-            }
-        }
+        // Validate & set options string of active model, invalid options are replaced by the defaults
+        let options_str = conversation_options(&provider, &options);
+        c_am.options = Set(Some(options_str.clone()));
         // Update DB
-        c_am.update(&self.connection).await.map_err(|err| {
+        c_am.update(&self.connection()).await.map_err(|err| {
             error!("{}", err);
             format!(
                 "Failed to update options of conversation with id = {}",
@@ -498,20 +616,111 @@ impl Repository {
     }
 
     /**
-     * Update title of a conversation
+     * Move a conversation to a folder, or out of any folder
+     */
+    pub async fn move_conversation(
+        &self,
+        conversation_id: i32,
+        folder_id: Option<i32>,
+    ) -> Result<(), String> {
+        if let Some(folder_id) = folder_id {
+            self.get_folder(folder_id).await?;
+        }
+        self.update_conversation_column(
+            conversation_id,
+            conversations::Column::FolderId,
+            sea_query::Expr::value(folder_id),
+        )
+        .await
+    }
+
+    /**
+     * Pin a conversation at the top of the list, or unpin it
+     */
+    pub async fn set_conversation_pinned(
+        &self,
+        conversation_id: i32,
+        pinned: bool,
+    ) -> Result<(), String> {
+        self.update_conversation_column(
+            conversation_id,
+            conversations::Column::Pinned,
+            sea_query::Expr::value(pinned),
+        )
+        .await
+    }
+
+    /**
+     * Archive a conversation to hide it from the list, or unarchive it
+     */
+    pub async fn set_conversation_archived(
+        &self,
+        conversation_id: i32,
+        archived: bool,
+    ) -> Result<(), String> {
+        self.update_conversation_column(
+            conversation_id,
+            conversations::Column::Archived,
+            sea_query::Expr::value(archived),
+        )
+        .await
+    }
+
+    async fn update_conversation_column(
+        &self,
+        conversation_id: i32,
+        column: conversations::Column,
+        value: sea_query::SimpleExpr,
+    ) -> Result<(), String> {
+        let update_result = conversations::Entity::update_many()
+            .filter(conversations::Column::Id.eq(conversation_id))
+            .col_expr(column, value)
+            .exec(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!(
+                    "Failed to update conversation with id = {}",
+                    conversation_id
+                )
+            })?;
+        if update_result.rows_affected == 0 {
+            Err(format!(
+                "Conversation with id {} doesn't exist",
+                conversation_id
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /**
+     * Update title of a conversation.
+     * A title set by the user marks the subject as edited, a generated one only replaces a
+     * subject which isn't.
      */
     pub async fn update_conversation_subject(
         &self,
         conversation_id: i32,
         subject: String,
+        generated: bool,
     ) -> Result<String, String> {
-        let update_result = conversations::Entity::update_many()
+        let mut query = conversations::Entity::update_many()
             .filter(conversations::Column::Id.eq(conversation_id))
             .col_expr(
                 conversations::Column::Subject,
                 sea_query::Expr::value(&subject),
-            )
-            .exec(&self.connection)
+            );
+        if generated {
+            query = query.filter(conversations::Column::SubjectEdited.eq(false));
+        } else {
+            query = query.col_expr(
+                conversations::Column::SubjectEdited,
+                sea_query::Expr::value(true),
+            );
+        }
+        let update_result = query
+            .exec(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -521,10 +730,17 @@ impl Repository {
                 )
             })?;
         if update_result.rows_affected == 0 {
-            Err(format!(
-                "Conversation with id {} doesn't exist",
-                conversation_id
-            ))
+            if generated {
+                Err(format!(
+                    "Conversation with id {} doesn't exist or its subject was edited",
+                    conversation_id
+                ))
+            } else {
+                Err(format!(
+                    "Conversation with id {} doesn't exist",
+                    conversation_id
+                ))
+            }
         } else {
             Ok(subject)
         }
@@ -568,7 +784,7 @@ impl Repository {
             }
         }
         active_model.updated_at = Set(Some(chrono::Local::now()));
-        active_model.update(&self.connection).await.map_err(|err| {
+        active_model.update(&self.connection()).await.map_err(|err| {
             error!("{}", err);
             "Failed to update conversation".to_string()
         })?;
@@ -587,7 +803,7 @@ impl Repository {
         let conversation_id = conversation.id;
         let mut active_model = conversations::ActiveModel::from(conversation);
         active_model.updated_at = Set(Some(chrono::Local::now()));
-        active_model.update(&self.connection).await.map_err(|err| {
+        active_model.update(&self.connection()).await.map_err(|err| {
             error!("{}", err);
             "Failed to update conversation".to_string()
         })?;
@@ -595,6 +811,24 @@ impl Repository {
         self.get_conversation_details(conversation_id).await
     }
 
+    /**
+     * Get a conversation by id
+     */
+    pub async fn get_conversation(&self, conversation_id: i32) -> Result<Conversation, String> {
+        let result = conversations::Entity::find_by_id(conversation_id)
+            .one(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to get conversation with id {}", conversation_id)
+            })?
+            .ok_or(format!(
+                "Conversation with id {} doesn't exist",
+                conversation_id
+            ))?;
+        Ok(result)
+    }
+
     /**
      * Get details of a conversation
      */
@@ -609,7 +843,7 @@ impl Repository {
             .column_as(messages::Column::Id.count(), "message_count")
             .group_by(conversations::Column::Id)
             .into_model::<ConversationDetailsDTO>()
-            .one(&self.connection)
+            .one(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -639,7 +873,7 @@ impl Repository {
         let messages = query
             .cursor_by(messages::Column::Id)
             .last(n as u64)
-            .all(&self.connection)
+            .all(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -649,7 +883,7 @@ impl Repository {
                 )
             })?;
         let contents = messages
-            .load_many(contents::Entity, &self.connection)
+            .load_many(contents::Entity, &self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -671,14 +905,20 @@ impl Repository {
     pub async fn create_message(&self, message: MessageDTO) -> Result<MessageDTO, String> {
         let contents = message.content.clone();
         let conversation_id = message.conversation_id;
+        let is_bot = messages::Roles::from(message.role) == messages::Roles::Bot;
         let mut msg_am = message.into_active_model();
         msg_am.created_at = Set(chrono::Local::now());
         let result = self
-            .connection
+            .connection()
             .transaction::<_, MessageDTO, DbErr>(|txn| {
                 Box::pin(async move {
+                    if is_bot {
+                        msg_am.prompt_revision =
+                            Set(prompt_revision_of(txn, conversation_id, None).await?);
+                    }
                     // Insert message first
                     let msg_m = msg_am.insert(txn).await?;
+                    let file_names = file_names_of(&contents);
                     let ctnt_ams: Vec<contents::ActiveModel> = contents
                         .into_iter()
                         .map(|content| {
@@ -689,6 +929,7 @@ impl Repository {
                         .collect();
                     // Insert contents
                     contents::Entity::insert_many(ctnt_ams).exec(txn).await?;
+                    sync_attachment_refs(txn, file_names).await?;
                     // Retrieve newly inserted contents
                     let contents = msg_m.find_related(contents::Entity).all(txn).await?;
                     // Update conversation's last message at
@@ -725,7 +966,7 @@ impl Repository {
             .filter(messages::Column::ConversationId.eq(conversation_id))
             .filter(messages::Column::Role.ne(Into::<i32>::into(messages::Roles::System)))
             .filter(messages::Column::DeletedAt.is_null())
-            .all(&self.connection)
+            .all(&self.connection())
             .await
             // .unwrap();
             .map_err(|err| {
@@ -741,6 +982,70 @@ impl Repository {
         Ok(result)
     }
 
+    /**
+     * List a page of messages of a conversation, the most recent ones before a message id.
+     * Messages of a page are sorted from oldest to newest, the next cursor points to older messages.
+     */
+    pub async fn list_messages_page(
+        &self,
+        conversation_id: i32,
+        before_message_id: Option<i32>,
+        page_size: u64,
+    ) -> Result<Page<MessageDTO, i32>, String> {
+        let query = messages::Entity::find()
+            .filter(messages::Column::ConversationId.eq(conversation_id))
+            .filter(messages::Column::Role.ne(Into::<i32>::into(messages::Roles::System)))
+            .filter(messages::Column::DeletedAt.is_null());
+        let total = query.clone().count(&self.connection()).await.map_err(|err| {
+            error!("{}", err);
+            format!(
+                "Failed to count messages of conversation with id = {}",
+                conversation_id
+            )
+        })?;
+        let mut cursor = query.cursor_by(messages::Column::Id);
+        if let Some(before_message_id) = before_message_id {
+            cursor.before(before_message_id);
+        }
+        let messages = cursor
+            .last(page_size + 1)
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!(
+                    "Failed to list messages of conversation with id = {}",
+                    conversation_id
+                )
+            })?;
+        let (messages, has_more) = split_extra(messages, page_size, true);
+        let next_cursor = if has_more {
+            messages.first().map(|message| message.id)
+        } else {
+            None
+        };
+        let contents = messages
+            .load_many(contents::Entity, &self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!(
+                    "Failed to list contents of messages of conversation with id = {}",
+                    conversation_id
+                )
+            })?;
+        let items = messages
+            .into_iter()
+            .zip(contents.into_iter())
+            .map(MessageDTO::from)
+            .collect();
+        Ok(Page {
+            items,
+            total,
+            next_cursor,
+        })
+    }
+
     /**
      * Get the system message of a conversation
      */
@@ -752,7 +1057,7 @@ impl Repository {
             .find_with_related(contents::Entity)
             .filter(messages::Column::ConversationId.eq(conversation_id))
             .filter(messages::Column::Role.eq(Into::<i32>::into(messages::Roles::System)))
-            .all(&self.connection)
+            .all(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -765,21 +1070,113 @@ impl Repository {
         Ok(dto)
     }
 
+    /**
+     * Get a message with its contents
+     */
+    pub async fn get_message(&self, message_id: i32) -> Result<MessageDTO, String> {
+        let message = messages::Entity::find_by_id(message_id)
+            .one(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to get message with id = {}", message_id)
+            })?
+            .ok_or(format!("Message with id {} doesn't exist", message_id))?;
+        let contents = message
+            .find_related(contents::Entity)
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to get contents of message with id = {}", message_id)
+            })?;
+        Ok(MessageDTO::from((message, contents)))
+    }
+
     /**
      * Update the system message of a conversation
      */
     pub async fn update_message(&self, message: MessageDTO) -> Result<MessageDTO, String> {
+        self.save_message(message, None).await
+    }
+
+    /**
+     * Update a message, answering the given prompt revision if any.
+     * Otherwise a bot message answers the current revision of the prompt before it.
+     */
+    async fn save_message(
+        &self,
+        message: MessageDTO,
+        prompt_revision: Option<Option<i32>>,
+    ) -> Result<MessageDTO, String> {
         let message_id = message.id.ok_or("Message id is missing")?;
         let contents = message.content.clone();
+        let reasoning = message.reasoning.clone();
         let mut msg_am = message.into_active_model();
         msg_am.updated_at = Set(Some(chrono::Local::now()));
         let result = self
-            .connection
+            .connection()
             .transaction::<_, MessageDTO, DbErr>(|txn| {
                 Box::pin(async move {
+                    // Keep the current version of the message as a revision, unless it's unchanged
+                    let current = messages::Entity::find_by_id(message_id)
+                        .find_with_related(contents::Entity)
+                        .all(txn)
+                        .await?
+                        .pop()
+                        .ok_or(DbErr::RecordNotFound(format!(
+                            "Message with id {} doesn't exist",
+                            message_id
+                        )))?;
+                    let (current, current_contents) = current;
+                    let current_contents: Vec<ContentDTO> =
+                        current_contents.into_iter().map(ContentDTO::from).collect();
+                    if current_contents != contents
+                        || (reasoning.is_some() && current.reasoning != reasoning)
+                    {
+                        message_revisions::ActiveModel {
+                            message_id: Set(message_id),
+                            revision: Set(current.revision),
+                            contents: Set(serde_json::to_string(&current_contents)
+                                .map_err(|err| DbErr::Custom(err.to_string()))?),
+                            reasoning: Set(current.reasoning.clone()),
+                            prompt_token: Set(current.prompt_token),
+                            completion_token: Set(current.completion_token),
+                            reasoning_token: Set(current.reasoning_token),
+                            total_token: Set(current.total_token),
+                            created_at: Set(current.updated_at.unwrap_or(current.created_at)),
+                            prompt_revision: Set(current.prompt_revision),
+                            ..Default::default()
+                        }
+                        .insert(txn)
+                        .await?;
+                        msg_am.revision = Set(current.revision + 1);
+                    }
+                    if let Some(prompt_revision) = prompt_revision {
+                        msg_am.prompt_revision = Set(prompt_revision);
+                    } else if messages::Roles::from(current.role) == messages::Roles::Bot {
+                        msg_am.prompt_revision = Set(prompt_revision_of(
+                            txn,
+                            current.conversation_id,
+                            Some(message_id),
+                        )
+                        .await?);
+                    }
                     // Update messge first
                     let msg_m = msg_am.update(txn).await?;
+                    // Delete the embedding so that the message is re-indexed
+                    message_embeddings::Entity::delete_by_id(msg_m.id)
+                        .exec(txn)
+                        .await?;
                     // Delete old content (hard delete)
+                    let old_contents = msg_m.find_related(contents::Entity).all(txn).await?;
+                    let mut file_names = file_names_of(&contents);
+                    file_names.extend(
+                        old_contents
+                            .into_iter()
+                            .filter(|content| content.r#type.is_file())
+                            .map(|content| content.data),
+                    );
                     contents::Entity::delete_many()
                         .filter(contents::Column::MessageId.eq(msg_m.id))
                         .exec(txn)
@@ -794,6 +1191,7 @@ impl Repository {
                         })
                         .collect();
                     contents::Entity::insert_many(ctnt_ams).exec(txn).await?;
+                    sync_attachment_refs(txn, file_names).await?;
                     // Retrieve newly inserted contents
                     let contents = msg_m.find_related(contents::Entity).all(txn).await?;
                     // Return DTO
@@ -813,7 +1211,7 @@ impl Repository {
         // // active_model.reset(messages::Column::Content);
         // active_model.updated_at = Set(Some(chrono::Local::now()));
         // let result = active_model
-        //     .update(&self.connection)
+        //     .update(&self.connection())
         //     .await
         //     .map_err(|err| {
         //         error!("{}", err);
@@ -822,13 +1220,87 @@ impl Repository {
         Ok(result)
     }
 
+    /**
+     * List the previous versions of a message, most recent first
+     */
+    pub async fn list_message_revisions(
+        &self,
+        message_id: i32,
+    ) -> Result<Vec<MessageRevisionDTO>, String> {
+        let result = message_revisions::Entity::find()
+            .filter(message_revisions::Column::MessageId.eq(message_id))
+            .order_by(message_revisions::Column::Revision, Order::Desc)
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to list revisions of message with id = {}", message_id)
+            })?
+            .into_iter()
+            .map(MessageRevisionDTO::from)
+            .collect();
+        Ok(result)
+    }
+
+    /**
+     * Restore a previous version of a message.
+     * The version being replaced is kept as a revision, so that restoring can be undone.
+     */
+    pub async fn restore_message_revision(
+        &self,
+        message_id: i32,
+        revision: i32,
+    ) -> Result<MessageDTO, String> {
+        let message_revision = message_revisions::Entity::find()
+            .filter(message_revisions::Column::MessageId.eq(message_id))
+            .filter(message_revisions::Column::Revision.eq(revision))
+            .one(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!(
+                    "Failed to get revision {} of message with id = {}",
+                    revision, message_id
+                )
+            })?
+            .ok_or(format!(
+                "Revision {} of message with id {} doesn't exist",
+                revision, message_id
+            ))?;
+        let message = self.get_message(message_id).await?;
+        // the restored version still answers the prompt it answered then
+        let prompt_revision = message_revision.prompt_revision;
+        self.save_message(
+            MessageDTO {
+                content: message_revision.get_contents(),
+                reasoning: message_revision.reasoning,
+                prompt_token: message_revision.prompt_token,
+                completion_token: message_revision.completion_token,
+                reasoning_token: message_revision.reasoning_token,
+                total_token: message_revision.total_token,
+                ..message
+            },
+            Some(prompt_revision),
+        )
+        .await
+    }
+
     /**
      * Hard delete all messages of a conversation
      */
     pub async fn hard_delete_messages(&self, conversation_id: i32) -> Result<(), String> {
-        messages::Entity::delete_many()
-            .filter(messages::Column::ConversationId.eq(conversation_id))
-            .exec(&self.connection)
+        let file_names = self.list_content_files(Some(conversation_id)).await?;
+        self.connection()
+            .transaction::<_, (), DbErr>(|txn| {
+                Box::pin(async move {
+                    // contents are deleted by cascade
+                    messages::Entity::delete_many()
+                        .filter(messages::Column::ConversationId.eq(conversation_id))
+                        .exec(txn)
+                        .await?;
+                    sync_attachment_refs(txn, file_names).await
+                })
+            })
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -845,8 +1317,26 @@ impl Repository {
      */
     pub async fn hard_delete_message(&self, message: MessageDTO) -> Result<MessageDTO, String> {
         let message_id = message.id.ok_or("Message id is missing")?;
-        messages::Entity::delete_by_id(message_id)
-            .exec(&self.connection)
+        self.connection()
+            .transaction::<_, (), DbErr>(|txn| {
+                Box::pin(async move {
+                    let contents = contents::Entity::find()
+                        .filter(contents::Column::MessageId.eq(message_id))
+                        .all(txn)
+                        .await?;
+                    // contents are deleted by cascade
+                    messages::Entity::delete_by_id(message_id).exec(txn).await?;
+                    sync_attachment_refs(
+                        txn,
+                        contents
+                            .into_iter()
+                            .filter(|content| content.r#type.is_file())
+                            .map(|content| content.data)
+                            .collect(),
+                    )
+                    .await
+                })
+            })
             .await
             .map_err(|err| {
                 error!("{}", err);
@@ -859,66 +1349,1834 @@ impl Repository {
      * Insert a new prompt
      */
     pub async fn create_prompt(&self, new_prompt: NewPrompt) -> Result<Prompt, String> {
-        let mut active_model = new_prompt.into_active_model();
-        active_model.created_at = Set(chrono::Local::now());
-        let result = active_model.insert(&self.connection).await.map_err(|err| {
-            error!("{}", err);
-            "Failed to create new prompt".to_string()
-        })?;
-        Ok(result)
-    }
+        let result = self
+            .connection()
+            .transaction::<_, Prompt, DbErr>(|txn| {
+                Box::pin(async move { insert_prompt(txn, new_prompt).await })
+            })
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to create new prompt".to_string()
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * List all prompts, optionally of a category
+     */
+    pub async fn list_prompts(
+        &self,
+        category: Option<String>,
+        sort: PromptSort,
+    ) -> Result<Vec<Prompt>, String> {
+        let mut query = prompts::Entity::find().filter(prompts::Column::DeletedAt.is_null());
+        if let Some(category) = category {
+            query = query.filter(prompts::Column::Category.eq(category));
+        }
+        query = match sort {
+            PromptSort::Created => query.order_by_asc(prompts::Column::Id),
+            PromptSort::Alias => query.order_by_asc(prompts::Column::Alias),
+            PromptSort::Popularity => query
+                .order_by_desc(prompts::Column::UsageCount)
+                .order_by_desc(prompts::Column::LastUsedAt),
+            PromptSort::LastUsed => query
+                .order_by(
+                    sea_query::Expr::cust("prompts.last_used_at IS NULL"),
+                    Order::Asc,
+                )
+                .order_by_desc(prompts::Column::LastUsedAt),
+        };
+        let result = query
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to list prompts".to_string()
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * Get a prompt by id
+     */
+    pub async fn get_prompt(&self, prompt_id: i32) -> Result<Prompt, String> {
+        let result = prompts::Entity::find_by_id(prompt_id)
+            .filter(prompts::Column::DeletedAt.is_null())
+            .one(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to get prompt with id {}", prompt_id)
+            })?
+            .ok_or(format!("Prompt with id {} doesn't exist", prompt_id))?;
+        Ok(result)
+    }
+
+    /**
+     * Update a prompt, recording a new version if its alias or content changed
+     */
+    pub async fn update_prompt(&self, prompt: Prompt) -> Result<Prompt, String> {
+        let prompt_id = prompt.id;
+        let result = self
+            .connection()
+            .transaction::<_, Prompt, DbErr>(|txn| {
+                Box::pin(async move {
+                    let current = prompts::Entity::find_by_id(prompt_id)
+                        .one(txn)
+                        .await?
+                        .ok_or(DbErr::RecordNotFound(format!(
+                            "Prompt with id {} doesn't exist",
+                            prompt_id
+                        )))?;
+                    let changed = current.alias != prompt.alias || current.content != prompt.content;
+                    let mut active_model: prompts::ActiveModel = prompt.into();
+                    active_model.reset(prompts::Column::Alias); // mark alias as dirty
+                    active_model.reset(prompts::Column::Content); // mark content as dirty
+                    active_model.reset(prompts::Column::Category);
+                    active_model.reset(prompts::Column::Tags);
+                    active_model.reset(prompts::Column::Description);
+                    update_prompt_versioned(txn, &current, active_model, changed).await
+                })
+            })
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to update prompt".to_string()
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * List the versions of a prompt, most recent first
+     */
+    pub async fn list_prompt_versions(&self, prompt_id: i32) -> Result<Vec<PromptVersion>, String> {
+        let result = prompt_versions::Entity::find()
+            .filter(prompt_versions::Column::PromptId.eq(prompt_id))
+            .order_by(prompt_versions::Column::Version, Order::Desc)
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to list versions of prompt with id = {}", prompt_id)
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * Get a version of a prompt
+     */
+    pub async fn get_prompt_version(
+        &self,
+        prompt_id: i32,
+        version: i32,
+    ) -> Result<PromptVersion, String> {
+        let result = prompt_versions::Entity::find()
+            .filter(prompt_versions::Column::PromptId.eq(prompt_id))
+            .filter(prompt_versions::Column::Version.eq(version))
+            .one(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!(
+                    "Failed to get version {} of prompt with id {}",
+                    version, prompt_id
+                )
+            })?
+            .ok_or(format!(
+                "Version {} of prompt with id {} doesn't exist",
+                version, prompt_id
+            ))?;
+        Ok(result)
+    }
+
+    /**
+     * Get a version of a prompt by its id
+     */
+    pub async fn get_prompt_version_by_id(
+        &self,
+        prompt_version_id: i32,
+    ) -> Result<PromptVersion, String> {
+        let result = prompt_versions::Entity::find_by_id(prompt_version_id)
+            .one(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to get prompt version with id {}", prompt_version_id)
+            })?
+            .ok_or(format!(
+                "Prompt version with id {} doesn't exist",
+                prompt_version_id
+            ))?;
+        Ok(result)
+    }
+
+    /**
+     * Bring back the wording of a previous version of a prompt, as a new version
+     */
+    pub async fn rollback_prompt(&self, prompt_id: i32, version: i32) -> Result<Prompt, String> {
+        let prompt_version = self.get_prompt_version(prompt_id, version).await?;
+        let prompt = self.get_prompt(prompt_id).await?;
+        self.update_prompt(Prompt {
+            alias: prompt_version.alias,
+            content: prompt_version.content,
+            ..prompt
+        })
+        .await
+    }
+
+    /**
+     * Pin a version of a prompt to a conversation, or unpin it
+     */
+    pub async fn set_conversation_prompt_version(
+        &self,
+        conversation_id: i32,
+        prompt_version_id: Option<i32>,
+    ) -> Result<(), String> {
+        if let Some(prompt_version_id) = prompt_version_id {
+            self.get_prompt_version_by_id(prompt_version_id).await?;
+        }
+        self.update_conversation_column(
+            conversation_id,
+            conversations::Column::PromptVersionId,
+            sea_query::Expr::value(prompt_version_id),
+        )
+        .await
+    }
+
+    /**
+     * List the categories of prompts, in alphabetical order
+     */
+    pub async fn list_prompt_categories(&self) -> Result<Vec<String>, String> {
+        let result = prompts::Entity::find()
+            .select_only()
+            .column(prompts::Column::Category)
+            .distinct()
+            .filter(prompts::Column::DeletedAt.is_null())
+            .filter(prompts::Column::Category.is_not_null())
+            .order_by_asc(prompts::Column::Category)
+            .into_tuple()
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to list categories of prompts".to_string()
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * Count a use of a prompt
+     */
+    pub async fn record_prompt_usage(&self, prompt_id: i32) -> Result<(), String> {
+        prompts::Entity::update_many()
+            .col_expr(
+                prompts::Column::UsageCount,
+                sea_query::Expr::col(prompts::Column::UsageCount).add(1),
+            )
+            .col_expr(
+                prompts::Column::LastUsedAt,
+                sea_query::Expr::value(chrono::Local::now()),
+            )
+            .filter(prompts::Column::Id.eq(prompt_id))
+            .exec(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to record usage of prompt with id = {}", prompt_id)
+            })?;
+        Ok(())
+    }
+
+    /**
+     * Get prompts by ids, in the order of creation
+     */
+    pub async fn get_prompts(&self, prompt_ids: Vec<i32>) -> Result<Vec<Prompt>, String> {
+        let result = prompts::Entity::find()
+            .filter(prompts::Column::Id.is_in(prompt_ids))
+            .filter(prompts::Column::DeletedAt.is_null())
+            .order_by_asc(prompts::Column::Id)
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to get prompts".to_string()
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * Insert prompts, handling those whose alias is already used as instructed
+     */
+    pub async fn import_prompts(
+        &self,
+        new_prompts: Vec<NewPrompt>,
+        on_conflict: ImportConflict,
+    ) -> Result<ImportReport, String> {
+        let result = self
+            .connection()
+            .transaction::<_, ImportReport, DbErr>(|txn| {
+                Box::pin(async move {
+                    let mut report = ImportReport::default();
+                    let mut existing: HashMap<String, Prompt> = prompts::Entity::find()
+                        .filter(prompts::Column::DeletedAt.is_null())
+                        .all(txn)
+                        .await?
+                        .into_iter()
+                        .map(|prompt| (prompt.alias.clone(), prompt))
+                        .collect();
+                    for mut new_prompt in new_prompts {
+                        if let Some(prompt) = existing.get(&new_prompt.alias) {
+                            match on_conflict {
+                                ImportConflict::Skip => {
+                                    report.skipped += 1;
+                                    continue;
+                                }
+                                ImportConflict::Overwrite => {
+                                    let changed = prompt.content != new_prompt.content;
+                                    let mut active_model: prompts::ActiveModel =
+                                        prompt.clone().into();
+                                    active_model.content = Set(new_prompt.content);
+                                    active_model.category = Set(new_prompt.category);
+                                    active_model.tags = Set(new_prompt.tags);
+                                    active_model.description = Set(new_prompt.description);
+                                    let prompt =
+                                        update_prompt_versioned(txn, prompt, active_model, changed)
+                                            .await?;
+                                    existing.insert(prompt.alias.clone(), prompt);
+                                    report.overwritten += 1;
+                                    continue;
+                                }
+                                ImportConflict::Rename => {
+                                    new_prompt.alias = unique_name(&new_prompt.alias, |alias| {
+                                        existing.contains_key(alias)
+                                    });
+                                    report.renamed += 1;
+                                }
+                            }
+                        } else {
+                            report.imported += 1;
+                        }
+                        let prompt = insert_prompt(txn, new_prompt).await?;
+                        existing.insert(prompt.alias.clone(), prompt);
+                    }
+                    Ok(report)
+                })
+            })
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to import prompts".to_string()
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * Soft delete a prompt
+     */
+    pub async fn delete_prompt(&self, prompt_id: i32) -> Result<Prompt, String> {
+        let prompt = prompts::Entity::find_by_id(prompt_id)
+            .one(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to get prompt with id {}", prompt_id)
+            })?
+            .ok_or(format!("Prompt with id {} doesn't exist", prompt_id))?;
+        let mut active_model: prompts::ActiveModel = prompt.clone().into();
+        // Perform soft delete
+        active_model.deleted_at = Set(Some(chrono::Local::now()));
+        let result = active_model.update(&self.connection()).await.map_err(|err| {
+            error!("{}", err);
+            format!("Failed to delete prompt with id = {}", prompt_id)
+        })?;
+        Ok(result)
+    }
+
+    /**
+     * Insert a new preset
+     */
+    pub async fn create_preset(&self, new_preset: NewPreset) -> Result<Preset, String> {
+        let mut active_model = new_preset.into_active_model();
+        active_model.created_at = Set(chrono::Local::now());
+        let result = active_model.insert(&self.connection()).await.map_err(|err| {
+            error!("{}", err);
+            "Failed to create new preset".to_string()
+        })?;
+        Ok(result)
+    }
+
+    /**
+     * List all presets, in alphabetical order
+     */
+    pub async fn list_presets(&self) -> Result<Vec<Preset>, String> {
+        let result = presets::Entity::find()
+            .order_by_asc(presets::Column::Name)
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to list presets".to_string()
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * Get a preset by id
+     */
+    pub async fn get_preset(&self, preset_id: i32) -> Result<Preset, String> {
+        let result = presets::Entity::find_by_id(preset_id)
+            .one(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to get preset with id {}", preset_id)
+            })?
+            .ok_or(format!("Preset with id {} doesn't exist", preset_id))?;
+        Ok(result)
+    }
+
+    /**
+     * Get presets by ids, in alphabetical order
+     */
+    pub async fn get_presets(&self, preset_ids: Vec<i32>) -> Result<Vec<Preset>, String> {
+        let result = presets::Entity::find()
+            .filter(presets::Column::Id.is_in(preset_ids))
+            .order_by_asc(presets::Column::Name)
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to get presets".to_string()
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * Update a preset
+     */
+    pub async fn update_preset(&self, preset: Preset) -> Result<Preset, String> {
+        let mut active_model: presets::ActiveModel = preset.into();
+        for column in [
+            presets::Column::Name,
+            presets::Column::Description,
+            presets::Column::ModelId,
+            presets::Column::SystemPrompt,
+            presets::Column::PromptId,
+            presets::Column::Options,
+            presets::Column::CollectionIds,
+            presets::Column::Tools,
+        ] {
+            active_model.reset(column);
+        }
+        active_model.updated_at = Set(Some(chrono::Local::now()));
+        let result = active_model.update(&self.connection()).await.map_err(|err| {
+            error!("{}", err);
+            "Failed to update preset".to_string()
+        })?;
+        Ok(result)
+    }
+
+    /**
+     * Delete a preset, conversations created from it are kept
+     */
+    pub async fn delete_preset(&self, preset_id: i32) -> Result<(), String> {
+        presets::Entity::delete_by_id(preset_id)
+            .exec(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to delete preset with id = {}", preset_id)
+            })?;
+        Ok(())
+    }
+
+    /**
+     * Insert presets, handling those whose name is already used as instructed
+     */
+    pub async fn import_presets(
+        &self,
+        new_presets: Vec<NewPreset>,
+        on_conflict: ImportConflict,
+    ) -> Result<ImportReport, String> {
+        let result = self
+            .connection()
+            .transaction::<_, ImportReport, DbErr>(|txn| {
+                Box::pin(async move {
+                    let mut report = ImportReport::default();
+                    let mut existing: HashMap<String, Preset> = presets::Entity::find()
+                        .all(txn)
+                        .await?
+                        .into_iter()
+                        .map(|preset| (preset.name.clone(), preset))
+                        .collect();
+                    for mut new_preset in new_presets {
+                        if let Some(preset) = existing.get(&new_preset.name) {
+                            match on_conflict {
+                                ImportConflict::Skip => {
+                                    report.skipped += 1;
+                                    continue;
+                                }
+                                ImportConflict::Overwrite => {
+                                    let mut active_model = new_preset.into_active_model();
+                                    active_model.id = Set(preset.id);
+                                    active_model.updated_at = Set(Some(chrono::Local::now()));
+                                    let preset = active_model.update(txn).await?;
+                                    existing.insert(preset.name.clone(), preset);
+                                    report.overwritten += 1;
+                                    continue;
+                                }
+                                ImportConflict::Rename => {
+                                    new_preset.name = unique_name(&new_preset.name, |name| {
+                                        existing.contains_key(name)
+                                    });
+                                    report.renamed += 1;
+                                }
+                            }
+                        } else {
+                            report.imported += 1;
+                        }
+                        let mut active_model = new_preset.into_active_model();
+                        active_model.created_at = Set(chrono::Local::now());
+                        let preset = active_model.insert(txn).await?;
+                        existing.insert(preset.name.clone(), preset);
+                    }
+                    Ok(report)
+                })
+            })
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to import presets".to_string()
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * Create a conversation set up as described by a preset: model, options checked against
+     * the provider of the model, system message and attached collections, all at once
+     */
+    pub async fn create_conversation_from_preset(
+        &self,
+        preset_id: i32,
+        subject: Option<String>,
+    ) -> Result<Conversation, String> {
+        let preset = self.get_preset(preset_id).await?;
+        let model_id = preset
+            .model_id
+            .ok_or(format!("Preset {} has no model", preset.name))?;
+        let model = self.get_model(model_id).await?;
+        if model.deleted_at.is_some() {
+            return Err(format!("Model {} of preset {} is deleted", model.alias, preset.name));
+        }
+        // a system prompt written in the preset takes precedence over a prompt
+        let system_prompt = match preset.system_prompt.clone().filter(|s| !s.trim().is_empty()) {
+            Some(system_prompt) => Some(system_prompt),
+            None => match preset.prompt_id {
+                Some(prompt_id) => self.get_prompt(prompt_id).await.ok().map(|p| p.content),
+                None => None,
+            },
+        };
+        // options of the preset > default options of the model > defaults of the provider
+        let options = provider_options(
+            &model.provider,
+            &[model.default_options.as_deref(), preset.options.as_deref()],
+        );
+        let subject = subject.filter(|subject| !subject.trim().is_empty());
+        // a subject given by the user is kept, the name of the preset may be replaced by a title
+        let subject_edited = subject.is_some();
+        let subject = subject.unwrap_or_else(|| preset.name.clone());
+        let collection_ids = preset.collection_ids.0.clone();
+
+        let result = self
+            .connection()
+            .transaction::<_, Conversation, DbErr>(|txn| {
+                Box::pin(async move {
+                    let conversation = ActiveConversation {
+                        model_id: Set(Some(model_id)),
+                        subject: Set(subject),
+                        options: Set(Some(options)),
+                        created_at: Set(chrono::Local::now()),
+                        // shown at the top of the list like other new conversations
+                        last_message_at: Set(Some(chrono::Local::now())),
+                        preset_id: Set(Some(preset_id)),
+                        subject_edited: Set(subject_edited),
+                        ..Default::default()
+                    }
+                    .insert(txn)
+                    .await?;
+
+                    if let Some(system_prompt) = system_prompt {
+                        let message = ActiveMessage {
+                            conversation_id: Set(conversation.id),
+                            role: Set(messages::Roles::System.into()),
+                            created_at: Set(chrono::Local::now()),
+                            ..Default::default()
+                        }
+                        .insert(txn)
+                        .await?;
+                        contents::ActiveModel {
+                            message_id: Set(message.id),
+                            r#type: Set(contents::ContentType::Text),
+                            data: Set(system_prompt),
+                            ..Default::default()
+                        }
+                        .insert(txn)
+                        .await?;
+                    }
+
+                    // collections deleted since the preset was saved are left out
+                    let collection_ids: Vec<i32> = collections::Entity::find()
+                        .select_only()
+                        .column(collections::Column::Id)
+                        .filter(collections::Column::Id.is_in(collection_ids))
+                        .into_tuple()
+                        .all(txn)
+                        .await?;
+                    let ams: Vec<conversation_collections::ActiveModel> = collection_ids
+                        .into_iter()
+                        .map(|collection_id| conversation_collections::ActiveModel {
+                            conversation_id: Set(conversation.id),
+                            collection_id: Set(collection_id),
+                        })
+                        .collect();
+                    if !ams.is_empty() {
+                        conversation_collections::Entity::insert_many(ams)
+                            .exec(txn)
+                            .await?;
+                    }
+                    Ok(conversation)
+                })
+            })
+            .await
+            .map_err(|err| {
+                error!("Failed to create conversation from preset: {}", err);
+                format!("Failed to create conversation from preset {}", preset.name)
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * Insert a new collection
+     */
+    pub async fn create_collection(
+        &self,
+        new_collection: NewCollection,
+    ) -> Result<Collection, String> {
+        let mut active_model = new_collection.into_active_model();
+        active_model.created_at = Set(chrono::Local::now());
+        let result = active_model.insert(&self.connection()).await.map_err(|err| {
+            error!("{}", err);
+            "Failed to create collection".to_string()
+        })?;
+        Ok(result)
+    }
+
+    /**
+     * List all collections
+     */
+    pub async fn list_collections(&self) -> Result<Vec<Collection>, String> {
+        let result = collections::Entity::find()
+            .order_by(collections::Column::CreatedAt, Order::Desc)
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to list collections".to_string()
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * Get a collection by id
+     */
+    pub async fn get_collection(&self, collection_id: i32) -> Result<Collection, String> {
+        let result = collections::Entity::find_by_id(collection_id)
+            .one(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to get collection with id {}", collection_id)
+            })?
+            .ok_or(format!("Collection with id {} doesn't exist", collection_id))?;
+        Ok(result)
+    }
+
+    /**
+     * Hard delete a collection, its documents and chunks are deleted by cascade
+     */
+    pub async fn delete_collection(&self, collection_id: i32) -> Result<(), String> {
+        collections::Entity::delete_by_id(collection_id)
+            .exec(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to delete collection with id {}", collection_id)
+            })?;
+        Ok(())
+    }
+
+    /**
+     * Insert a document together with its chunks
+     */
+    pub async fn create_document_with_chunks(
+        &self,
+        new_document: NewDocument,
+        chunks: Vec<NewChunk>,
+    ) -> Result<Document, String> {
+        let collection_id = new_document.collection_id;
+        let mut doc_am = new_document.into_active_model();
+        doc_am.chunk_count = Set(chunks.len() as i32);
+        doc_am.created_at = Set(chrono::Local::now());
+        let result = self
+            .connection()
+            .transaction::<_, Document, DbErr>(|txn| {
+                Box::pin(async move {
+                    let doc_m = doc_am.insert(txn).await?;
+                    let chunk_ams: Vec<chunks::ActiveModel> = chunks
+                        .into_iter()
+                        .enumerate()
+                        .map(|(position, chunk)| chunks::ActiveModel {
+                            document_id: Set(doc_m.id),
+                            collection_id: Set(collection_id),
+                            position: Set(position as i32),
+                            text: Set(chunk.text),
+                            embedding: Set(chunk.embedding),
+                            ..Default::default()
+                        })
+                        .collect();
+                    if !chunk_ams.is_empty() {
+                        chunks::Entity::insert_many(chunk_ams).exec(txn).await?;
+                    }
+                    Ok(doc_m)
+                })
+            })
+            .await
+            .map_err(|err| {
+                error!("Failed to create document with chunks: {}", err);
+                err.to_string()
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * List all documents of a collection
+     */
+    pub async fn list_documents(&self, collection_id: i32) -> Result<Vec<Document>, String> {
+        let result = documents::Entity::find()
+            .filter(documents::Column::CollectionId.eq(collection_id))
+            .order_by(documents::Column::CreatedAt, Order::Asc)
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!(
+                    "Failed to list documents of collection with id = {}",
+                    collection_id
+                )
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * Hard delete a document, its chunks are deleted by cascade
+     */
+    pub async fn delete_document(&self, document_id: i32) -> Result<(), String> {
+        documents::Entity::delete_by_id(document_id)
+            .exec(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to delete document with id {}", document_id)
+            })?;
+        Ok(())
+    }
+
+    /**
+     * List all chunks of collections, along with the documents they belong to
+     */
+    pub async fn list_chunks_of_collections(
+        &self,
+        collection_ids: Vec<i32>,
+    ) -> Result<Vec<(Chunk, Option<Document>)>, String> {
+        let result = chunks::Entity::find()
+            .find_also_related(documents::Entity)
+            .filter(chunks::Column::CollectionId.is_in(collection_ids))
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to list chunks of collections".to_string()
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * Replace the collections attached to a conversation
+     */
+    pub async fn set_conversation_collections(
+        &self,
+        conversation_id: i32,
+        collection_ids: Vec<i32>,
+    ) -> Result<Vec<Collection>, String> {
+        self.connection()
+            .transaction::<_, (), DbErr>(|txn| {
+                Box::pin(async move {
+                    conversation_collections::Entity::delete_many()
+                        .filter(conversation_collections::Column::ConversationId.eq(conversation_id))
+                        .exec(txn)
+                        .await?;
+                    let ams: Vec<conversation_collections::ActiveModel> = collection_ids
+                        .into_iter()
+                        .map(|collection_id| conversation_collections::ActiveModel {
+                            conversation_id: Set(conversation_id),
+                            collection_id: Set(collection_id),
+                        })
+                        .collect();
+                    if !ams.is_empty() {
+                        conversation_collections::Entity::insert_many(ams)
+                            .exec(txn)
+                            .await?;
+                    }
+                    Ok(())
+                })
+            })
+            .await
+            .map_err(|err| {
+                error!("Failed to set collections of conversation: {}", err);
+                err.to_string()
+            })?;
+        self.list_conversation_collections(conversation_id).await
+    }
+
+    /**
+     * List the collections attached to a conversation
+     */
+    pub async fn list_conversation_collections(
+        &self,
+        conversation_id: i32,
+    ) -> Result<Vec<Collection>, String> {
+        let result = collections::Entity::find()
+            .join(
+                JoinType::InnerJoin,
+                conversation_collections::Relation::Collections.def().rev(),
+            )
+            .filter(conversation_collections::Column::ConversationId.eq(conversation_id))
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!(
+                    "Failed to list collections of conversation with id = {}",
+                    conversation_id
+                )
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * Insert a new folder
+     */
+    pub async fn create_folder(&self, new_folder: NewFolder) -> Result<Folder, String> {
+        let mut active_model = new_folder.into_active_model();
+        active_model.created_at = Set(chrono::Local::now());
+        let result = active_model.insert(&self.connection()).await.map_err(|err| {
+            error!("{}", err);
+            "Failed to create folder".to_string()
+        })?;
+        Ok(result)
+    }
+
+    /**
+     * List all folders by name
+     */
+    pub async fn list_folders(&self) -> Result<Vec<Folder>, String> {
+        let result = folders::Entity::find()
+            .order_by(folders::Column::Name, Order::Asc)
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to list folders".to_string()
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * Get a folder by id
+     */
+    pub async fn get_folder(&self, folder_id: i32) -> Result<Folder, String> {
+        let result = folders::Entity::find_by_id(folder_id)
+            .one(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to get folder with id {}", folder_id)
+            })?
+            .ok_or(format!("Folder with id {} doesn't exist", folder_id))?;
+        Ok(result)
+    }
+
+    /**
+     * Rename a folder
+     */
+    pub async fn rename_folder(&self, folder_id: i32, name: String) -> Result<Folder, String> {
+        let folder = self.get_folder(folder_id).await?;
+        let mut active_model: folders::ActiveModel = folder.into();
+        active_model.name = Set(name);
+        active_model.updated_at = Set(Some(chrono::Local::now()));
+        let result = active_model.update(&self.connection()).await.map_err(|err| {
+            error!("{}", err);
+            format!("Failed to rename folder with id {}", folder_id)
+        })?;
+        Ok(result)
+    }
+
+    /**
+     * Hard delete a folder, its conversations are moved out of it
+     */
+    pub async fn delete_folder(&self, folder_id: i32) -> Result<(), String> {
+        self.connection()
+            .transaction::<_, (), DbErr>(|txn| {
+                Box::pin(async move {
+                    conversations::Entity::update_many()
+                        .filter(conversations::Column::FolderId.eq(folder_id))
+                        .col_expr(
+                            conversations::Column::FolderId,
+                            sea_query::Expr::value(Option::<i32>::None),
+                        )
+                        .exec(txn)
+                        .await?;
+                    folders::Entity::delete_by_id(folder_id).exec(txn).await?;
+                    Ok(())
+                })
+            })
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to delete folder with id {}", folder_id)
+            })?;
+        Ok(())
+    }
+
+    /**
+     * Insert a new tag
+     */
+    pub async fn create_tag(&self, new_tag: NewTag) -> Result<Tag, String> {
+        let mut active_model = new_tag.into_active_model();
+        active_model.created_at = Set(chrono::Local::now());
+        let result = active_model.insert(&self.connection()).await.map_err(|err| {
+            error!("{}", err);
+            "Failed to create tag".to_string()
+        })?;
+        Ok(result)
+    }
+
+    /**
+     * List all tags by name
+     */
+    pub async fn list_tags(&self) -> Result<Vec<Tag>, String> {
+        let result = tags::Entity::find()
+            .order_by(tags::Column::Name, Order::Asc)
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to list tags".to_string()
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * Update name and color of a tag
+     */
+    pub async fn update_tag(&self, tag: Tag) -> Result<Tag, String> {
+        let mut active_model: tags::ActiveModel = tag.into();
+        active_model.reset(tags::Column::Name); // mark name as dirty
+        active_model.reset(tags::Column::Color); // mark color as dirty
+        active_model.created_at = ActiveValue::NotSet;
+        let result = active_model.update(&self.connection()).await.map_err(|err| {
+            error!("{}", err);
+            "Failed to update tag".to_string()
+        })?;
+        Ok(result)
+    }
+
+    /**
+     * Hard delete a tag, its links to conversations are deleted by cascade
+     */
+    pub async fn delete_tag(&self, tag_id: i32) -> Result<(), String> {
+        tags::Entity::delete_by_id(tag_id)
+            .exec(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to delete tag with id {}", tag_id)
+            })?;
+        Ok(())
+    }
+
+    /**
+     * Replace the tags of a conversation
+     */
+    pub async fn set_conversation_tags(
+        &self,
+        conversation_id: i32,
+        tag_ids: Vec<i32>,
+    ) -> Result<Vec<Tag>, String> {
+        self.connection()
+            .transaction::<_, (), DbErr>(|txn| {
+                Box::pin(async move {
+                    conversation_tags::Entity::delete_many()
+                        .filter(conversation_tags::Column::ConversationId.eq(conversation_id))
+                        .exec(txn)
+                        .await?;
+                    let ams: Vec<conversation_tags::ActiveModel> = tag_ids
+                        .into_iter()
+                        .map(|tag_id| conversation_tags::ActiveModel {
+                            conversation_id: Set(conversation_id),
+                            tag_id: Set(tag_id),
+                        })
+                        .collect();
+                    if !ams.is_empty() {
+                        conversation_tags::Entity::insert_many(ams).exec(txn).await?;
+                    }
+                    Ok(())
+                })
+            })
+            .await
+            .map_err(|err| {
+                error!("Failed to set tags of conversation: {}", err);
+                err.to_string()
+            })?;
+        self.list_conversation_tags(conversation_id).await
+    }
+
+    /**
+     * List the tags of a conversation
+     */
+    pub async fn list_conversation_tags(&self, conversation_id: i32) -> Result<Vec<Tag>, String> {
+        let result = tags::Entity::find()
+            .join(
+                JoinType::InnerJoin,
+                conversation_tags::Relation::Tags.def().rev(),
+            )
+            .filter(conversation_tags::Column::ConversationId.eq(conversation_id))
+            .order_by(tags::Column::Name, Order::Asc)
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!(
+                    "Failed to list tags of conversation with id = {}",
+                    conversation_id
+                )
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * List the text of messages that have no embedding computed by the model, in batches.
+     * Embeddings of deleted messages are removed by cascade.
+     */
+    pub async fn list_unindexed_messages(
+        &self,
+        model_id: i32,
+        limit: u64,
+    ) -> Result<Vec<(i32, String)>, String> {
+        // The limit applies to messages, which may have several text contents
+        let message_ids: Vec<i32> = contents::Entity::find()
+            .select_only()
+            .column(contents::Column::MessageId)
+            .distinct()
+            .join(JoinType::InnerJoin, contents::Relation::Messages.def())
+            .join(JoinType::InnerJoin, messages::Relation::Conversations.def())
+            .join(
+                JoinType::LeftJoin,
+                message_embeddings::Relation::Messages.def().rev(),
+            )
+            .filter(contents::Column::Type.eq(contents::ContentType::Text))
+            .filter(contents::Column::Data.ne(""))
+            .filter(messages::Column::Role.ne(Into::<i32>::into(messages::Roles::System)))
+            .filter(messages::Column::DeletedAt.is_null())
+            .filter(conversations::Column::DeletedAt.is_null())
+            .filter(
+                Condition::any()
+                    .add(message_embeddings::Column::MessageId.is_null())
+                    .add(message_embeddings::Column::ModelId.ne(model_id)),
+            )
+            .order_by(contents::Column::MessageId, Order::Asc)
+            .limit(limit)
+            .into_tuple()
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to list unindexed messages".to_string()
+            })?;
+        if message_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let contents = contents::Entity::find()
+            .filter(contents::Column::MessageId.is_in(message_ids))
+            .filter(contents::Column::Type.eq(contents::ContentType::Text))
+            .filter(contents::Column::Data.ne(""))
+            .order_by(contents::Column::MessageId, Order::Asc)
+            .order_by(contents::Column::Id, Order::Asc)
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to list contents of unindexed messages".to_string()
+            })?;
+        // Merge the text contents of each message
+        let mut result: Vec<(i32, String)> = vec![];
+        for content in contents {
+            match result.last_mut() {
+                Some((message_id, text)) if *message_id == content.message_id => {
+                    text.push_str("\n");
+                    text.push_str(&content.data);
+                }
+                _ => result.push((content.message_id, content.data)),
+            }
+        }
+        Ok(result)
+    }
+
+    /**
+     * Insert or replace the embeddings of messages
+     */
+    pub async fn upsert_message_embeddings(
+        &self,
+        model_id: i32,
+        embeddings: Vec<(i32, Vec<u8>)>,
+    ) -> Result<(), String> {
+        if embeddings.is_empty() {
+            return Ok(());
+        }
+        let now = chrono::Local::now();
+        let active_models: Vec<message_embeddings::ActiveModel> = embeddings
+            .into_iter()
+            .map(|(message_id, embedding)| message_embeddings::ActiveModel {
+                message_id: Set(message_id),
+                model_id: Set(model_id),
+                embedding: Set(embedding),
+                created_at: Set(now),
+            })
+            .collect();
+        message_embeddings::Entity::insert_many(active_models)
+            .on_conflict(
+                sea_query::OnConflict::column(message_embeddings::Column::MessageId)
+                    .update_columns([
+                        message_embeddings::Column::ModelId,
+                        message_embeddings::Column::Embedding,
+                        message_embeddings::Column::CreatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to upsert message embeddings".to_string()
+            })?;
+        Ok(())
+    }
 
     /**
-     * List all prompts
+     * List the message embeddings computed by a model, skipping deleted messages and conversations
      */
-    pub async fn list_prompts(&self) -> Result<Vec<Prompt>, String> {
-        let result = prompts::Entity::find()
-            .filter(prompts::Column::DeletedAt.is_null())
-            .all(&self.connection)
+    pub async fn list_message_embeddings(
+        &self,
+        model_id: i32,
+    ) -> Result<Vec<MessageEmbedding>, String> {
+        let result = message_embeddings::Entity::find()
+            .join(JoinType::InnerJoin, message_embeddings::Relation::Messages.def())
+            .join(JoinType::InnerJoin, messages::Relation::Conversations.def())
+            .filter(message_embeddings::Column::ModelId.eq(model_id))
+            .filter(messages::Column::DeletedAt.is_null())
+            .filter(conversations::Column::DeletedAt.is_null())
+            .all(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
-                "Failed to list prompts".to_string()
+                format!("Failed to list message embeddings of model with id = {}", model_id)
             })?;
         Ok(result)
     }
 
     /**
-     * Update a prompt
+     * Get messages by ids along with their conversations, skipping deleted ones
      */
-    pub async fn update_prompt(&self, prompt: Prompt) -> Result<Prompt, String> {
-        let mut active_model: prompts::ActiveModel = prompt.into();
-        active_model.reset(prompts::Column::Alias); // mark alias as dirty
-        active_model.reset(prompts::Column::Content); // mark content as dirty
-        active_model.updated_at = Set(Some(chrono::Local::now()));
-        let result = active_model.update(&self.connection).await.map_err(|err| {
+    pub async fn get_messages_with_conversations(
+        &self,
+        message_ids: Vec<i32>,
+    ) -> Result<Vec<(MessageDTO, Conversation)>, String> {
+        let result = messages::Entity::find()
+            .find_also_related(conversations::Entity)
+            .filter(messages::Column::Id.is_in(message_ids))
+            .filter(messages::Column::DeletedAt.is_null())
+            .filter(conversations::Column::DeletedAt.is_null())
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to get messages".to_string()
+            })?;
+        let mut dtos = vec![];
+        for (message, conversation) in result {
+            if let Some(conversation) = conversation {
+                let contents = message
+                    .find_related(contents::Entity)
+                    .all(&self.connection())
+                    .await
+                    .map_err(|err| {
+                        error!("{}", err);
+                        format!("Failed to get contents of message with id = {}", message.id)
+                    })?;
+                dtos.push((MessageDTO::from((message, contents)), conversation));
+            }
+        }
+        Ok(dtos)
+    }
+
+    /**
+     * Get the messages right before and after a message in its conversation, system messages excluded
+     */
+    pub async fn get_adjacent_messages(
+        &self,
+        conversation_id: i32,
+        message_id: i32,
+    ) -> Result<(Option<MessageDTO>, Option<MessageDTO>), String> {
+        let query = messages::Entity::find()
+            .filter(messages::Column::ConversationId.eq(conversation_id))
+            .filter(messages::Column::Role.ne(Into::<i32>::into(messages::Roles::System)))
+            .filter(messages::Column::DeletedAt.is_null());
+        let previous = query
+            .clone()
+            .filter(messages::Column::Id.lt(message_id))
+            .order_by(messages::Column::Id, Order::Desc)
+            .one(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to get message before message with id = {}", message_id)
+            })?;
+        let next = query
+            .filter(messages::Column::Id.gt(message_id))
+            .order_by(messages::Column::Id, Order::Asc)
+            .one(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to get message after message with id = {}", message_id)
+            })?;
+        let mut result = vec![];
+        for message in [previous, next] {
+            let dto = match message {
+                Some(message) => {
+                    let contents = message
+                        .find_related(contents::Entity)
+                        .all(&self.connection())
+                        .await
+                        .map_err(|err| {
+                            error!("{}", err);
+                            format!("Failed to get contents of message with id = {}", message.id)
+                        })?;
+                    Some(MessageDTO::from((message, contents)))
+                }
+                None => None,
+            };
+            result.push(dto);
+        }
+        let next = result.pop().flatten();
+        let previous = result.pop().flatten();
+        Ok((previous, next))
+    }
+
+    /**
+     * List the names of files in cache referenced by contents and previous versions of messages,
+     * optionally only those of a conversation
+     */
+    pub async fn list_content_files(
+        &self,
+        conversation_id: Option<i32>,
+    ) -> Result<Vec<String>, String> {
+        let mut query = contents::Entity::find()
+            .select_only()
+            .column(contents::Column::Data)
+            .filter(contents::Column::Type.ne(contents::ContentType::Text));
+        if let Some(conversation_id) = conversation_id {
+            query = query
+                .join(JoinType::InnerJoin, contents::Relation::Messages.def())
+                .filter(messages::Column::ConversationId.eq(conversation_id));
+        }
+        let mut result: Vec<String> = query
+            .into_tuple()
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to list files of contents".to_string()
+            })?;
+        let revision_files = revision_file_names(
+            &self.connection(),
+            conversation_id.map(|conversation_id| vec![conversation_id]),
+        )
+        .await
+        .map_err(|err| {
             error!("{}", err);
-            "Failed to update prompt".to_string()
+            "Failed to list files of message revisions".to_string()
         })?;
+        result.extend(revision_files);
         Ok(result)
     }
 
     /**
-     * Soft delete a prompt
+     * List the ids of all messages, including soft deleted ones
      */
-    pub async fn delete_prompt(&self, prompt_id: i32) -> Result<Prompt, String> {
-        let prompt = prompts::Entity::find_by_id(prompt_id)
-            .one(&self.connection)
+    pub async fn list_message_ids(&self) -> Result<Vec<i32>, String> {
+        let result = messages::Entity::find()
+            .select_only()
+            .column(messages::Column::Id)
+            .into_tuple()
+            .all(&self.connection())
             .await
             .map_err(|err| {
                 error!("{}", err);
-                format!("Failed to get prompt with id {}", prompt_id)
+                "Failed to list message ids".to_string()
+            })?;
+        Ok(result)
+    }
+
+    /**
+     * Get an attachment by the hash of its file
+     */
+    pub async fn get_attachment(&self, hash: &str) -> Result<Option<Attachment>, String> {
+        attachments::Entity::find_by_id(hash.to_string())
+            .one(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to get attachment with hash = {}", hash)
+            })
+    }
+
+    /**
+     * Insert a new attachment, not referenced by any content yet
+     */
+    pub async fn create_attachment(&self, new_attachment: NewAttachment) -> Result<Attachment, String> {
+        let mut active_model = new_attachment.into_active_model();
+        active_model.ref_count = Set(0);
+        active_model.created_at = Set(chrono::Local::now());
+        let result = active_model.insert(&self.connection()).await.map_err(|err| {
+            error!("{}", err);
+            "Failed to create attachment".to_string()
+        })?;
+        Ok(result)
+    }
+
+    /**
+     * List attachments that no content references and that were created before a time
+     */
+    pub async fn list_unreferenced_attachments(
+        &self,
+        created_before: chrono::DateTime<chrono::Local>,
+    ) -> Result<Vec<Attachment>, String> {
+        attachments::Entity::find()
+            .filter(attachments::Column::RefCount.lte(0))
+            .filter(attachments::Column::CreatedAt.lt(created_before))
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to list unreferenced attachments".to_string()
+            })
+    }
+
+    /**
+     * Delete an attachment, its file has to be removed from cache by the caller
+     */
+    pub async fn delete_attachment(&self, hash: &str) -> Result<(), String> {
+        attachments::Entity::delete_by_id(hash.to_string())
+            .exec(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to delete attachment with hash = {}", hash)
+            })?;
+        Ok(())
+    }
+
+    /**
+     * List soft deleted conversations, most recently deleted first
+     */
+    pub async fn list_deleted_conversations(&self) -> Result<Vec<ConversationDTO>, String> {
+        conversations::Entity::find()
+            .filter(conversations::Column::DeletedAt.is_not_null())
+            .order_by(conversations::Column::DeletedAt, Order::Desc)
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to list deleted conversations".to_string()
+            })
+    }
+
+    /**
+     * List soft deleted models, most recently deleted first
+     */
+    pub async fn list_deleted_models(&self) -> Result<Vec<Model>, String> {
+        models::Entity::find()
+            .filter(models::Column::DeletedAt.is_not_null())
+            .order_by(models::Column::DeletedAt, Order::Desc)
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to list deleted models".to_string()
+            })
+    }
+
+    /**
+     * Restore a soft deleted conversation, along with its model if it has been deleted too
+     */
+    pub async fn restore_conversation(
+        &self,
+        conversation_id: i32,
+    ) -> Result<ConversationDTO, String> {
+        let conv = conversations::Entity::find_by_id(conversation_id)
+            .one(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to get conversation with id = {}", conversation_id)
             })?
-            .ok_or(format!("Prompt with id {} doesn't exist", prompt_id))?;
-        let mut active_model: prompts::ActiveModel = prompt.clone().into();
-        // Perform soft delete
-        active_model.deleted_at = Set(Some(chrono::Local::now()));
-        let result = active_model.update(&self.connection).await.map_err(|err| {
+            .ok_or(format!(
+                "Conversation with id {} doesn't exist",
+                conversation_id
+            ))?;
+        let model_id = conv.model_id;
+        let mut active_model: conversations::ActiveModel = conv.into();
+        active_model.deleted_at = Set(None);
+        self.connection()
+            .transaction::<_, ConversationDTO, DbErr>(|txn| {
+                Box::pin(async move {
+                    if let Some(model_id) = model_id {
+                        models::Entity::update_many()
+                            .filter(models::Column::Id.eq(model_id))
+                            .filter(models::Column::DeletedAt.is_not_null())
+                            .col_expr(
+                                models::Column::DeletedAt,
+                                sea_query::Expr::value(Option::<chrono::DateTime<chrono::Local>>::None),
+                            )
+                            .exec(txn)
+                            .await?;
+                    }
+                    active_model.update(txn).await
+                })
+            })
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!(
+                    "Failed to restore conversation with id = {}",
+                    conversation_id
+                )
+            })
+    }
+
+    /**
+     * Restore a soft deleted model
+     */
+    pub async fn restore_model(&self, model_id: i32) -> Result<Model, String> {
+        let model = models::Entity::find_by_id(model_id)
+            .one(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                format!("Failed to get model with id {}", model_id)
+            })?
+            .ok_or(format!("Model with id {} doesn't exist", model_id))?;
+        let mut active_model: models::ActiveModel = model.into();
+        active_model.deleted_at = Set(None);
+        let result = active_model.update(&self.connection()).await.map_err(|err| {
             error!("{}", err);
-            format!("Failed to delete prompt with id = {}", prompt_id)
+            format!("Failed to restore model with id = {}", model_id)
         })?;
         Ok(result)
     }
+
+    /**
+     * Permanently delete soft deleted conversations, optionally only those deleted before a date.
+     * Their messages, contents and embeddings are deleted by cascade.
+     * Returns the ids of the purged conversations and the names of the files their contents referenced.
+     */
+    pub async fn purge_conversations(
+        &self,
+        deleted_before: Option<chrono::DateTime<chrono::Local>>,
+    ) -> Result<(Vec<i32>, Vec<String>), String> {
+        self.connection()
+            .transaction::<_, (Vec<i32>, Vec<String>), DbErr>(|txn| {
+                Box::pin(async move {
+                    let mut query = conversations::Entity::find()
+                        .select_only()
+                        .column(conversations::Column::Id)
+                        .filter(conversations::Column::DeletedAt.is_not_null());
+                    if let Some(deleted_before) = deleted_before {
+                        query = query.filter(conversations::Column::DeletedAt.lt(deleted_before));
+                    }
+                    let ids: Vec<i32> = query.into_tuple().all(txn).await?;
+                    if ids.is_empty() {
+                        return Ok((ids, vec![]));
+                    }
+                    let file_names: Vec<String> = contents::Entity::find()
+                        .select_only()
+                        .column(contents::Column::Data)
+                        .filter(contents::Column::Type.ne(contents::ContentType::Text))
+                        .join(JoinType::InnerJoin, contents::Relation::Messages.def())
+                        .filter(messages::Column::ConversationId.is_in(ids.clone()))
+                        .into_tuple()
+                        .all(txn)
+                        .await?;
+                    let mut file_names = file_names;
+                    file_names.extend(revision_file_names(txn, Some(ids.clone())).await?);
+                    conversations::Entity::delete_many()
+                        .filter(conversations::Column::Id.is_in(ids.clone()))
+                        .exec(txn)
+                        .await?;
+                    sync_attachment_refs(txn, file_names.clone()).await?;
+                    Ok((ids, file_names))
+                })
+            })
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to purge deleted conversations".to_string()
+            })
+    }
+
+    /**
+     * Permanently delete soft deleted models, optionally only those deleted before a date.
+     * Models still used by a conversation or a collection are kept, as they would be deleted by cascade.
+     * Returns the ids of the purged models.
+     */
+    pub async fn purge_models(
+        &self,
+        deleted_before: Option<chrono::DateTime<chrono::Local>>,
+    ) -> Result<Vec<i32>, String> {
+        let mut query = models::Entity::find()
+            .select_only()
+            .column(models::Column::Id)
+            .filter(models::Column::DeletedAt.is_not_null())
+            .filter(
+                models::Column::Id.not_in_subquery(
+                    sea_query::Query::select()
+                        .column(conversations::Column::ModelId)
+                        .from(conversations::Entity)
+                        .and_where(conversations::Column::ModelId.is_not_null())
+                        .to_owned(),
+                ),
+            )
+            .filter(
+                models::Column::Id.not_in_subquery(
+                    sea_query::Query::select()
+                        .column(collections::Column::ModelId)
+                        .from(collections::Entity)
+                        .to_owned(),
+                ),
+            );
+        if let Some(deleted_before) = deleted_before {
+            query = query.filter(models::Column::DeletedAt.lt(deleted_before));
+        }
+        let ids: Vec<i32> = query
+            .into_tuple()
+            .all(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to list deleted models".to_string()
+            })?;
+        if ids.is_empty() {
+            return Ok(ids);
+        }
+        models::Entity::delete_many()
+            .filter(models::Column::Id.is_in(ids.clone()))
+            .exec(&self.connection())
+            .await
+            .map_err(|err| {
+                error!("{}", err);
+                "Failed to purge deleted models".to_string()
+            })?;
+        Ok(ids)
+    }
+}
+
+/// Sort key of conversations by pin, as stored in SQLite
+const CONVERSATION_PINNED_SQL: &str = "conversations.pinned";
+/// Sort key of conversations by activity, conversations without message sorted by creation time
+const CONVERSATION_ACTIVITY_SQL: &str =
+    "COALESCE(conversations.last_message_at, conversations.created_at)";
+
+/// Query of the conversations that are not deleted and match a filter
+fn filter_conversations(filter: ConversationFilter) -> sea_orm::Select<conversations::Entity> {
+    let mut query = conversations::Entity::find()
+        .filter(conversations::Column::DeletedAt.is_null())
+        .filter(conversations::Column::Archived.eq(filter.archived));
+    if let Some(folder_id) = filter.folder_id {
+        query = query.filter(conversations::Column::FolderId.eq(folder_id));
+    }
+    if let Some(pinned) = filter.pinned {
+        query = query.filter(conversations::Column::Pinned.eq(pinned));
+    }
+    for tag_id in filter.tag_ids {
+        query = query.filter(
+            conversations::Column::Id.in_subquery(
+                sea_query::Query::select()
+                    .column(conversation_tags::Column::ConversationId)
+                    .from(conversation_tags::Entity)
+                    .and_where(conversation_tags::Column::TagId.eq(tag_id))
+                    .to_owned(),
+            ),
+        );
+    }
+    query
+}
+
+/// Names of the files in cache referenced by contents
+fn file_names_of(contents: &[ContentDTO]) -> Vec<String> {
+    contents
+        .iter()
+        .filter(|content| content.r#type.is_file())
+        .map(|content| content.data.clone())
+        .collect()
+}
+
+/// Names of the files in cache referenced by previous versions of messages,
+/// optionally only those of some conversations
+async fn revision_file_names<C: ConnectionTrait>(
+    db: &C,
+    conversation_ids: Option<Vec<i32>>,
+) -> Result<Vec<String>, DbErr> {
+    let mut query = message_revisions::Entity::find()
+        .select_only()
+        .column(message_revisions::Column::Contents);
+    if let Some(conversation_ids) = conversation_ids {
+        query = query
+            .join(JoinType::InnerJoin, message_revisions::Relation::Messages.def())
+            .filter(messages::Column::ConversationId.is_in(conversation_ids));
+    }
+    let contents: Vec<String> = query.into_tuple().all(db).await?;
+    Ok(contents
+        .iter()
+        .flat_map(|contents| {
+            let contents: Vec<ContentDTO> = serde_json::from_str(contents).unwrap_or_default();
+            file_names_of(&contents)
+        })
+        .collect())
+}
+
+/// Options checked against the options of a provider: unknown keys are rejected, and image generation
+/// options are validated as [ImageGenerationOptions] and kept along with the options of the provider
+fn check_options<T: serde::Serialize + serde::de::DeserializeOwned>(
+    options: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    fn strict<T: serde::Serialize + serde::de::DeserializeOwned>(
+        fields: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<serde_json::Value, String> {
+        let checked: T =
+            serde_json::from_value(serde_json::Value::Object(fields.clone())).map_err(|err| err.to_string())?;
+        let checked = serde_json::to_value(&checked).map_err(|err| err.to_string())?;
+        // known fields set to a value are serialized back, null ones are left out
+        let unknown: Vec<&str> = fields
+            .iter()
+            .filter(|(key, value)| !value.is_null() && checked.get(key.as_str()).is_none())
+            .map(|(key, _)| key.as_str())
+            .collect();
+        if !unknown.is_empty() {
+            return Err(format!("Unknown options: {}", unknown.join(", ")));
+        }
+        Ok(checked)
+    }
+    let mut fields = match options {
+        serde_json::Value::Object(fields) => fields,
+        _ => return Err("Options are not an object".to_string()),
+    };
+    let image_generation = match fields.remove(IMAGE_GENERATION_OPTIONS_KEY) {
+        Some(serde_json::Value::Object(fields)) => Some(
+            strict::<ImageGenerationOptions>(fields)
+                .map_err(|err| format!("Invalid image generation options: {}", err))?,
+        ),
+        Some(serde_json::Value::Null) | None => None,
+        Some(_) => return Err("Image generation options are not an object".to_string()),
+    };
+    let mut checked = strict::<T>(fields)?;
+    if let Some(image_generation) = image_generation {
+        checked[IMAGE_GENERATION_OPTIONS_KEY] = image_generation;
+    }
+    Ok(checked)
+}
+
+/// Options of a conversation checked against the provider of its model, the defaults of the provider
+/// are used if they are invalid
+fn conversation_options(provider: &str, options: &str) -> String {
+    fn checked<T: Default + serde::Serialize + serde::de::DeserializeOwned>(options: &str) -> String {
+        let checked = serde_json::from_str::<serde_json::Value>(options)
+            .map_err(|err| err.to_string())
+            .and_then(check_options::<T>)
+            .unwrap_or_else(|err| {
+                error!("db::conversation_options: Invalid options: {}", err);
+                serde_json::to_value(T::default()).unwrap_or_default()
+            });
+        serde_json::to_string(&checked).unwrap_or_default()
+    }
+    match provider.into() {
+        Providers::Azure => checked::<AzureOptions>(options),
+        Providers::Claude => checked::<ClaudeOptions>(options),
+        Providers::Ollama => checked::<OllamaOptions>(options),
+        _ => checked::<OpenAIOptions>(options),
+    }
+}
+
+/// Options of a conversation checked against the provider of its model.
+/// Layers of options are merged over the defaults of the provider in order, so a field set by a
+/// later layer takes precedence; the defaults are used if the result is invalid.
+/// Fields left unset fall back to global settings when chatting.
+pub fn provider_options(provider: &str, layers: &[Option<&str>]) -> String {
+    fn checked<T: Default + serde::Serialize + serde::de::DeserializeOwned>(
+        layers: &[Option<&str>],
+    ) -> String {
+        let mut merged = serde_json::to_value(T::default()).unwrap_or_default();
+        for layer in layers.iter().flatten() {
+            match serde_json::from_str::<serde_json::Value>(layer) {
+                Ok(serde_json::Value::Object(fields)) => {
+                    for (key, value) in fields.into_iter().filter(|(_, value)| !value.is_null()) {
+                        merged[key] = value;
+                    }
+                }
+                Ok(_) => error!("db::provider_options: Options are not an object: {}", layer),
+                Err(err) => error!("db::provider_options: Error deserializing options: {}", err),
+            }
+        }
+        let options = check_options::<T>(merged).unwrap_or_else(|err| {
+            error!("db::provider_options: Invalid options: {}", err);
+            serde_json::to_value(T::default()).unwrap_or_default()
+        });
+        serde_json::to_string(&options).unwrap_or_default()
+    }
+    match provider.into() {
+        Providers::Azure => checked::<AzureOptions>(layers),
+        Providers::Claude => checked::<ClaudeOptions>(layers),
+        Providers::Ollama => checked::<OllamaOptions>(layers),
+        _ => checked::<OpenAIOptions>(layers),
+    }
+}
+
+/// Default options of a model checked against its provider, blank ones are cleared.
+/// Unlike options of conversations, invalid ones are rejected rather than replaced.
+fn checked_default_options(provider: &str, options: Option<String>) -> Result<Option<String>, String> {
+    fn checked<T: serde::Serialize + serde::de::DeserializeOwned>(
+        provider: &str,
+        options: &str,
+    ) -> Result<String, String> {
+        let options = serde_json::from_str::<serde_json::Value>(options)
+            .map_err(|err| err.to_string())
+            .and_then(check_options::<T>)
+            .map_err(|err| format!("Invalid default options for {} models: {}", provider, err))?;
+        serde_json::to_string(&options).map_err(|err| err.to_string())
+    }
+    let options = match options.filter(|options| !options.trim().is_empty()) {
+        Some(options) => options,
+        None => return Ok(None),
+    };
+    let result = match provider.into() {
+        Providers::Azure => checked::<AzureOptions>(provider, &options),
+        Providers::Claude => checked::<ClaudeOptions>(provider, &options),
+        Providers::Ollama => checked::<OllamaOptions>(provider, &options),
+        _ => checked::<OpenAIOptions>(provider, &options),
+    }?;
+    Ok(Some(result))
+}
+
+/// Insert a prompt along with its first version
+async fn insert_prompt<C: ConnectionTrait>(db: &C, new_prompt: NewPrompt) -> Result<Prompt, DbErr> {
+    let mut active_model = new_prompt.into_active_model();
+    active_model.created_at = Set(chrono::Local::now());
+    let prompt = active_model.insert(db).await?;
+    insert_prompt_version(db, &prompt).await?;
+    Ok(prompt)
+}
+
+/// Update a prompt, bumping its version and recording the new one if its wording changed
+async fn update_prompt_versioned<C: ConnectionTrait>(
+    db: &C,
+    current: &Prompt,
+    mut active_model: prompts::ActiveModel,
+    changed: bool,
+) -> Result<Prompt, DbErr> {
+    active_model.updated_at = Set(Some(chrono::Local::now()));
+    if changed {
+        active_model.version = Set(current.version + 1);
+    }
+    let prompt = active_model.update(db).await?;
+    if changed {
+        insert_prompt_version(db, &prompt).await?;
+    }
+    Ok(prompt)
+}
+
+async fn insert_prompt_version<C: ConnectionTrait>(db: &C, prompt: &Prompt) -> Result<(), DbErr> {
+    prompt_versions::ActiveModel {
+        prompt_id: Set(prompt.id),
+        version: Set(prompt.version),
+        alias: Set(prompt.alias.clone()),
+        content: Set(prompt.content.clone()),
+        created_at: Set(chrono::Local::now()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
+
+/// Revision of the last user message of a conversation, optionally before a message
+async fn prompt_revision_of<C: ConnectionTrait>(
+    db: &C,
+    conversation_id: i32,
+    before_message_id: Option<i32>,
+) -> Result<Option<i32>, DbErr> {
+    let mut query = messages::Entity::find()
+        .select_only()
+        .column(messages::Column::Revision)
+        .filter(messages::Column::ConversationId.eq(conversation_id))
+        .filter(messages::Column::Role.eq(Into::<i32>::into(messages::Roles::User)))
+        .filter(messages::Column::DeletedAt.is_null());
+    if let Some(before_message_id) = before_message_id {
+        query = query.filter(messages::Column::Id.lt(before_message_id));
+    }
+    query
+        .order_by(messages::Column::Id, Order::Desc)
+        .into_tuple()
+        .one(db)
+        .await
+}
+
+/// Recount the contents referencing the attachments stored in these files
+async fn sync_attachment_refs<C: ConnectionTrait>(db: &C, file_names: Vec<String>) -> Result<(), DbErr> {
+    if file_names.is_empty() {
+        return Ok(());
+    }
+    attachments::Entity::update_many()
+        .col_expr(
+            attachments::Column::RefCount,
+            sea_query::Expr::cust(
+                "(SELECT COUNT(*) FROM contents WHERE contents.data = attachments.file_name AND contents.type <> 0)",
+            ),
+        )
+        .filter(attachments::Column::FileName.is_in(file_names))
+        .exec(db)
+        .await?;
+    Ok(())
 }
 
 #[derive(Default)]
@@ -944,7 +3202,12 @@ impl Builder {
             let connection = Database::connect(format!("sqlite:{}", url))
                 .await
                 .map_err(|_| "Failed to connect to database".to_string())?;
-            Ok(Repository { connection })
+            Ok(Repository {
+                connection: RwLock::new(connection),
+                settings: RwLock::new(None),
+                settings_generation: AtomicU64::new(0),
+                workspace: tokio::sync::RwLock::new(()),
+            })
         })
     }
 }
@@ -0,0 +1,121 @@
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// A line of a diff, with its 1-based number in the old and/or new text
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    pub op: DiffOp,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_line: Option<usize>,
+}
+
+/// Line diff of two texts based on their longest common subsequence of lines.
+/// In a changed block, deleted lines come before inserted ones.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut result = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            result.push(DiffLine {
+                op: DiffOp::Equal,
+                text: old[i].to_string(),
+                old_line: Some(i + 1),
+                new_line: Some(j + 1),
+            });
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            result.push(DiffLine {
+                op: DiffOp::Delete,
+                text: old[i].to_string(),
+                old_line: Some(i + 1),
+                new_line: None,
+            });
+            i += 1;
+        } else {
+            result.push(DiffLine {
+                op: DiffOp::Insert,
+                text: new[j].to_string(),
+                old_line: None,
+                new_line: Some(j + 1),
+            });
+            j += 1;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ops(diff: &[DiffLine]) -> Vec<(DiffOp, &str)> {
+        diff.iter()
+            .map(|line| (line.op, line.text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_identical() {
+        let diff = diff_lines("a\nb", "a\nb\n");
+        assert_eq!(vec![(DiffOp::Equal, "a"), (DiffOp::Equal, "b")], ops(&diff));
+        assert!(diff_lines("", "").is_empty());
+    }
+
+    #[test]
+    fn test_changed_line() {
+        let diff = diff_lines(
+            "# CONTEXT #\n{Context}\n# TONE #",
+            "# CONTEXT #\n{Context}\nMore\n# STYLE #",
+        );
+        assert_eq!(
+            vec![
+                (DiffOp::Equal, "# CONTEXT #"),
+                (DiffOp::Equal, "{Context}"),
+                (DiffOp::Delete, "# TONE #"),
+                (DiffOp::Insert, "More"),
+                (DiffOp::Insert, "# STYLE #"),
+            ],
+            ops(&diff)
+        );
+        assert_eq!((Some(3), None), (diff[2].old_line, diff[2].new_line));
+        assert_eq!((None, Some(4)), (diff[4].old_line, diff[4].new_line));
+    }
+
+    #[test]
+    fn test_insert_and_delete_only() {
+        assert_eq!(
+            vec![(DiffOp::Insert, "a"), (DiffOp::Insert, "b")],
+            ops(&diff_lines("", "a\nb"))
+        );
+        assert_eq!(
+            vec![(DiffOp::Equal, "a"), (DiffOp::Delete, "b")],
+            ops(&diff_lines("a\nb", "a"))
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::{io::Read, path::Path};
+
+/// Extensions of plain text and source code files, read as UTF-8 text
+const TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "log", "csv", "tsv", "json", "jsonl", "xml", "yaml", "yml", "toml", "ini", "cfg", "conf",
+    "html", "htm", "css", "scss", "js", "jsx", "ts", "tsx", "vue", "py", "rb", "php", "java", "kt",
+    "swift", "go", "rs", "c", "h", "cc", "cpp", "hpp", "cs", "m", "scala", "sh", "bash", "ps1", "sql",
+    "lua", "r",
+];
+
+/// Text extracted from a local document
+#[derive(Debug)]
+pub struct ExtractedDocument {
+    /// File name of the document
+    pub name: String,
+    pub mimetype: Option<String>,
+    pub text: String,
+}
+
+/// Mimetype of a supported document, inferred from its extension
+pub fn get_mimetype(path: &Path) -> Option<&'static str> {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "pdf" => Some("application/pdf"),
+        "docx" => Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+        "md" | "markdown" => Some("text/markdown"),
+        "csv" => Some("text/csv"),
+        "json" => Some("application/json"),
+        "html" | "htm" => Some("text/html"),
+        ext if TEXT_EXTENSIONS.contains(&ext) => Some("text/plain"),
+        _ => None,
+    }
+}
+
+/// Whether the document is a rich document (PDF, DOCX) rather than a plain text file
+pub fn is_rich_document(mimetype: &str) -> bool {
+    mimetype == "application/pdf" || mimetype.starts_with("application/vnd.openxmlformats")
+}
+
+/// Extract the text of a local text, Markdown, source code, PDF or DOCX file
+pub fn extract_text(path: &Path) -> Result<ExtractedDocument, String> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or(format!("Invalid file path: {}", path.display()))?;
+    let mimetype =
+        get_mimetype(path).ok_or(format!("Unsupported document type: {}", name))?;
+    let text = match mimetype {
+        "application/pdf" => pdf_extract::extract_text(path).map_err(|err| {
+            log::error!("documents::extract_text: {}", err);
+            format!("Failed to extract text from {}", name)
+        })?,
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => {
+            extract_docx_text(path).map_err(|err| {
+                log::error!("documents::extract_text: {}", err);
+                format!("Failed to extract text from {}", name)
+            })?
+        }
+        _ => read_text_file(path)?,
+    };
+    Ok(ExtractedDocument {
+        name,
+        mimetype: Some(mimetype.to_string()),
+        text,
+    })
+}
+
+fn read_text_file(path: &Path) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|err| {
+        log::error!("documents::read_text_file: {}", err);
+        format!("Failed to read file {}", path.display())
+    })?;
+    Ok(String::from_utf8_lossy(&bytes).to_string())
+}
+
+/// Read the main part of a DOCX archive and convert it to text
+fn extract_docx_text(path: &Path) -> Result<String, String> {
+    let file = std::fs::File::open(path).map_err(|err| err.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|err| err.to_string())?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")
+        .map_err(|err| err.to_string())?
+        .read_to_string(&mut xml)
+        .map_err(|err| err.to_string())?;
+    Ok(docx_xml_to_text(&xml))
+}
+
+/// Convert WordprocessingML to text, keeping paragraphs, line breaks and tabs
+fn docx_xml_to_text(xml: &str) -> String {
+    let mut text = String::new();
+    let mut rest = xml;
+    let mut in_text = false;
+    while let Some(start) = rest.find('<') {
+        if in_text {
+            text.push_str(&unescape_xml(&rest[..start]));
+        }
+        let end = match rest[start..].find('>') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let tag = &rest[start + 1..end];
+        let name = tag
+            .trim_end_matches('/')
+            .split_whitespace()
+            .next()
+            .unwrap_or_default();
+        match name {
+            "w:t" => in_text = !tag.ends_with('/'),
+            "/w:t" => in_text = false,
+            "/w:p" => text.push('\n'),
+            "w:br" | "w:cr" => text.push('\n'),
+            "w:tab" => text.push('\t'),
+            _ => {}
+        }
+        rest = &rest[end + 1..];
+    }
+    text.trim_end().to_string()
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docx_xml_to_text() {
+        let xml = r#"<w:document><w:body><w:p><w:r><w:t>Hello</w:t></w:r><w:r><w:tab/><w:t xml:space="preserve">A &amp; B</w:t></w:r></w:p><w:p><w:r><w:t>Line</w:t><w:br/><w:t>two</w:t></w:r></w:p></w:body></w:document>"#;
+        assert_eq!(docx_xml_to_text(xml), "Hello\tA & B\nLine\ntwo");
+    }
+
+    #[test]
+    fn test_get_mimetype() {
+        assert_eq!(get_mimetype(Path::new("a/b.PDF")), Some("application/pdf"));
+        assert_eq!(get_mimetype(Path::new("main.rs")), Some("text/plain"));
+        assert_eq!(get_mimetype(Path::new("notes.md")), Some("text/markdown"));
+        assert_eq!(get_mimetype(Path::new("photo.png")), None);
+    }
+}
@@ -0,0 +1,167 @@
+use std::{
+    collections::HashSet,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use serde::Serialize;
+
+use crate::log_utils::{info, warn};
+
+use super::{attachments, cache, db::Repository, tts};
+
+/// Files younger than this are kept even if unreferenced, as they may be attached to a message being written
+const MIN_ORPHAN_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Result of a garbage collection of the cache dir
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GcReport {
+    pub removed_files: usize,
+    pub reclaimed_bytes: u64,
+    pub kept_files: usize,
+}
+
+impl GcReport {
+    fn remove(&mut self, path: &Path) {
+        let size = std::fs::metadata(path)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        match std::fs::remove_file(path) {
+            Ok(()) => {
+                self.removed_files += 1;
+                self.reclaimed_bytes += size;
+            }
+            Err(err) => warn(
+                "gc::GcReport::remove",
+                format!("Failed to remove {}: {}", path.display(), err),
+            ),
+        }
+    }
+}
+
+/// Whether the cache should be collected when the app starts
+pub async fn is_gc_on_startup_enabled(repo: &Repository) -> bool {
+    repo.get_settings().await.cache_gc_on_startup
+}
+
+/// Remove files of the cache dir that no content or message references anymore,
+/// i.e. attachments of deleted messages or purged conversations with their extracted text and variants,
+/// and speech of deleted messages
+pub async fn collect_cache_garbage(repo: &Repository) -> Result<GcReport, String> {
+    let referenced: HashSet<String> = repo.list_content_files(None).await?.into_iter().collect();
+    let message_ids: HashSet<i32> = repo.list_message_ids().await?.into_iter().collect();
+    let cache_dir = cache::get_cache_dir()?;
+    let mut report = GcReport::default();
+    let created_before = chrono::Local::now()
+        - chrono::Duration::from_std(MIN_ORPHAN_AGE).map_err(|err| err.to_string())?;
+    for attachment in repo.list_unreferenced_attachments(created_before).await? {
+        if referenced.contains(&attachment.file_name) {
+            continue;
+        }
+        repo.delete_attachment(&attachment.hash).await?;
+    }
+    collect_dir(&cache_dir, &mut report, |file_name| {
+        referenced.contains(file_name)
+            || attachments::sidecar_source(file_name)
+                .map_or(false, |source| referenced.contains(source))
+    });
+    collect_dir(
+        &cache_dir.join(tts::TTS_CACHE_DIR),
+        &mut report,
+        |file_name| {
+            tts::speech_message_id(file_name)
+                .map_or(false, |message_id| message_ids.contains(&message_id))
+        },
+    );
+    collect_dir(
+        &cache_dir.join(attachments::VARIANTS_DIR),
+        &mut report,
+        |file_name| {
+            attachments::variant_source(file_name)
+                .map_or(false, |source| referenced.contains(source))
+        },
+    );
+    info(
+        "gc::collect_cache_garbage",
+        format!(
+            "Removed {} files, reclaimed {} bytes, kept {} files",
+            report.removed_files, report.reclaimed_bytes, report.kept_files
+        ),
+    );
+    Ok(report)
+}
+
+/// Remove the files of a dir that are not referenced and old enough
+fn collect_dir<F: Fn(&str) -> bool>(dir: &Path, report: &mut GcReport, is_referenced: F) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let is_recent = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .map_or(true, |age| age < MIN_ORPHAN_AGE);
+        if is_referenced(&file_name) || is_recent {
+            report.kept_files += 1;
+        } else {
+            report.remove(&path);
+        }
+    }
+}
+
+/// Remove the files of deleted contents right away, unless other contents still reference them
+pub async fn remove_unreferenced_files(
+    repo: &Repository,
+    file_names: Vec<String>,
+) -> Result<GcReport, String> {
+    let referenced: HashSet<String> = repo.list_content_files(None).await?.into_iter().collect();
+    let cache_dir = cache::get_cache_dir()?;
+    let mut report = GcReport::default();
+    for file_name in file_names {
+        if referenced.contains(&file_name) {
+            report.kept_files += 1;
+            continue;
+        }
+        for path in [
+            cache_dir.join(&file_name),
+            cache_dir.join(attachments::sidecar_name(&file_name)),
+        ] {
+            if path.is_file() {
+                report.remove(&path);
+            }
+        }
+        remove_variants(
+            &cache_dir.join(attachments::VARIANTS_DIR),
+            &file_name,
+            &mut report,
+        );
+        if let Some(hash) = attachments::hash_of(&file_name) {
+            repo.delete_attachment(hash).await?;
+        }
+    }
+    Ok(report)
+}
+
+/// Remove the variants encoded from a file
+fn remove_variants(dir: &Path, file_name: &str, report: &mut GcReport) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let variant_name = entry.file_name().to_string_lossy().to_string();
+        if attachments::variant_source(&variant_name) == Some(file_name) {
+            report.remove(&entry.path());
+        }
+    }
+}
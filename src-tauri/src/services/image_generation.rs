@@ -0,0 +1,79 @@
+use entity::entities::{
+    contents::{ContentDTO, ContentType},
+    conversations::ImageGenerationOptions,
+    messages::{MessageDTO, Roles},
+    settings::ProxySetting,
+};
+
+use crate::log_utils::info;
+
+use super::{attachments, db::Repository, llm::client::LLMClient};
+
+/// Min number of turns sent to the model, so that images of the previous turn can be refined
+const MIN_CONTEXT_LENGTH: u16 = 2;
+
+/// Generate images for the last user message of a conversation in image generation mode,
+/// then save them to cache and store them as the bot's reply
+pub async fn generate_reply(
+    repo: &Repository,
+    conversation_id: i32,
+    before_message_id: Option<i32>,
+    proxy_setting: Option<ProxySetting>,
+) -> Result<MessageDTO, String> {
+    let log_tag = "image_generation::generate_reply";
+    let options = repo.get_conversation_options(conversation_id).await?;
+    let image_options =
+        ImageGenerationOptions::from_conversation_options(&options.options).ok_or(format!(
+            "Conversation with id {} is not in image generation mode",
+            conversation_id
+        ))?;
+    let context_length = serde_json::from_str::<serde_json::Value>(&options.options)
+        .ok()
+        .and_then(|options_json| options_json["contextLength"].as_u64())
+        .and_then(|ctx_length| u16::try_from(ctx_length).ok())
+        .unwrap_or(MIN_CONTEXT_LENGTH)
+        .max(MIN_CONTEXT_LENGTH);
+    let context = repo
+        .get_last_messages(
+            conversation_id,
+            (context_length - 1) * 2 + 1,
+            before_message_id,
+        )
+        .await?;
+    let config = repo.get_conversation_config(conversation_id).await?;
+    let client = LLMClient::new(config, proxy_setting)?;
+    let result = client
+        .generate_images(&options.provider, context, image_options)
+        .await?;
+    if result.images.is_empty() {
+        return Err("No image was generated".to_string());
+    }
+    info(log_tag, format!("Generated {} images", result.images.len()));
+    let timestamp = chrono::Local::now().timestamp_millis();
+    let mut content = vec![];
+    for (i, image) in result.images.into_iter().enumerate() {
+        let extension = image.mimetype.rsplit('/').next().unwrap_or("png");
+        let name = format!("{}_generated_{}.{}", timestamp, i + 1, extension);
+        let attachment =
+            attachments::store_bytes(repo, image.bytes, &name, &image.mimetype).await?;
+        content.push(ContentDTO {
+            r#type: ContentType::Image,
+            mimetype: Some(attachment.mimetype),
+            data: attachment.file_name,
+        });
+    }
+    if let Some(text) = result.text {
+        content.push(ContentDTO {
+            r#type: ContentType::Text,
+            mimetype: None,
+            data: text,
+        });
+    }
+    repo.create_message(MessageDTO {
+        conversation_id,
+        role: Roles::Bot.into(),
+        content,
+        ..Default::default()
+    })
+    .await
+}
@@ -0,0 +1,271 @@
+use std::{collections::HashMap, path::Path};
+
+use entity::entities::{
+    chunks::NewChunk,
+    collections::Model as Collection,
+    contents::ContentType,
+    documents::{Model as Document, NewDocument},
+    messages::{MessageDTO, Roles},
+    models::GenericConfig,
+    settings::ProxySetting,
+};
+
+use crate::log_utils::{info, warn};
+
+use super::{
+    db::Repository,
+    documents,
+    llm::{
+        client::LLMClient,
+        embeddings::{cosine_similarity, decode_vector, encode_vector},
+    },
+};
+
+/// Max number of characters in a chunk
+pub const CHUNK_SIZE: usize = 1000;
+/// Number of characters shared by consecutive chunks
+pub const CHUNK_OVERLAP: usize = 200;
+
+/// A chunk retrieved for a query
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetrievedChunk {
+    pub document: String,
+    pub text: String,
+    pub score: f32,
+}
+
+/// Split text into chunks of at most `chunk_size` characters overlapping by `overlap` characters.
+/// Chunks preferably end at a paragraph, line or sentence boundary.
+pub fn chunk_text(text: &str, chunk_size: usize, overlap: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut chunks = vec![];
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = (start + chunk_size).min(chars.len());
+        if end < chars.len() {
+            if let Some(pos) = find_break(&chars, start + chunk_size / 2, end) {
+                end = pos;
+            }
+        }
+        let chunk = chars[start..end].iter().collect::<String>().trim().to_string();
+        if !chunk.is_empty() {
+            chunks.push(chunk);
+        }
+        if end >= chars.len() {
+            break;
+        }
+        start = end.saturating_sub(overlap).max(start + 1);
+    }
+    chunks
+}
+
+/// Find the best position to end a chunk within `from..to`
+fn find_break(chars: &[char], from: usize, to: usize) -> Option<usize> {
+    let window = from..to;
+    let paragraph = window
+        .clone()
+        .rev()
+        .find(|&i| i > 0 && chars[i] == '\n' && chars[i - 1] == '\n');
+    let line = || window.clone().rev().find(|&i| chars[i] == '\n');
+    let sentence = || {
+        window
+            .clone()
+            .rev()
+            .find(|&i| i > 0 && chars[i].is_whitespace() && matches!(chars[i - 1], '.' | '!' | '?' | '。'))
+    };
+    let word = || window.clone().rev().find(|&i| chars[i].is_whitespace());
+    paragraph
+        .or_else(line)
+        .or_else(sentence)
+        .or_else(word)
+        .map(|i| i + 1)
+}
+
+fn build_client(
+    model: entity::entities::models::Model,
+    proxy_setting: Option<ProxySetting>,
+) -> Result<LLMClient, String> {
+    LLMClient::new(
+        GenericConfig {
+            provider: model.provider,
+            config: model.config,
+        },
+        proxy_setting,
+    )
+}
+
+/// Extract, chunk and embed a local file, then store it in a collection
+pub async fn ingest_document(
+    repo: &Repository,
+    collection: &Collection,
+    path: &str,
+    proxy_setting: Option<ProxySetting>,
+) -> Result<Document, String> {
+    let log_tag = "knowledge::ingest_document";
+    let extracted = documents::extract_text(Path::new(path))?;
+    let texts = chunk_text(&extracted.text, CHUNK_SIZE, CHUNK_OVERLAP);
+    if texts.is_empty() {
+        return Err(format!("No text found in {}", extracted.name));
+    }
+    let model = repo.get_model(collection.model_id).await?;
+    let client = build_client(model, proxy_setting)?;
+    let embeddings = client.embed(texts.clone()).await?;
+    info(
+        log_tag,
+        format!(
+            "Embedded {} chunks of {} with {} dimensions",
+            texts.len(),
+            extracted.name,
+            embeddings.dimensions
+        ),
+    );
+    let chunks = texts
+        .into_iter()
+        .zip(embeddings.vectors.iter())
+        .map(|(text, vector)| NewChunk {
+            text,
+            embedding: encode_vector(vector),
+        })
+        .collect();
+    repo.create_document_with_chunks(
+        NewDocument {
+            collection_id: collection.id,
+            name: extracted.name,
+            path: path.to_string(),
+            mimetype: extracted.mimetype,
+        },
+        chunks,
+    )
+    .await
+}
+
+/// Retrieve the top-k chunks of collections for a query.
+/// Collections are grouped by embedding model so that the query is embedded once per model.
+pub async fn retrieve(
+    repo: &Repository,
+    collections: Vec<Collection>,
+    query: &str,
+    top_k: usize,
+    proxy_setting: Option<ProxySetting>,
+) -> Result<Vec<RetrievedChunk>, String> {
+    let mut collections_by_model: HashMap<i32, Vec<i32>> = HashMap::new();
+    for collection in collections {
+        collections_by_model
+            .entry(collection.model_id)
+            .or_default()
+            .push(collection.id);
+    }
+    let mut retrieved = vec![];
+    for (model_id, collection_ids) in collections_by_model {
+        let model = repo.get_model(model_id).await?;
+        let client = build_client(model, proxy_setting.clone())?;
+        let embeddings = client.embed(vec![query.to_string()]).await?;
+        let query_vector = embeddings
+            .vectors
+            .first()
+            .ok_or("Failed to embed query".to_string())?;
+        let chunks = repo.list_chunks_of_collections(collection_ids).await?;
+        retrieved.extend(chunks.into_iter().map(|(chunk, document)| RetrievedChunk {
+            document: document.map(|d| d.name).unwrap_or_default(),
+            score: cosine_similarity(query_vector, &decode_vector(&chunk.embedding)),
+            text: chunk.text,
+        }));
+    }
+    retrieved.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    retrieved.truncate(top_k);
+    Ok(retrieved)
+}
+
+/// Prepend retrieved chunks, numbered for citation, to the text of a message
+pub fn augment_message(message: &mut MessageDTO, chunks: &[RetrievedChunk]) {
+    if chunks.is_empty() {
+        return;
+    }
+    let sources = chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| format!("[{}] ({})\n{}", i + 1, chunk.document, chunk.text))
+        .collect::<Vec<String>>()
+        .join("\n\n");
+    let preamble = format!(
+        "Use the following sources to answer when they are relevant, and cite them by their numbers, e.g. [1].\n\n{}\n\n---\n\n",
+        sources
+    );
+    match message
+        .content
+        .iter_mut()
+        .find(|item| item.r#type == ContentType::Text)
+    {
+        Some(item) => item.data = format!("{}{}", preamble, item.data),
+        None => {
+            warn("knowledge::augment_message", "Message has no text content to augment");
+        }
+    }
+}
+
+/// Inject chunks of the conversation's collections relevant to the last user message into the context.
+/// Failures are logged without interrupting the chat.
+pub async fn augment_context(
+    repo: &Repository,
+    conversation_id: i32,
+    context: &mut Vec<MessageDTO>,
+    top_k: usize,
+    proxy_setting: Option<ProxySetting>,
+) {
+    let log_tag = "knowledge::augment_context";
+    let collections = match repo.list_conversation_collections(conversation_id).await {
+        Ok(collections) => collections,
+        Err(err) => {
+            warn(log_tag, err);
+            return;
+        }
+    };
+    if collections.is_empty() {
+        return;
+    }
+    let message = match context
+        .iter_mut()
+        .rev()
+        .find(|message| Roles::from(message.role) == Roles::User)
+    {
+        Some(message) => message,
+        None => return,
+    };
+    let query = match message.get_text() {
+        Some(query) => query,
+        None => return,
+    };
+    match retrieve(repo, collections, &query, top_k, proxy_setting).await {
+        Ok(chunks) => {
+            info(log_tag, format!("Retrieved {} chunks", chunks.len()));
+            augment_message(message, &chunks);
+        }
+        Err(err) => warn(log_tag, format!("Failed to retrieve knowledge: {}", err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_text_short() {
+        assert_eq!(chunk_text("hello world", 100, 10), vec!["hello world"]);
+        assert!(chunk_text("   ", 100, 10).is_empty());
+    }
+
+    #[test]
+    fn test_chunk_text_prefers_paragraphs() {
+        let text = format!("{}\n\n{}", "a".repeat(60), "b".repeat(60));
+        let chunks = chunk_text(&text, 100, 0);
+        assert_eq!(chunks, vec!["a".repeat(60), "b".repeat(60)]);
+    }
+
+    #[test]
+    fn test_chunk_text_overlap() {
+        let text = "x".repeat(250);
+        let chunks = chunk_text(&text, 100, 20);
+        assert!(chunks.iter().all(|chunk| chunk.chars().count() <= 100));
+        assert_eq!(chunks.len(), 3);
+    }
+}
@@ -0,0 +1,184 @@
+use async_openai::{
+    config::{AzureConfig, Config},
+    error::{ApiError, OpenAIError},
+    types::{AudioInput, CreateTranscriptionRequestArgs},
+    Client,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::{Deserialize, Serialize};
+
+use super::providers::{
+    google::{
+        chat::{
+            GoogleChat, GoogleChatCompletionContent, GoogleChatCompletionContentPart,
+            GoogleChatCompletionContentPartInlineData, GoogleChatCompletionRequest, GoogleRole,
+        },
+        config::GoogleConfig,
+    },
+    openai::config::OpenAICompatibleConfig,
+};
+
+/// Instruction sent along with the audio to models transcribing through chat
+const TRANSCRIPTION_PROMPT: &str =
+    "Transcribe the speech in this audio verbatim. Reply with the transcript only.";
+const SPEECH_PATH: &str = "/audio/speech";
+
+/// Audio to transcribe
+pub struct AudioFile {
+    pub file_name: String,
+    pub mimetype: String,
+    pub bytes: Vec<u8>,
+}
+
+pub enum TranscriptionRequestExecutor<'c> {
+    OpenAITranscriptionRequestExecutor(&'c Client<OpenAICompatibleConfig>),
+    AzureTranscriptionRequestExecutor(&'c Client<AzureConfig>),
+    GoogleTranscriptionRequestExecutor(&'c Client<GoogleConfig>),
+}
+
+impl<'c> TranscriptionRequestExecutor<'c> {
+    pub fn openai(client: &'c Client<OpenAICompatibleConfig>) -> Self {
+        return TranscriptionRequestExecutor::OpenAITranscriptionRequestExecutor(client);
+    }
+
+    pub fn azure(client: &'c Client<AzureConfig>) -> Self {
+        return TranscriptionRequestExecutor::AzureTranscriptionRequestExecutor(client);
+    }
+
+    pub fn google(client: &'c Client<GoogleConfig>) -> Self {
+        return TranscriptionRequestExecutor::GoogleTranscriptionRequestExecutor(client);
+    }
+
+    pub async fn execute(&self, model: String, audio: AudioFile) -> Result<String, OpenAIError> {
+        match self {
+            TranscriptionRequestExecutor::OpenAITranscriptionRequestExecutor(client) => {
+                Self::execute_openai_compatible(client, model, audio).await
+            }
+            TranscriptionRequestExecutor::AzureTranscriptionRequestExecutor(client) => {
+                Self::execute_openai_compatible(client, model, audio).await
+            }
+            TranscriptionRequestExecutor::GoogleTranscriptionRequestExecutor(client) => {
+                // Gemini transcribes inline audio parts through generateContent
+                let request = GoogleChatCompletionRequest {
+                    contents: vec![GoogleChatCompletionContent {
+                        parts: Some(vec![
+                            GoogleChatCompletionContentPart::InlineData(
+                                GoogleChatCompletionContentPartInlineData {
+                                    mime_type: audio.mimetype,
+                                    data: STANDARD.encode(audio.bytes),
+                                },
+                            ),
+                            GoogleChatCompletionContentPart::Text(TRANSCRIPTION_PROMPT.to_string()),
+                        ]),
+                        role: GoogleRole::User,
+                    }],
+                    system_instruction: None,
+                    generation_config: None,
+                };
+                let response = GoogleChat::new(client).create(request).await?;
+                let text = response
+                    .candidates
+                    .first()
+                    .and_then(|candidate| candidate.content.parts.as_ref())
+                    .map(|parts| {
+                        parts
+                            .iter()
+                            .filter_map(|part| match part {
+                                GoogleChatCompletionContentPart::Text(text) => Some(text.as_str()),
+                                _ => None,
+                            })
+                            .collect::<Vec<&str>>()
+                            .join("")
+                    })
+                    .unwrap_or_default();
+                Ok(text.trim().to_string())
+            }
+        }
+    }
+
+    /// Transcribe with `/audio/transcriptions`, which local whisper servers also implement
+    async fn execute_openai_compatible<C: Config>(
+        client: &Client<C>,
+        model: String,
+        audio: AudioFile,
+    ) -> Result<String, OpenAIError> {
+        let request = CreateTranscriptionRequestArgs::default()
+            .file(AudioInput::from_vec_u8(audio.file_name, audio.bytes))
+            .model(model)
+            .build()?;
+        let response = client.audio().transcribe(request).await?;
+        Ok(response.text.trim().to_string())
+    }
+}
+
+/// Request of `/audio/speech`.
+/// Voice is a plain string so that voices of local servers can be used too.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct SpeechRequest {
+    pub model: String,
+    pub input: String,
+    pub voice: String,
+    pub response_format: String, // mp3, opus, aac, flac, wav or pcm
+    pub speed: f32,              // min: 0.25, max: 4.0, default: 1.0
+}
+
+#[derive(Debug, Deserialize)]
+struct SpeechErrorResponse {
+    error: ApiError,
+}
+
+pub enum SpeechRequestExecutor<'c> {
+    OpenAISpeechRequestExecutor(&'c Client<OpenAICompatibleConfig>),
+    AzureSpeechRequestExecutor(&'c Client<AzureConfig>),
+}
+
+impl<'c> SpeechRequestExecutor<'c> {
+    pub fn openai(client: &'c Client<OpenAICompatibleConfig>) -> Self {
+        return SpeechRequestExecutor::OpenAISpeechRequestExecutor(client);
+    }
+
+    pub fn azure(client: &'c Client<AzureConfig>) -> Self {
+        return SpeechRequestExecutor::AzureSpeechRequestExecutor(client);
+    }
+
+    pub async fn execute(&self, request: SpeechRequest) -> Result<Vec<u8>, OpenAIError> {
+        match self {
+            SpeechRequestExecutor::OpenAISpeechRequestExecutor(client) => {
+                Self::execute_openai_compatible(client, request).await
+            }
+            SpeechRequestExecutor::AzureSpeechRequestExecutor(client) => {
+                Self::execute_openai_compatible(client, request).await
+            }
+        }
+    }
+
+    /// Synthesize speech with `/audio/speech`, whose response is the audio file itself
+    async fn execute_openai_compatible<C: Config>(
+        client: &Client<C>,
+        request: SpeechRequest,
+    ) -> Result<Vec<u8>, OpenAIError> {
+        let config = client.config();
+        let response = client
+            .http_client()
+            .post(config.url(SPEECH_PATH))
+            .query(&config.query())
+            .headers(config.headers())
+            .json(&request)
+            .send()
+            .await
+            .map_err(OpenAIError::Reqwest)?;
+        let status = response.status();
+        let bytes = response.bytes().await.map_err(OpenAIError::Reqwest)?;
+        if !status.is_success() {
+            return Err(match serde_json::from_slice::<SpeechErrorResponse>(&bytes) {
+                Ok(response) => OpenAIError::ApiError(response.error),
+                Err(_) => OpenAIError::InvalidArgument(format!(
+                    "{}: {}",
+                    status,
+                    String::from_utf8_lossy(&bytes)
+                )),
+            });
+        }
+        Ok(bytes.to_vec())
+    }
+}
@@ -0,0 +1,327 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use entity::entities::{
+    contents::{ContentDTO, ContentType, MIMETYPE_TRANSCRIPT},
+    messages::{MessageDTO, Roles},
+    models::Providers,
+};
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+
+use crate::log_utils::warn;
+
+use super::{
+    models::normalize_model_id,
+    utils::{remove_contents, remove_contents_of_type},
+};
+
+/// What a model accepts in a chat request.
+/// Unknown models are assumed to support everything, so that nothing is stripped from their requests,
+/// except audio input which is only sent natively to models known to accept it.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelCapabilities {
+    /// Accepts image parts in user messages
+    pub vision: bool,
+    /// Accepts audio parts in user messages, otherwise their transcripts are sent
+    pub audio: bool,
+    /// Supports tool calling
+    pub tools: bool,
+    /// Supports JSON response format
+    pub json_mode: bool,
+    /// Accepts a reasoning effort
+    pub reasoning: bool,
+    /// Accepts sampling parameters such as temperature and top_p
+    pub sampling: bool,
+    /// Accepts messages with the system role
+    pub system_role: bool,
+    /// Edits images with `/images/edits`, otherwise images are only generated
+    pub image_edits: bool,
+    /// Max number of tokens in the context window
+    pub max_context: Option<u32>,
+    /// Max number of tokens the model can generate
+    pub max_output: Option<u32>,
+}
+
+impl Default for ModelCapabilities {
+    fn default() -> Self {
+        Self {
+            vision: true,
+            audio: false,
+            tools: true,
+            json_mode: true,
+            reasoning: true,
+            sampling: true,
+            system_role: true,
+            image_edits: true,
+            max_context: None,
+            max_output: None,
+        }
+    }
+}
+
+impl ModelCapabilities {
+    /// Built-in capabilities of well-known models
+    pub fn defaults_for(provider: &str, model: &str) -> Self {
+        let model = model.to_lowercase();
+        let mut caps = Self::default();
+        match Providers::from(provider) {
+            Providers::OpenAI => {
+                if model.starts_with("o1-mini") || model.starts_with("o1-preview") {
+                    caps.vision = false;
+                    caps.tools = false;
+                    caps.json_mode = false;
+                    caps.sampling = false;
+                    caps.system_role = false;
+                    caps.reasoning = false;
+                } else if model.starts_with("o3-mini") {
+                    caps.vision = false;
+                    caps.sampling = false;
+                } else if model.starts_with("o1")
+                    || model.starts_with("o3")
+                    || model.starts_with("o4")
+                    || model.starts_with("gpt-5")
+                {
+                    caps.sampling = false;
+                } else if model.starts_with("gpt-3.5") {
+                    caps.vision = false;
+                    caps.reasoning = false;
+                    caps.max_context = Some(16_385);
+                    caps.max_output = Some(4_096);
+                } else if model.starts_with("dall-e-3") {
+                    // DALL·E 3 has no edits endpoint
+                    caps.image_edits = false;
+                    caps.reasoning = false;
+                } else if model.starts_with("gpt-4o") {
+                    caps.reasoning = false;
+                    caps.max_context = Some(128_000);
+                    caps.max_output = Some(16_384);
+                } else {
+                    caps.reasoning = false;
+                }
+            }
+            Providers::Deepseek => {
+                // Deepseek's models are text only
+                caps.vision = false;
+                caps.reasoning = false;
+                if model == "deepseek-reasoner" {
+                    caps.tools = false;
+                    caps.sampling = false;
+                }
+            }
+            Providers::Claude => {
+                caps.json_mode = false;
+                caps.reasoning = false;
+            }
+            Providers::Azure | Providers::Openrouter | Providers::Xai => {
+                caps.reasoning = false;
+            }
+            Providers::Google => {
+                caps.audio = model.starts_with("gemini");
+            }
+            _ => {}
+        }
+        caps
+    }
+
+    /// Strip contents and roles the model doesn't accept from messages
+    pub fn adapt_messages(&self, messages: Vec<MessageDTO>, log_tag: &str) -> Vec<MessageDTO> {
+        let messages = if self.vision {
+            messages
+        } else {
+            let (messages, removed) = remove_contents_of_type(messages, ContentType::Image);
+            if removed > 0 {
+                warn(
+                    log_tag,
+                    format!("Model doesn't support images, {} image(s) removed from request", removed),
+                );
+            }
+            messages
+        };
+        let messages = if self.audio {
+            // the audio is sent as is, its transcript would be redundant
+            remove_contents(messages, is_transcript).0
+        } else {
+            let (messages, removed) = remove_contents_of_type(messages, ContentType::Audio);
+            if removed > 0 {
+                warn(
+                    log_tag,
+                    format!("Model doesn't support audio, {} audio(s) replaced by their transcripts", removed),
+                );
+            }
+            messages
+        };
+        if self.system_role {
+            return messages;
+        }
+        messages
+            .into_iter()
+            .map(|mut message| {
+                if Roles::from(message.role) == Roles::System {
+                    warn(log_tag, "Model doesn't support system role, system message sent as user message");
+                    message.role = Roles::User.into();
+                }
+                message
+            })
+            .collect()
+    }
+
+    /// Drop a sampling parameter if the model doesn't accept it
+    pub fn adapt_sampling(&self, value: Option<f32>, name: &str, log_tag: &str) -> Option<f32> {
+        if self.sampling || value.is_none() {
+            return value;
+        }
+        warn(log_tag, format!("Model doesn't support {}, option ignored", name));
+        None
+    }
+
+    /// Drop the reasoning effort if the model doesn't accept it
+    pub fn adapt_reasoning<T>(&self, value: Option<T>, log_tag: &str) -> Option<T> {
+        if self.reasoning || value.is_none() {
+            return value;
+        }
+        warn(log_tag, "Model doesn't support reasoning effort, option ignored");
+        None
+    }
+
+    /// Clamp the max tokens to the max output of the model
+    pub fn adapt_max_tokens(&self, value: Option<u32>, log_tag: &str) -> Option<u32> {
+        match (value, self.max_output) {
+            (Some(tokens), Some(max_output)) if tokens > max_output => {
+                warn(
+                    log_tag,
+                    format!("Max tokens {} exceeds model's max output, clamped to {}", tokens, max_output),
+                );
+                Some(max_output)
+            }
+            _ => value,
+        }
+    }
+}
+
+/// Whether the content is the transcript of the audio preceding it
+pub fn is_transcript(item: &ContentDTO) -> bool {
+    item.r#type == ContentType::Text && item.mimetype.as_deref() == Some(MIMETYPE_TRANSCRIPT)
+}
+
+/// Registry of model capabilities keyed by provider and model id, without Ollama's implicit `latest` tag.
+/// Entries are learnt from the metadata returned by list models APIs and take precedence over built-in defaults.
+pub struct CapabilityRegistry {
+    entries: Arc<RwLock<HashMap<(String, String), ModelCapabilities>>>,
+}
+
+impl CapabilityRegistry {
+    pub fn global() -> &'static CapabilityRegistry {
+        static INSTANCE: OnceCell<CapabilityRegistry> = OnceCell::new();
+
+        INSTANCE.get_or_init(|| CapabilityRegistry {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    pub fn get(&self, provider: &str, model: &str) -> ModelCapabilities {
+        let entries = self
+            .entries
+            .read()
+            .expect("Failed to lock capability registry");
+        entries
+            .get(&(provider.to_string(), normalize_model_id(model).to_string()))
+            .cloned()
+            .unwrap_or_else(|| ModelCapabilities::defaults_for(provider, model))
+    }
+
+    /// Update the capabilities of a model, starting from the current entry or the defaults
+    pub fn update<F: FnOnce(&mut ModelCapabilities)>(&self, provider: &str, model: &str, f: F) {
+        let mut caps = self.get(provider, model);
+        f(&mut caps);
+        let mut entries = self
+            .entries
+            .write()
+            .expect("Failed to lock capability registry");
+        entries.insert((provider.to_string(), normalize_model_id(model).to_string()), caps);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_o_series_defaults() {
+        let caps = ModelCapabilities::defaults_for("OpenAI", "o1-mini");
+        assert!(!caps.sampling);
+        assert!(!caps.system_role);
+        assert!(!caps.vision);
+
+        let caps = ModelCapabilities::defaults_for("OpenAI", "o3");
+        assert!(!caps.sampling);
+        assert!(caps.reasoning);
+        assert!(caps.vision);
+    }
+
+    #[test]
+    fn test_unknown_model_defaults() {
+        assert_eq!(
+            ModelCapabilities::defaults_for("CUSTOM", "my-model"),
+            ModelCapabilities::default()
+        );
+        assert!(!ModelCapabilities::defaults_for("OpenAI", "gpt-4o").reasoning);
+        assert!(!ModelCapabilities::defaults_for("OpenAI", "dall-e-3").image_edits);
+        assert!(ModelCapabilities::defaults_for("OpenAI", "gpt-image-1").image_edits);
+    }
+
+    #[test]
+    fn test_adapt_options() {
+        let caps = ModelCapabilities::defaults_for("OpenAI", "o1");
+        assert_eq!(caps.adapt_sampling(Some(0.7), "temperature", "test"), None);
+        assert_eq!(caps.adapt_reasoning(Some("high"), "test"), Some("high"));
+
+        let caps = ModelCapabilities::defaults_for("OpenAI", "gpt-4o");
+        assert_eq!(caps.adapt_max_tokens(Some(100_000), "test"), Some(16_384));
+        assert_eq!(caps.adapt_max_tokens(Some(256), "test"), Some(256));
+    }
+
+    #[test]
+    fn test_adapt_audio_messages() {
+        let message = MessageDTO {
+            role: Roles::User.into(),
+            content: vec![
+                ContentDTO {
+                    r#type: ContentType::Audio,
+                    mimetype: Some("audio/wav".to_string()),
+                    data: "1_memo.wav".to_string(),
+                },
+                ContentDTO {
+                    r#type: ContentType::Text,
+                    mimetype: Some(MIMETYPE_TRANSCRIPT.to_string()),
+                    data: "hello".to_string(),
+                },
+            ],
+            ..Default::default()
+        };
+        let caps = ModelCapabilities::defaults_for("OpenAI", "gpt-4o");
+        let messages = caps.adapt_messages(vec![message.clone()], "test");
+        assert_eq!(messages[0].content.len(), 1);
+        assert_eq!(messages[0].content[0].r#type, ContentType::Text);
+
+        let caps = ModelCapabilities::defaults_for("Google", "gemini-2.0-flash");
+        let messages = caps.adapt_messages(vec![message], "test");
+        assert_eq!(messages[0].content.len(), 1);
+        assert_eq!(messages[0].content[0].r#type, ContentType::Audio);
+    }
+
+    #[test]
+    fn test_registry_overrides_defaults() {
+        let registry = CapabilityRegistry::global();
+        registry.update("Ollama", "llava:7b", |caps| caps.max_context = Some(4096));
+        let caps = registry.get("Ollama", "llava:7b");
+        assert_eq!(caps.max_context, Some(4096));
+        assert!(caps.vision);
+
+        registry.update("Ollama", "phi3:latest", |caps| caps.vision = false);
+        assert!(!registry.get("Ollama", "phi3").vision);
+    }
+}
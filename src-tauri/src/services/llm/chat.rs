@@ -8,6 +8,7 @@ use async_openai::{
     Client,
 };
 use entity::entities::{
+    contents::ContentType,
     conversations::{AzureOptions, ClaudeOptions, DeepseekOptions, GenericOptions, GoogleOptions, OllamaOptions, OpenAIOptions, XaiOptions},
     messages::MessageDTO,
 };
@@ -15,6 +16,7 @@ use serde::Serialize;
 use tokio_stream::{Stream, StreamExt};
 
 use super::{
+    capabilities::CapabilityRegistry,
     providers::{
         claude::{
             chat::{
@@ -29,9 +31,9 @@ use super::{
                 OllamaMessage,
             },
             config::OllamaConfig,
-        }, openai::chat::{OpenAIChat, OpenAIChatCompletionRequest, OpenAIChatCompletionResponseStream}, openrouter::chat::{OpenrouterChat, OpenrouterChatCompletionRequest, OpenrouterChatCompletionResponseStream}, types::{ChatCompletionRequestCommon, ChatCompletionStreamOptions}, xai::{chat::{XaiChat, XaiChatCompletionRequest, XaiChatCompletionResponseStream}, config::XaiConfig}
+        }, openai::{chat::{OpenAIChat, OpenAIChatCompletionRequest, OpenAIChatCompletionResponseStream}, config::OpenAICompatibleConfig}, openrouter::chat::{OpenrouterChat, OpenrouterChatCompletionRequest, OpenrouterChatCompletionResponseStream}, types::{ChatCompletionRequestCommon, ChatCompletionStreamOptions}, xai::{chat::{XaiChat, XaiChatCompletionRequest, XaiChatCompletionResponseStream}, config::XaiConfig}
     },
-    utils::{message_to_google_request_message, message_to_openai_request_message, sum_option},
+    utils::{message_to_google_request_message, message_to_openai_request_message, remove_contents_of_type, sum_option},
 };
 
 #[derive(Clone, Debug, Default, PartialEq, Serialize)]
@@ -62,7 +64,7 @@ pub struct GlobalSettings {
 }
 
 pub enum ChatRequestExecutor<'c> {
-    OpenAIChatRequestExecutor(&'c Client<OpenAIConfig>, OpenAIChatCompletionRequest),
+    OpenAIChatRequestExecutor(&'c Client<OpenAICompatibleConfig>, OpenAIChatCompletionRequest),
     AzureChatRequestExecutor(&'c Client<AzureConfig>, OpenAIChatCompletionRequest),
     ClaudeChatRequestExecutor(&'c Client<ClaudeConfig>, ClaudeChatCompletionRequest),
     OllamaChatRequestExecutor(&'c Client<OllamaConfig>, OllamaChatCompletionRequest),
@@ -74,30 +76,65 @@ pub enum ChatRequestExecutor<'c> {
 
 impl<'c> ChatRequestExecutor<'c> {
     pub fn openai(
-        client: &'c Client<OpenAIConfig>,
+        client: &'c Client<OpenAICompatibleConfig>,
         messages: Vec<MessageDTO>,
         options: GenericOptions,
         global_settings: GlobalSettings,
         model: String,
     ) -> Result<ChatRequestExecutor, String> {
+        let log_tag = "ChatRequestExecutor::openai";
+        let profile = client.config().profile();
+        let caps = CapabilityRegistry::global().get(&options.provider, &model);
         let request: OpenAIChatCompletionRequest;
         // set messages
-        let req_messages = messages
+        let messages = if profile.supports_image_parts {
+            messages
+        } else {
+            let (messages, removed) = remove_contents_of_type(messages, ContentType::Image);
+            if removed > 0 {
+                warn(
+                    log_tag,
+                    format!(
+                        "Profile '{}' doesn't support images, {} image(s) removed from request",
+                        profile.name, removed
+                    ),
+                );
+            }
+            messages
+        };
+        let req_messages = caps
+            .adapt_messages(messages, log_tag)
             .into_iter()
             .map(message_to_openai_request_message)
             .collect();
         // set options
         let options: OpenAIOptions = serde_json::from_str(&options.options)
             .map_err(|_| format!("Failed to parse conversation options: {}", &options.options))?;
+        let max_tokens = caps.adapt_max_tokens(options.max_tokens.or(Some(global_settings.max_tokens)), log_tag);
+        let reasoning_effort = if profile.supports_reasoning_effort {
+            caps.adapt_reasoning(options.reasoning_effort.map(|x| x.into()), log_tag)
+        } else {
+            if options.reasoning_effort.is_some() {
+                warn(
+                    log_tag,
+                    format!("Profile '{}' doesn't support reasoning_effort, option ignored", profile.name),
+                );
+            }
+            None
+        };
         // build request
         request = OpenAIChatCompletionRequest {
             common: ChatCompletionRequestCommon {
                 model: model.to_string(),
-                frequency_penalty: options.frequency_penalty,
-                max_tokens: options.max_tokens.or(Some(global_settings.max_tokens)),
-                presence_penalty: options.presence_penalty,
+                frequency_penalty: caps.adapt_sampling(options.frequency_penalty, "frequency_penalty", log_tag),
+                max_tokens: if profile.supports_max_completion_tokens {
+                    None
+                } else {
+                    max_tokens
+                },
+                presence_penalty: caps.adapt_sampling(options.presence_penalty, "presence_penalty", log_tag),
                 stream: options.stream,
-                stream_options: if options.stream.unwrap_or(false) {
+                stream_options: if options.stream.unwrap_or(false) && profile.supports_stream_options {
                     // default to return usage when streaming
                     Some(ChatCompletionStreamOptions {
                         include_usage: true
@@ -105,11 +142,16 @@ impl<'c> ChatRequestExecutor<'c> {
                 } else {
                     None
                 },
-                temperature: options.temperature,
-                top_p: options.top_p,
+                temperature: caps.adapt_sampling(options.temperature, "temperature", log_tag),
+                top_p: caps.adapt_sampling(options.top_p, "top_p", log_tag),
                 ..Default::default()
             },
-            reasoning_effort: options.reasoning_effort.map(|x| x.into()),
+            max_completion_tokens: if profile.supports_max_completion_tokens {
+                max_tokens
+            } else {
+                None
+            },
+            reasoning_effort,
             messages: req_messages,
             user: options.user,
             ..Default::default()
@@ -122,11 +164,14 @@ impl<'c> ChatRequestExecutor<'c> {
         messages: Vec<MessageDTO>,
         options: GenericOptions,
         global_settings: GlobalSettings,
-        _model: String,
+        model: String,
     ) -> Result<ChatRequestExecutor, String> {
         let request: OpenAIChatCompletionRequest;
+        let log_tag = "ChatRequestExecutor::azure";
+        let caps = CapabilityRegistry::global().get(&options.provider, &model);
         // set messages
-        let req_messages = messages
+        let req_messages = caps
+            .adapt_messages(messages, log_tag)
             .into_iter()
             .map(message_to_openai_request_message)
             .collect();
@@ -136,12 +181,12 @@ impl<'c> ChatRequestExecutor<'c> {
         // build request
         request = OpenAIChatCompletionRequest {
             common: ChatCompletionRequestCommon {
-                frequency_penalty: options.frequency_penalty,
-                max_tokens: options.max_tokens.or(Some(global_settings.max_tokens)),
-                presence_penalty: options.presence_penalty,
+                frequency_penalty: caps.adapt_sampling(options.frequency_penalty, "frequency_penalty", log_tag),
+                max_tokens: caps.adapt_max_tokens(options.max_tokens.or(Some(global_settings.max_tokens)), log_tag),
+                presence_penalty: caps.adapt_sampling(options.presence_penalty, "presence_penalty", log_tag),
                 stream: options.stream,
-                temperature: options.temperature,
-                top_p: options.top_p,
+                temperature: caps.adapt_sampling(options.temperature, "temperature", log_tag),
+                top_p: caps.adapt_sampling(options.top_p, "top_p", log_tag),
                 ..Default::default()
             },
             messages: req_messages,
@@ -159,8 +204,11 @@ impl<'c> ChatRequestExecutor<'c> {
         model: String,
     ) -> Result<ChatRequestExecutor, String> {
         let request: ClaudeChatCompletionRequest;
+        let log_tag = "ChatRequestExecutor::claude";
+        let caps = CapabilityRegistry::global().get(&options.provider, &model);
         // set messages
-        let req_messages: Vec<ClaudeMessage> = messages
+        let req_messages: Vec<ClaudeMessage> = caps
+            .adapt_messages(messages, log_tag)
             .into_iter()
             .map(Into::<ClaudeMessage>::into)
             .collect();
@@ -171,10 +219,10 @@ impl<'c> ChatRequestExecutor<'c> {
         request = ClaudeChatCompletionRequest {
             common: ChatCompletionRequestCommon {
                 model: model.to_string(),
-                max_tokens: options.max_tokens.or(Some(global_settings.max_tokens)), // Claude requires max_tokens
+                max_tokens: caps.adapt_max_tokens(options.max_tokens.or(Some(global_settings.max_tokens)), log_tag), // Claude requires max_tokens
                 stream: options.stream,
-                temperature: options.temperature,
-                top_p: options.top_p,
+                temperature: caps.adapt_sampling(options.temperature, "temperature", log_tag),
+                top_p: caps.adapt_sampling(options.top_p, "top_p", log_tag),
                 ..Default::default()
             },
             messages: req_messages,
@@ -192,8 +240,11 @@ impl<'c> ChatRequestExecutor<'c> {
         model: String,
     ) -> Result<ChatRequestExecutor, String> {
         let request: OllamaChatCompletionRequest;
+        let log_tag = "ChatRequestExecutor::ollama";
+        let caps = CapabilityRegistry::global().get(&options.provider, &model);
         // set messages
-        let req_messages: Vec<OllamaMessage> = messages
+        let req_messages: Vec<OllamaMessage> = caps
+            .adapt_messages(messages, log_tag)
             .into_iter()
             .map(Into::<OllamaMessage>::into)
             .collect();
@@ -223,8 +274,11 @@ impl<'c> ChatRequestExecutor<'c> {
         global_settings: GlobalSettings,
         model: String,
     ) -> Result<ChatRequestExecutor, String> {
+        let log_tag = "ChatRequestExecutor::openrouter";
+        let caps = CapabilityRegistry::global().get(&options.provider, &model);
         // set messages
-        let req_messages: Vec<ChatCompletionRequestMessage> = messages
+        let req_messages: Vec<ChatCompletionRequestMessage> = caps
+            .adapt_messages(messages, log_tag)
             .into_iter()
             .map(message_to_openai_request_message)
             .collect();
@@ -236,11 +290,11 @@ impl<'c> ChatRequestExecutor<'c> {
             common: ChatCompletionRequestCommon {
                 model: model.to_string(),
                 stream: options.stream,
-                temperature: options.temperature,
-                top_p: options.top_p,
-                max_tokens: options.max_tokens.or(Some(global_settings.max_tokens)),
-                frequency_penalty: options.frequency_penalty,
-                presence_penalty: options.presence_penalty,
+                temperature: caps.adapt_sampling(options.temperature, "temperature", log_tag),
+                top_p: caps.adapt_sampling(options.top_p, "top_p", log_tag),
+                max_tokens: caps.adapt_max_tokens(options.max_tokens.or(Some(global_settings.max_tokens)), log_tag),
+                frequency_penalty: caps.adapt_sampling(options.frequency_penalty, "frequency_penalty", log_tag),
+                presence_penalty: caps.adapt_sampling(options.presence_penalty, "presence_penalty", log_tag),
                 ..Default::default()
             },
             messages: req_messages,
@@ -258,8 +312,11 @@ impl<'c> ChatRequestExecutor<'c> {
         model: String,
     ) -> Result<ChatRequestExecutor, String> {
         let request: DeepseekChatCompletionRequest;
+        let log_tag = "ChatRequestExecutor::deepseek";
+        let caps = CapabilityRegistry::global().get(&options.provider, &model);
         // set messages
-        let req_messages = messages
+        let req_messages = caps
+            .adapt_messages(messages, log_tag)
             .into_iter()
             .map(message_to_openai_request_message)
             .collect();
@@ -271,11 +328,11 @@ impl<'c> ChatRequestExecutor<'c> {
             common: ChatCompletionRequestCommon {
                 model: model.to_string(),
                 stream: options.stream,
-                temperature: options.temperature,
-                top_p: options.top_p,
-                max_tokens: options.max_tokens.or(Some(global_settings.max_tokens)),
-                frequency_penalty: options.frequency_penalty,
-                presence_penalty: options.presence_penalty,
+                temperature: caps.adapt_sampling(options.temperature, "temperature", log_tag),
+                top_p: caps.adapt_sampling(options.top_p, "top_p", log_tag),
+                max_tokens: caps.adapt_max_tokens(options.max_tokens.or(Some(global_settings.max_tokens)), log_tag),
+                frequency_penalty: caps.adapt_sampling(options.frequency_penalty, "frequency_penalty", log_tag),
+                presence_penalty: caps.adapt_sampling(options.presence_penalty, "presence_penalty", log_tag),
                 stream_options: if options.stream.unwrap_or(false) {
                     // default to return usage when streaming
                     Some(ChatCompletionStreamOptions {
@@ -299,8 +356,11 @@ impl<'c> ChatRequestExecutor<'c> {
         model: String,
     ) -> Result<ChatRequestExecutor, String> {
         let request: XaiChatCompletionRequest;
+        let log_tag = "ChatRequestExecutor::xai";
+        let caps = CapabilityRegistry::global().get(&options.provider, &model);
         // set messages
-        let req_messages = messages
+        let req_messages = caps
+            .adapt_messages(messages, log_tag)
             .into_iter()
             .map(message_to_openai_request_message)
             .collect();
@@ -312,11 +372,11 @@ impl<'c> ChatRequestExecutor<'c> {
             common: ChatCompletionRequestCommon {
                 model: model.to_string(),
                 stream: options.stream,
-                temperature: options.temperature,
-                top_p: options.top_p,
-                max_tokens: options.max_tokens.or(Some(global_settings.max_tokens)),
-                frequency_penalty: options.frequency_penalty,
-                presence_penalty: options.presence_penalty,
+                temperature: caps.adapt_sampling(options.temperature, "temperature", log_tag),
+                top_p: caps.adapt_sampling(options.top_p, "top_p", log_tag),
+                max_tokens: caps.adapt_max_tokens(options.max_tokens.or(Some(global_settings.max_tokens)), log_tag),
+                frequency_penalty: caps.adapt_sampling(options.frequency_penalty, "frequency_penalty", log_tag),
+                presence_penalty: caps.adapt_sampling(options.presence_penalty, "presence_penalty", log_tag),
                 stream_options: if options.stream.unwrap_or(false) {
                     // default to return usage when streaming
                     Some(ChatCompletionStreamOptions {
@@ -337,11 +397,14 @@ impl<'c> ChatRequestExecutor<'c> {
         messages: Vec<MessageDTO>,
         options: GenericOptions,
         global_settings: GlobalSettings,
-        _model: String,
+        model: String,
     ) -> Result<ChatRequestExecutor, String> {
         let request: GoogleChatCompletionRequest;
+        let log_tag = "ChatRequestExecutor::google";
+        let caps = CapabilityRegistry::global().get(&options.provider, &model);
         // set messages
-        let req_messages = messages
+        let req_messages = caps
+            .adapt_messages(messages, log_tag)
             .into_iter()
             .map(message_to_google_request_message)
             .collect();
@@ -353,11 +416,11 @@ impl<'c> ChatRequestExecutor<'c> {
             contents: req_messages,
             system_instruction: None,
             generation_config: Some(GoogleChatCompletionRequestGenerationConfig {
-                max_output_tokens: options.max_tokens.or(Some(global_settings.max_tokens)),
-                temperature: options.temperature,
-                top_p: options.top_p,
-                presence_penalty: options.presence_penalty,
-                frequency_penalty: options.frequency_penalty,
+                max_output_tokens: caps.adapt_max_tokens(options.max_tokens.or(Some(global_settings.max_tokens)), log_tag),
+                temperature: caps.adapt_sampling(options.temperature, "temperature", log_tag),
+                top_p: caps.adapt_sampling(options.top_p, "top_p", log_tag),
+                presence_penalty: caps.adapt_sampling(options.presence_penalty, "presence_penalty", log_tag),
+                frequency_penalty: caps.adapt_sampling(options.frequency_penalty, "frequency_penalty", log_tag),
                 ..Default::default()
             }),
         };
@@ -670,7 +733,8 @@ impl<'c> ChatRequestExecutor<'c> {
                             .map(|part| {
                                 match part {
                                     GoogleChatCompletionContentPart::Text(text) => text.clone(),
-                                    GoogleChatCompletionContentPart::FileData(_) => String::default(),
+                                    GoogleChatCompletionContentPart::FileData(_)
+                                    | GoogleChatCompletionContentPart::InlineData(_) => String::default(),
                                 }
                             })
                             .collect::<Vec<String>>()
@@ -933,7 +997,8 @@ impl<'c> ChatRequestExecutor<'c> {
                                         .map(|part| {
                                             match part {
                                                 GoogleChatCompletionContentPart::Text(text) => text.clone(),
-                                                GoogleChatCompletionContentPart::FileData(_) => String::default(),
+                                                GoogleChatCompletionContentPart::FileData(_)
+                                                | GoogleChatCompletionContentPart::InlineData(_) => String::default(),
                                             }
                                         }).collect::<Vec<String>>()
                                         .join("")
@@ -1,25 +1,29 @@
 use async_openai::{
     config::{AzureConfig, Config, OpenAIConfig},
+    error::OpenAIError,
     Client,
 };
 use entity::entities::{
-    conversations::GenericOptions,
+    conversations::{GenericOptions, ImageGenerationOptions},
     messages::MessageDTO,
     models::{GenericConfig, Providers},
     settings::ProxySetting,
 };
 use reqwest;
 
+use crate::services::attachments;
+
 use super::{
-    chat::{BotReply, BotReplyStream, ChatRequestExecutor, GlobalSettings}, models::{ListModelsRequestExecutor, RemoteModel}, providers::{
-        claude::config::ClaudeConfig, deepseek::config::DeepseekConfig, google::config::GoogleConfig, ollama::config::OllamaConfig, openrouter::config::DEFAULT_OPENROUTER_API_BASE, xai::config::XaiConfig
+    audio::{AudioFile, SpeechRequest, SpeechRequestExecutor, TranscriptionRequestExecutor},
+    chat::{BotReply, BotReplyStream, ChatRequestExecutor, GlobalSettings}, embeddings::{EmbeddingRequestExecutor, EmbeddingResult}, images::{ImageGenerationRequestExecutor, ImageGenerationResult}, models::{ListModelsRequestExecutor, RemoteModel}, providers::{
+        claude::config::ClaudeConfig, deepseek::config::DeepseekConfig, google::config::GoogleConfig, ollama::config::OllamaConfig, openai::{config::OpenAICompatibleConfig, profiles::{PROFILE_GENERIC, PROFILE_OPENAI}}, openrouter::config::DEFAULT_OPENROUTER_API_BASE, xai::config::XaiConfig
     }, types::{RawAzureConfig, RawClaudeConfig, RawDeepseekConfig, RawGoogleConfig, RawOllamaConfig, RawOpenAIConfig, RawXaiConfig}, utils::build_http_client
 };
 
 /// Wrapper of async-openai's Client struct
 #[derive(Debug, Clone)]
 pub enum LLMClient {
-    OpenAIClient(Client<OpenAIConfig>, Option<String>),
+    OpenAIClient(Client<OpenAICompatibleConfig>, Option<String>),
     AzureClient(Client<AzureConfig>, Option<String>),
     ClaudeClient(Client<ClaudeConfig>, Option<String>),
     OllamaClient(Client<OllamaConfig>, Option<String>),
@@ -40,11 +44,17 @@ impl LLMClient {
                 let client = Client::with_config(raw_config.into()).with_http_client(http_client);
                 Ok(LLMClient::AzureClient(client, Some(String::default()))) // Azure doesn't require model, so use a blank string here
             }
-            Providers::OpenAI | Providers::CUSTOM => {
+            provider @ (Providers::OpenAI | Providers::CUSTOM) => {
                 let raw_config: RawOpenAIConfig = serde_json::from_str(&config.config)
                     .map_err(|_| format!("Failed to parse model config: {}", &config.config))?;
                 let model = raw_config.model.clone();
-                let client = Client::with_config(raw_config.into()).with_http_client(http_client);
+                // CUSTOM models may point to any OpenAI-compatible server
+                let default_profile = match provider {
+                    Providers::OpenAI => PROFILE_OPENAI,
+                    _ => PROFILE_GENERIC,
+                };
+                let client = Client::with_config(raw_config.into_compatible_config(default_profile))
+                    .with_http_client(http_client);
                 Ok(LLMClient::OpenAIClient(client, model))
             }
             Providers::Claude => {
@@ -110,6 +120,7 @@ impl LLMClient {
         F: FnOnce(&'c Client<C>, Vec<MessageDTO>, GenericOptions, GlobalSettings, String) -> Result<ChatRequestExecutor<'c>, String>,
         C: Config,
     {
+        attachments::check_limits(&messages, &options.provider)?;
         match model {
             Some(model_str) => {
                 let reply = executor(client, messages, options, global_settings, model_str.to_string())?
@@ -133,6 +144,7 @@ impl LLMClient {
         F: FnOnce(&'c Client<C>, Vec<MessageDTO>, GenericOptions, GlobalSettings, String) -> Result<ChatRequestExecutor<'c>, String>,
         C: Config,
     {
+        attachments::check_limits(&messages, &options.provider)?;
         match model {
             Some(model_str) => {
                 let stream = executor(client, messages, options, global_settings, model_str.to_string())?
@@ -212,40 +224,129 @@ impl LLMClient {
         }
     }
 
-    pub async fn models(&self) -> Result<Vec<RemoteModel>, String> {
+    /// Model configured for the client
+    pub fn model(&self) -> Option<&String> {
         match self {
-            LLMClient::OpenAIClient(client, _) => {
-                let result = ListModelsRequestExecutor::openai(client).execute().await?;
-                Ok(result)
-            }
+            LLMClient::OpenAIClient(_, model)
+            | LLMClient::AzureClient(_, model)
+            | LLMClient::ClaudeClient(_, model)
+            | LLMClient::OllamaClient(_, model)
+            | LLMClient::OpenrouterClient(_, model)
+            | LLMClient::DeepseekClient(_, model)
+            | LLMClient::XaiClient(_, model)
+            | LLMClient::GoogleClient(_, model) => model.as_ref(),
+        }
+    }
+
+    /// List models without mapping the error, returning None if the provider doesn't support listing
+    pub(crate) async fn list_models(&self) -> Option<Result<Vec<RemoteModel>, OpenAIError>> {
+        let executor = match self {
+            LLMClient::OpenAIClient(client, _) => ListModelsRequestExecutor::openai(client),
             LLMClient::AzureClient(_, _) => {
                 // Azure doesn't support model list
-                Err("List models API is not supported by Azure".to_string())
-            }
-            LLMClient::ClaudeClient(client, _) => {
-                let result = ListModelsRequestExecutor::claude(client).execute().await?;
-                Ok(result)
-            }
-            LLMClient::OllamaClient(client, _) => {
-                let result = ListModelsRequestExecutor::ollama(client).execute().await?;
-                Ok(result)
-            }
-            LLMClient::OpenrouterClient(client, _) => {
-                let result = ListModelsRequestExecutor::openrouter(client).execute().await?;
-                Ok(result)
-            }
-            LLMClient::DeepseekClient(client, _) => {
-                let result = ListModelsRequestExecutor::deepseek(client).execute().await?;
-                Ok(result)
-            }
-            LLMClient::XaiClient(client, _) => {
-                let result = ListModelsRequestExecutor::xai(client).execute().await?;
-                Ok(result)
-            }
-            LLMClient::GoogleClient(client, _) => {
-                let result = ListModelsRequestExecutor::google(client).execute().await?;
-                Ok(result)
+                return None;
             }
+            LLMClient::ClaudeClient(client, _) => ListModelsRequestExecutor::claude(client),
+            LLMClient::OllamaClient(client, _) => ListModelsRequestExecutor::ollama(client),
+            LLMClient::OpenrouterClient(client, _) => ListModelsRequestExecutor::openrouter(client),
+            LLMClient::DeepseekClient(client, _) => ListModelsRequestExecutor::deepseek(client),
+            LLMClient::XaiClient(client, _) => ListModelsRequestExecutor::xai(client),
+            LLMClient::GoogleClient(client, _) => ListModelsRequestExecutor::google(client),
+        };
+        Some(executor.execute().await)
+    }
+
+    pub async fn models(&self) -> Result<Vec<RemoteModel>, String> {
+        match self.list_models().await {
+            Some(result) => result.map_err(|err| {
+                log::error!("LLMClient::models: {}", err);
+                String::from("Failed to list models")
+            }),
+            None => Err("List models API is not supported by Azure".to_string()),
         }
     }
+
+    /// Embed texts with the configured model, e.g. an embedding model such as `text-embedding-3-small`
+    pub async fn embed(&self, texts: Vec<String>) -> Result<EmbeddingResult, String> {
+        let model = self
+            .model()
+            .cloned()
+            .ok_or(String::from("Model not set for embeddings"))?;
+        let executor = match self {
+            LLMClient::OpenAIClient(client, _) => EmbeddingRequestExecutor::openai(client),
+            LLMClient::AzureClient(client, _) => EmbeddingRequestExecutor::azure(client),
+            LLMClient::OllamaClient(client, _) => EmbeddingRequestExecutor::ollama(client),
+            LLMClient::GoogleClient(client, _) => EmbeddingRequestExecutor::google(client),
+            _ => return Err("Embeddings API is not supported by this provider".to_string()),
+        };
+        executor.execute(model, texts).await.map_err(|err| {
+            log::error!("LLMClient::embed: {}", err);
+            format!("Failed to create embeddings: {}", err)
+        })
+    }
+
+    /// Transcribe audio with the configured model, e.g. a speech-to-text model such as `whisper-1`
+    pub async fn transcribe(&self, audio: AudioFile) -> Result<String, String> {
+        let model = self
+            .model()
+            .cloned()
+            .ok_or(String::from("Model not set for transcription"))?;
+        let executor = match self {
+            LLMClient::OpenAIClient(client, _) => TranscriptionRequestExecutor::openai(client),
+            LLMClient::AzureClient(client, _) => TranscriptionRequestExecutor::azure(client),
+            LLMClient::GoogleClient(client, _) => TranscriptionRequestExecutor::google(client),
+            _ => return Err("Transcription API is not supported by this provider".to_string()),
+        };
+        executor.execute(model, audio).await.map_err(|err| {
+            log::error!("LLMClient::transcribe: {}", err);
+            format!("Failed to transcribe audio: {}", err)
+        })
+    }
+
+    /// Synthesize speech with the configured model, e.g. a text-to-speech model such as `tts-1`.
+    /// The model of the request is overridden by the configured one.
+    pub async fn speak(&self, request: SpeechRequest) -> Result<Vec<u8>, String> {
+        let model = self
+            .model()
+            .cloned()
+            .ok_or(String::from("Model not set for speech"))?;
+        let executor = match self {
+            LLMClient::OpenAIClient(client, _) => SpeechRequestExecutor::openai(client),
+            LLMClient::AzureClient(client, _) => SpeechRequestExecutor::azure(client),
+            _ => return Err("Speech API is not supported by this provider".to_string()),
+        };
+        executor
+            .execute(SpeechRequest { model, ..request })
+            .await
+            .map_err(|err| {
+                log::error!("LLMClient::speak: {}", err);
+                format!("Failed to synthesize speech: {}", err)
+            })
+    }
+
+    /// Generate images with the configured model, e.g. `gpt-image-1` or a Gemini model that outputs images
+    pub async fn generate_images(
+        &self,
+        provider: &str,
+        messages: Vec<MessageDTO>,
+        options: ImageGenerationOptions,
+    ) -> Result<ImageGenerationResult, String> {
+        let model = self
+            .model()
+            .cloned()
+            .ok_or(String::from("Model not set for image generation"))?;
+        let executor = match self {
+            LLMClient::OpenAIClient(client, _) => ImageGenerationRequestExecutor::openai(client),
+            LLMClient::AzureClient(client, _) => ImageGenerationRequestExecutor::azure(client),
+            LLMClient::GoogleClient(client, _) => ImageGenerationRequestExecutor::google(client),
+            _ => return Err("Image generation is not supported by this provider".to_string()),
+        };
+        executor
+            .execute(provider, model, messages, options)
+            .await
+            .map_err(|err| {
+                log::error!("LLMClient::generate_images: {}", err);
+                format!("Failed to generate images: {}", err)
+            })
+    }
 }
@@ -0,0 +1,283 @@
+use std::{error::Error, time::Instant};
+
+use async_openai::{
+    error::OpenAIError,
+    types::{ChatCompletionRequestMessage, ChatCompletionRequestUserMessage, ChatCompletionRequestUserMessageContent},
+};
+use serde::Serialize;
+
+use super::{
+    client::LLMClient,
+    models::{normalize_model_id, RemoteModel},
+    providers::{
+        openai::chat::{OpenAIChat, OpenAIChatCompletionRequest},
+        types::ChatCompletionRequestCommon,
+    },
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiagnosticKind {
+    Ok,
+    Dns,
+    Tls,
+    Proxy,
+    Connection,
+    Timeout,
+    Auth,
+    UnknownModel,
+    Region,
+    RateLimit,
+    Server,
+    Unknown,
+}
+
+/// Result of testing a model config against its server
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionDiagnostic {
+    pub ok: bool,
+    pub kind: DiagnosticKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub latency_ms: u64,
+    /// Models reported by the server, if the provider supports listing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub models: Option<Vec<RemoteModel>>,
+    /// Whether the configured model is in the server's model list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_found: Option<bool>,
+}
+
+/// Perform a cheap authenticated call with the client and diagnose the outcome.
+/// Models are listed when the provider supports it, otherwise a one-token completion is requested.
+pub async fn test_client(client: &LLMClient) -> ConnectionDiagnostic {
+    let now = Instant::now();
+    let result = match client.list_models().await {
+        Some(result) => result.map(Some),
+        None => ping(client).await.map(|_| None),
+    };
+    let latency_ms = now.elapsed().as_millis() as u64;
+    match result {
+        Ok(models) => {
+            let model_found = match (&models, client.model()) {
+                (Some(models), Some(model)) if !model.is_empty() => {
+                    Some(models.iter().any(|m| same_model(m.id(), model)))
+                }
+                _ => None,
+            };
+            if model_found == Some(false) {
+                ConnectionDiagnostic {
+                    ok: false,
+                    kind: DiagnosticKind::UnknownModel,
+                    message: client
+                        .model()
+                        .map(|model| format!("Model '{}' is not in the server's model list", model)),
+                    latency_ms,
+                    models,
+                    model_found,
+                }
+            } else {
+                ConnectionDiagnostic {
+                    ok: true,
+                    kind: DiagnosticKind::Ok,
+                    message: None,
+                    latency_ms,
+                    models,
+                    model_found,
+                }
+            }
+        }
+        Err(err) => {
+            log::error!("diagnostics::test_client: {:?}", err);
+            ConnectionDiagnostic {
+                ok: false,
+                kind: classify_error(&err),
+                message: Some(error_chain(&err)),
+                latency_ms,
+                models: None,
+                model_found: None,
+            }
+        }
+    }
+}
+
+/// Request a one-token completion, for providers that can't list models
+async fn ping(client: &LLMClient) -> Result<(), OpenAIError> {
+    match client {
+        LLMClient::AzureClient(client, _) => {
+            let request = OpenAIChatCompletionRequest {
+                common: ChatCompletionRequestCommon {
+                    max_tokens: Some(1),
+                    ..Default::default()
+                },
+                messages: vec![ChatCompletionRequestMessage::User(
+                    ChatCompletionRequestUserMessage {
+                        content: ChatCompletionRequestUserMessageContent::Text("ping".to_string()),
+                        name: None,
+                    },
+                )],
+                ..Default::default()
+            };
+            OpenAIChat::new(client).create(request).await?;
+            Ok(())
+        }
+        _ => Err(OpenAIError::InvalidArgument(
+            "Provider supports listing models".to_string(),
+        )),
+    }
+}
+
+/// Whether two model ids name the same model, Ollama implies the `latest` tag when none is given
+fn same_model(a: &str, b: &str) -> bool {
+    normalize_model_id(a) == normalize_model_id(b)
+}
+
+/// Message of the error followed by the messages of its sources
+fn error_chain(err: &OpenAIError) -> String {
+    let mut messages = vec![err.to_string()];
+    let mut source = err.source();
+    while let Some(err) = source {
+        messages.push(err.to_string());
+        source = err.source();
+    }
+    messages.join(": ")
+}
+
+fn classify_error(err: &OpenAIError) -> DiagnosticKind {
+    match err {
+        OpenAIError::Reqwest(reqwest_err) => {
+            let chain = error_chain(err).to_lowercase();
+            if reqwest_err.is_timeout() {
+                DiagnosticKind::Timeout
+            } else if chain.contains("proxy") {
+                DiagnosticKind::Proxy
+            } else if chain.contains("dns error") || chain.contains("failed to lookup address") {
+                DiagnosticKind::Dns
+            } else if chain.contains("certificate") || chain.contains("tls") || chain.contains("ssl") {
+                DiagnosticKind::Tls
+            } else if reqwest_err.is_connect() {
+                DiagnosticKind::Connection
+            } else {
+                DiagnosticKind::Unknown
+            }
+        }
+        OpenAIError::ApiError(api_err) => {
+            let text = format!("{:?}", api_err).to_lowercase();
+            if text.contains("api key")
+                || text.contains("api_key")
+                || text.contains("unauthorized")
+                || text.contains("authentication")
+                || text.contains("permission")
+            {
+                DiagnosticKind::Auth
+            } else if text.contains("region")
+                || text.contains("country")
+                || text.contains("location is not supported")
+            {
+                DiagnosticKind::Region
+            } else if (text.contains("model") || text.contains("deployment"))
+                && (text.contains("not found") || text.contains("does not exist") || text.contains("notfound"))
+            {
+                DiagnosticKind::UnknownModel
+            } else if text.contains("rate limit") || text.contains("quota") {
+                DiagnosticKind::RateLimit
+            } else {
+                DiagnosticKind::Server
+            }
+        }
+        // The server returned something that isn't an OpenAI-style response
+        OpenAIError::JSONDeserialize(_) => DiagnosticKind::Server,
+        _ => DiagnosticKind::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, time::Duration};
+
+    use async_openai::error::ApiError;
+    use serde_json::json;
+
+    use super::*;
+
+    fn api_error(message: &str, r#type: &str, code: &str) -> OpenAIError {
+        OpenAIError::ApiError(
+            serde_json::from_value::<ApiError>(json!({
+                "message": message,
+                "type": r#type,
+                "param": null,
+                "code": code,
+            }))
+            .unwrap(),
+        )
+    }
+
+    fn request_error(url: String, timeout: Duration) -> OpenAIError {
+        tauri::async_runtime::block_on(async move {
+            let client = reqwest::Client::builder()
+                .no_proxy()
+                .timeout(timeout)
+                .build()
+                .unwrap();
+            OpenAIError::Reqwest(client.get(url).send().await.unwrap_err())
+        })
+    }
+
+    #[test]
+    fn test_classify_api_errors() {
+        assert_eq!(
+            DiagnosticKind::Auth,
+            classify_error(&api_error(
+                "Incorrect API key provided: sk-1234. You can find your API key at https://platform.openai.com/account/api-keys.",
+                "invalid_request_error",
+                "invalid_api_key"
+            ))
+        );
+        assert_eq!(
+            DiagnosticKind::RateLimit,
+            classify_error(&api_error(
+                "You exceeded your current quota, please check your plan and billing details.",
+                "insufficient_quota",
+                "insufficient_quota"
+            ))
+        );
+        assert_eq!(
+            DiagnosticKind::UnknownModel,
+            classify_error(&api_error(
+                "The model `gpt-0` does not exist",
+                "invalid_request_error",
+                "model_not_found"
+            ))
+        );
+        assert_eq!(
+            DiagnosticKind::UnknownModel,
+            classify_error(&api_error(
+                "model \"llama3\" not found, try pulling it first",
+                "api_error",
+                "not_found"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_classify_network_errors() {
+        // nothing listens on the port once the listener is dropped
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let err = request_error(format!("http://127.0.0.1:{}", port), Duration::from_secs(5));
+        assert_eq!(DiagnosticKind::Connection, classify_error(&err));
+
+        // the connection is accepted by the OS but never answered
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let err = request_error(url, Duration::from_millis(200));
+        assert_eq!(DiagnosticKind::Timeout, classify_error(&err));
+    }
+
+    #[test]
+    fn test_same_model() {
+        assert!(same_model("llama3:latest", "llama3"));
+        assert!(same_model("llama3", "llama3:latest"));
+        assert!(!same_model("llama3:8b", "llama3"));
+    }
+}
@@ -0,0 +1,182 @@
+use async_openai::{
+    config::{AzureConfig, Config},
+    error::OpenAIError,
+    types::CreateEmbeddingRequestArgs,
+    Client,
+};
+use serde::Serialize;
+
+use super::{
+    providers::{
+        google::{config::GoogleConfig, embeddings::GoogleEmbeddings},
+        ollama::{
+            config::OllamaConfig,
+            embeddings::{OllamaEmbedRequest, OllamaEmbeddings},
+        },
+        openai::config::OpenAICompatibleConfig,
+    },
+    utils::sum_option,
+};
+
+/// Max number of texts sent in one embeddings request
+const EMBEDDING_BATCH_SIZE: usize = 64;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddingResult {
+    pub model: String,
+    pub dimensions: usize,
+    /// One vector per input text, in the same order
+    pub vectors: Vec<Vec<f32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens: Option<u32>,
+}
+
+pub enum EmbeddingRequestExecutor<'c> {
+    OpenAIEmbeddingRequestExecutor(&'c Client<OpenAICompatibleConfig>),
+    AzureEmbeddingRequestExecutor(&'c Client<AzureConfig>),
+    OllamaEmbeddingRequestExecutor(&'c Client<OllamaConfig>),
+    GoogleEmbeddingRequestExecutor(&'c Client<GoogleConfig>),
+}
+
+impl<'c> EmbeddingRequestExecutor<'c> {
+    pub fn openai(client: &'c Client<OpenAICompatibleConfig>) -> Self {
+        return EmbeddingRequestExecutor::OpenAIEmbeddingRequestExecutor(client);
+    }
+
+    pub fn azure(client: &'c Client<AzureConfig>) -> Self {
+        return EmbeddingRequestExecutor::AzureEmbeddingRequestExecutor(client);
+    }
+
+    pub fn ollama(client: &'c Client<OllamaConfig>) -> Self {
+        return EmbeddingRequestExecutor::OllamaEmbeddingRequestExecutor(client);
+    }
+
+    pub fn google(client: &'c Client<GoogleConfig>) -> Self {
+        return EmbeddingRequestExecutor::GoogleEmbeddingRequestExecutor(client);
+    }
+
+    /// Embed the texts, splitting them into batches of [EMBEDDING_BATCH_SIZE]
+    pub async fn execute(&self, model: String, texts: Vec<String>) -> Result<EmbeddingResult, OpenAIError> {
+        let mut result = EmbeddingResult {
+            model: model.clone(),
+            ..Default::default()
+        };
+        for batch in texts.chunks(EMBEDDING_BATCH_SIZE) {
+            let (vectors, prompt_tokens) = match self {
+                EmbeddingRequestExecutor::OpenAIEmbeddingRequestExecutor(client) => {
+                    Self::execute_openai_compatible_batch(client, &model, batch).await?
+                }
+                EmbeddingRequestExecutor::AzureEmbeddingRequestExecutor(client) => {
+                    Self::execute_openai_compatible_batch(client, &model, batch).await?
+                }
+                EmbeddingRequestExecutor::OllamaEmbeddingRequestExecutor(client) => {
+                    let response = OllamaEmbeddings::new(client)
+                        .create(OllamaEmbedRequest {
+                            model: model.clone(),
+                            input: batch.to_vec(),
+                        })
+                        .await?;
+                    (response.embeddings, response.prompt_eval_count)
+                }
+                EmbeddingRequestExecutor::GoogleEmbeddingRequestExecutor(client) => {
+                    let response = GoogleEmbeddings::new(client).create(batch.to_vec()).await?;
+                    let vectors = response
+                        .embeddings
+                        .into_iter()
+                        .map(|embedding| embedding.values)
+                        .collect();
+                    (vectors, None)
+                }
+            };
+            if vectors.len() != batch.len() {
+                return Err(OpenAIError::InvalidArgument(format!(
+                    "Expected {} embeddings but received {}",
+                    batch.len(),
+                    vectors.len()
+                )));
+            }
+            result.vectors.extend(vectors);
+            result.prompt_tokens = sum_option(result.prompt_tokens, prompt_tokens);
+        }
+        result.dimensions = result.vectors.first().map(|v| v.len()).unwrap_or(0);
+        if result.vectors.iter().any(|v| v.len() != result.dimensions) {
+            return Err(OpenAIError::InvalidArgument(
+                "Embeddings have inconsistent dimensions".to_string(),
+            ));
+        }
+        Ok(result)
+    }
+
+    async fn execute_openai_compatible_batch<C: Config>(
+        client: &Client<C>,
+        model: &str,
+        batch: &[String],
+    ) -> Result<(Vec<Vec<f32>>, Option<u32>), OpenAIError> {
+        let request = CreateEmbeddingRequestArgs::default()
+            .model(model)
+            .input(batch.to_vec())
+            .build()?;
+        let mut response = client.embeddings().create(request).await?;
+        response.data.sort_by_key(|embedding| embedding.index);
+        let vectors = response
+            .data
+            .into_iter()
+            .map(|embedding| embedding.embedding)
+            .collect();
+        Ok((vectors, Some(response.usage.prompt_tokens)))
+    }
+}
+
+/// Serialize a vector as little-endian f32 bytes, for storing in a BLOB column
+pub fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+/// Deserialize a vector stored by [encode_vector]
+pub fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+/// Cosine similarity of two vectors, 0 if they have different dimensions or either is a zero vector
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let mut dot = 0.0;
+    let mut norm_a = 0.0;
+    let mut norm_b = 0.0;
+    for (x, y) in a.iter().zip(b.iter()) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode_vector() {
+        let vector = vec![0.5, -1.25, 3.0];
+        let bytes = encode_vector(&vector);
+        assert_eq!(bytes.len(), 12);
+        assert_eq!(decode_vector(&bytes), vector);
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[1.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[1.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+}
@@ -0,0 +1,304 @@
+use async_openai::{
+    config::{AzureConfig, Config},
+    error::OpenAIError,
+    Client,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use entity::entities::{
+    contents::{ContentDTO, ContentType},
+    conversations::ImageGenerationOptions,
+    messages::{MessageDTO, Roles},
+};
+
+use crate::services::cache;
+
+use super::{
+    capabilities::CapabilityRegistry,
+    providers::{
+        google::{
+            chat::{
+                GoogleChat, GoogleChatCompletionContentPart, GoogleChatCompletionRequest,
+                GoogleChatCompletionRequestGenerationConfig,
+            },
+            config::GoogleConfig,
+        },
+        openai::{
+            config::OpenAICompatibleConfig,
+            images::{OpenAIImageEditRequest, OpenAIImageGenerationRequest, OpenAIImages},
+        },
+    },
+    utils::message_to_google_request_message,
+};
+
+/// An image generated by a model
+#[derive(Debug)]
+pub struct GeneratedImage {
+    pub mimetype: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Images generated for a prompt, with the text the model replied along with them
+#[derive(Debug, Default)]
+pub struct ImageGenerationResult {
+    pub images: Vec<GeneratedImage>,
+    pub text: Option<String>,
+}
+
+pub enum ImageGenerationRequestExecutor<'c> {
+    OpenAIImageGenerationRequestExecutor(&'c Client<OpenAICompatibleConfig>),
+    AzureImageGenerationRequestExecutor(&'c Client<AzureConfig>),
+    GoogleImageGenerationRequestExecutor(&'c Client<GoogleConfig>),
+}
+
+impl<'c> ImageGenerationRequestExecutor<'c> {
+    pub fn openai(client: &'c Client<OpenAICompatibleConfig>) -> Self {
+        return ImageGenerationRequestExecutor::OpenAIImageGenerationRequestExecutor(client);
+    }
+
+    pub fn azure(client: &'c Client<AzureConfig>) -> Self {
+        return ImageGenerationRequestExecutor::AzureImageGenerationRequestExecutor(client);
+    }
+
+    pub fn google(client: &'c Client<GoogleConfig>) -> Self {
+        return ImageGenerationRequestExecutor::GoogleImageGenerationRequestExecutor(client);
+    }
+
+    pub async fn execute(
+        &self,
+        provider: &str,
+        model: String,
+        messages: Vec<MessageDTO>,
+        options: ImageGenerationOptions,
+    ) -> Result<ImageGenerationResult, OpenAIError> {
+        match self {
+            ImageGenerationRequestExecutor::OpenAIImageGenerationRequestExecutor(client) => {
+                Self::execute_openai_compatible(client, provider, model, messages, options).await
+            }
+            ImageGenerationRequestExecutor::AzureImageGenerationRequestExecutor(client) => {
+                Self::execute_openai_compatible(client, provider, model, messages, options).await
+            }
+            ImageGenerationRequestExecutor::GoogleImageGenerationRequestExecutor(client) => {
+                Self::execute_google(client, messages, options).await
+            }
+        }
+    }
+
+    /// Generate images with `/images/generations`, or edit an image with `/images/edits`
+    /// so that generated and attached images can be refined in subsequent turns
+    async fn execute_openai_compatible<C: Config>(
+        client: &Client<C>,
+        provider: &str,
+        model: String,
+        messages: Vec<MessageDTO>,
+        options: ImageGenerationOptions,
+    ) -> Result<ImageGenerationResult, OpenAIError> {
+        let prompt = get_prompt(&messages)?;
+        let image_edits = CapabilityRegistry::global().get(provider, &model).image_edits;
+        // gpt-image models reject response_format
+        let response_format = if model.starts_with("dall-e") {
+            Some("b64_json".to_string())
+        } else {
+            None
+        };
+        let image = get_image_to_edit(&messages, image_edits).and_then(|item| {
+            cache::read_bytes(&item.data)
+                .ok()
+                .map(|bytes| (item.data.clone(), item.mimetype.clone(), bytes))
+        });
+        let data = match image {
+            Some((image_name, image_mimetype, image)) => {
+                let request = OpenAIImageEditRequest {
+                    image_mimetype: image_mimetype.unwrap_or(infer_image_mimetype(&image)),
+                    image_name,
+                    image,
+                    prompt,
+                    model,
+                    n: options.n,
+                    size: options.size,
+                    quality: options.quality,
+                    response_format,
+                };
+                OpenAIImages::new(client).create_edit(request).await?.data
+            }
+            None => {
+                let request = OpenAIImageGenerationRequest {
+                    prompt,
+                    model,
+                    n: options.n,
+                    size: options.size,
+                    quality: options.quality,
+                    style: options.style,
+                    response_format,
+                };
+                OpenAIImages::new(client).create(request).await?.data
+            }
+        };
+        let mut result = ImageGenerationResult::default();
+        let mut revised_prompts = vec![];
+        for item in data {
+            let bytes = match (item.b64_json, item.url) {
+                (Some(b64_json), _) => decode_base64(&b64_json)?,
+                (None, Some(url)) => client
+                    .http_client()
+                    .get(url)
+                    .send()
+                    .await
+                    .map_err(OpenAIError::Reqwest)?
+                    .bytes()
+                    .await
+                    .map_err(OpenAIError::Reqwest)?
+                    .to_vec(),
+                (None, None) => continue,
+            };
+            result.images.push(GeneratedImage {
+                mimetype: infer_image_mimetype(&bytes),
+                bytes,
+            });
+            if let Some(revised_prompt) = item.revised_prompt {
+                revised_prompts.push(revised_prompt);
+            }
+        }
+        if !revised_prompts.is_empty() {
+            result.text = Some(revised_prompts.join("\n\n"));
+        }
+        Ok(result)
+    }
+
+    /// Generate images with Gemini models that output images, sending the conversation so that
+    /// images of previous turns are used as inputs
+    async fn execute_google(
+        client: &Client<GoogleConfig>,
+        messages: Vec<MessageDTO>,
+        options: ImageGenerationOptions,
+    ) -> Result<ImageGenerationResult, OpenAIError> {
+        let request = GoogleChatCompletionRequest {
+            contents: messages
+                .into_iter()
+                .map(message_to_google_request_message)
+                .collect(),
+            system_instruction: None,
+            generation_config: Some(GoogleChatCompletionRequestGenerationConfig {
+                response_modalities: Some(vec!["TEXT".to_string(), "IMAGE".to_string()]),
+                ..Default::default()
+            }),
+        };
+        let mut result = ImageGenerationResult::default();
+        let mut texts = vec![];
+        // Gemini generates one candidate with images, so send a request per image
+        for _ in 0..options.n.unwrap_or(1).max(1) {
+            let response = GoogleChat::new(client).create(request.clone()).await?;
+            let parts = response
+                .candidates
+                .into_iter()
+                .next()
+                .and_then(|candidate| candidate.content.parts)
+                .unwrap_or_default();
+            for part in parts {
+                match part {
+                    GoogleChatCompletionContentPart::InlineData(data) => {
+                        result.images.push(GeneratedImage {
+                            mimetype: data.mime_type,
+                            bytes: decode_base64(&data.data)?,
+                        });
+                    }
+                    GoogleChatCompletionContentPart::Text(text) => texts.push(text),
+                    _ => {}
+                }
+            }
+        }
+        let text = texts.join("").trim().to_string();
+        if !text.is_empty() {
+            result.text = Some(text);
+        }
+        Ok(result)
+    }
+}
+
+/// Text of the last user message, used as the prompt of image APIs
+fn get_prompt(messages: &[MessageDTO]) -> Result<String, OpenAIError> {
+    messages
+        .iter()
+        .rev()
+        .find(|message| Roles::from(message.role) == Roles::User)
+        .and_then(|message| message.get_text())
+        .ok_or(OpenAIError::InvalidArgument(
+            "Prompt is required to generate images".into(),
+        ))
+}
+
+/// Most recent image of a message
+fn get_last_image(message: &MessageDTO) -> Option<&ContentDTO> {
+    message
+        .content
+        .iter()
+        .rev()
+        .find(|item| item.r#type == ContentType::Image)
+}
+
+/// Image to edit: the image attached to the last user message, otherwise the most recent image
+/// of the conversation if the model can edit images. None if a new image is generated.
+fn get_image_to_edit(messages: &[MessageDTO], image_edits: bool) -> Option<&ContentDTO> {
+    let attached = messages
+        .iter()
+        .rev()
+        .find(|message| Roles::from(message.role) == Roles::User)
+        .and_then(get_last_image);
+    if attached.is_some() || !image_edits {
+        return attached;
+    }
+    messages.iter().rev().find_map(get_last_image)
+}
+
+fn decode_base64(data: &str) -> Result<Vec<u8>, OpenAIError> {
+    STANDARD
+        .decode(data)
+        .map_err(|err| OpenAIError::InvalidArgument(format!("Invalid image data: {}", err)))
+}
+
+fn infer_image_mimetype(bytes: &[u8]) -> String {
+    infer::get(bytes)
+        .map(|kind| kind.mime_type())
+        .unwrap_or("image/png")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: Roles, images: &[&str]) -> MessageDTO {
+        MessageDTO {
+            role: role.into(),
+            content: images
+                .iter()
+                .map(|image| ContentDTO {
+                    r#type: ContentType::Image,
+                    mimetype: Some("image/png".to_string()),
+                    data: image.to_string(),
+                })
+                .chain(std::iter::once(ContentDTO {
+                    r#type: ContentType::Text,
+                    mimetype: None,
+                    data: "a cat".to_string(),
+                }))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_get_image_to_edit() {
+        let image_name = |messages: &[MessageDTO], image_edits: bool| {
+            get_image_to_edit(messages, image_edits).map(|item| item.data.clone())
+        };
+        let generated = [message(Roles::User, &[]), message(Roles::Bot, &["generated.png"])];
+        let follow_up = [generated.to_vec(), vec![message(Roles::User, &[])]].concat();
+        assert_eq!(Some("generated.png".to_string()), image_name(&follow_up, true));
+        assert_eq!(None, image_name(&follow_up, false));
+
+        let attached =
+            [generated.to_vec(), vec![message(Roles::User, &["cat.png", "hat.png"])]].concat();
+        assert_eq!(Some("hat.png".to_string()), image_name(&attached, true));
+        assert_eq!(Some("hat.png".to_string()), image_name(&attached, false));
+    }
+}
@@ -1,4 +1,9 @@
+pub mod audio;
+pub mod capabilities;
 pub mod chat;
+pub mod diagnostics;
+pub mod embeddings;
+pub mod images;
 pub mod models;
 mod providers;
 mod utils;
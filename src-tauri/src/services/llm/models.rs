@@ -1,22 +1,114 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use super::capabilities::CapabilityRegistry;
 use super::providers::{
     claude::{config::ClaudeConfig, models::ClaudeModels}, 
     deepseek::{config::DeepseekConfig, models::DeepseekModels}, 
     ollama::{config::OllamaConfig, models::OllamaModels}, 
+    openai::config::OpenAICompatibleConfig,
     openrouter::models::OpenrouterModels, 
     xai::{config::XaiConfig, models::XaiModels},
     google::{config::GoogleConfig, models::GoogleModels},
 };
-use async_openai::{config::OpenAIConfig, Client};
+use async_openai::{config::OpenAIConfig, error::OpenAIError, Client};
+use entity::entities::models::{GenericConfig, Providers};
+use once_cell::sync::OnceCell;
 use serde::Serialize;
 
+/// Id of a model without Ollama's implicit `latest` tag, so that `llama3` and `llama3:latest` name the same model
+pub fn normalize_model_id(model: &str) -> &str {
+    model.strip_suffix(":latest").unwrap_or(model)
+}
+
+/// How long listed models are reused before the provider is asked again
+const REMOTE_MODELS_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteModelPricing {
+    /// USD per prompt token
+    pub prompt: String,
+    /// USD per completion token
+    pub completion: String,
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Serialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub struct RemoteModel {
     id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    context_length: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_output: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pricing: Option<RemoteModelPricing>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modalities: Option<Vec<String>>,
+    /// Unix timestamp in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created: Option<i64>,
+}
+
+impl RemoteModel {
+    fn new(id: String) -> Self {
+        Self {
+            id,
+            ..Default::default()
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Cache of listed models keyed by model config
+pub struct RemoteModelsCache {
+    entries: Arc<RwLock<HashMap<String, (Instant, Vec<RemoteModel>)>>>,
+}
+
+impl RemoteModelsCache {
+    pub fn global() -> &'static RemoteModelsCache {
+        static INSTANCE: OnceCell<RemoteModelsCache> = OnceCell::new();
+
+        INSTANCE.get_or_init(|| RemoteModelsCache {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    fn key(config: &GenericConfig) -> String {
+        format!("{}:{}", config.provider, config.config)
+    }
+
+    pub fn get(&self, config: &GenericConfig) -> Option<Vec<RemoteModel>> {
+        let entries = self
+            .entries
+            .read()
+            .expect("Failed to lock remote models cache");
+        entries
+            .get(&Self::key(config))
+            .filter(|(cached_at, _)| cached_at.elapsed() < REMOTE_MODELS_CACHE_TTL)
+            .map(|(_, models)| models.clone())
+    }
+
+    pub fn insert(&self, config: &GenericConfig, models: Vec<RemoteModel>) {
+        let mut entries = self
+            .entries
+            .write()
+            .expect("Failed to lock remote models cache");
+        entries.retain(|_, (cached_at, _)| cached_at.elapsed() < REMOTE_MODELS_CACHE_TTL);
+        entries.insert(Self::key(config), (Instant::now(), models));
+    }
 }
 
 pub enum ListModelsRequestExecutor<'c> {
-    OpenAIListModelsRequestExecutor(&'c Client<OpenAIConfig>),
+    OpenAIListModelsRequestExecutor(&'c Client<OpenAICompatibleConfig>),
     OllamaListModelsRequestExecutor(&'c Client<OllamaConfig>),
     OpenrouterListModelsRequestExecutor(&'c Client<OpenAIConfig>),
     DeepseekListModelsRequestExecutor(&'c Client<DeepseekConfig>),
@@ -26,7 +118,7 @@ pub enum ListModelsRequestExecutor<'c> {
 }
 
 impl<'c> ListModelsRequestExecutor<'c> {
-    pub fn openai(client: &'c Client<OpenAIConfig>) -> Self {
+    pub fn openai(client: &'c Client<OpenAICompatibleConfig>) -> Self {
         return ListModelsRequestExecutor::OpenAIListModelsRequestExecutor(client);
     }
 
@@ -54,96 +146,156 @@ impl<'c> ListModelsRequestExecutor<'c> {
         return ListModelsRequestExecutor::GoogleListModelsRequestExecutor(client);
     }
 
-    pub async fn execute(&self) -> Result<Vec<RemoteModel>, String> {
+    pub async fn execute(&self) -> Result<Vec<RemoteModel>, OpenAIError> {
         match self {
             ListModelsRequestExecutor::OpenAIListModelsRequestExecutor(client) => {
                 let result = client
                     .models()
                     .list()
-                    .await
-                    .map_err(|err| {
-                        log::error!("OpenAIListModelsRequestExecutor: {}", err);
-                        String::from("Failed to list models")
-                    })?
+                    .await?
                     .data
                     .iter()
-                    .map(|m| RemoteModel { id: m.id.clone() })
+                    .map(|m| RemoteModel {
+                        created: Some(m.created as i64),
+                        ..RemoteModel::new(m.id.clone())
+                    })
                     .collect();
                 Ok(result)
             }
             ListModelsRequestExecutor::OllamaListModelsRequestExecutor(client) => {
-                let response = OllamaModels::new(client).list().await.map_err(|err| {
-                    log::error!("OllamaListModelsRequestExecutor: {}", err);
-                    String::from("Failed to list models")
-                })?;
-                let result = response
-                    .models
-                    .iter()
-                    .map(|m| RemoteModel { id: m.name.clone() })
-                    .collect();
+                let models = OllamaModels::new(client);
+                let response = models.list().await?;
+                let provider: String = Providers::Ollama.into();
+                let mut result = vec![];
+                for m in response.models {
+                    // recent versions of Ollama report capabilities, older ones only the families,
+                    // where vision models ship with a CLIP or mllama projector
+                    let capabilities = models
+                        .show(&m.name)
+                        .await
+                        .ok()
+                        .and_then(|response| response.capabilities);
+                    let has_projector = m.details.families.as_ref().map_or(false, |families| {
+                        families.iter().any(|f| f == "clip" || f == "mllama")
+                    });
+                    let registry = CapabilityRegistry::global();
+                    match &capabilities {
+                        Some(capabilities) => {
+                            let has_vision = capabilities.iter().any(|c| c == "vision");
+                            registry.update(&provider, &m.name, |caps| caps.vision = has_vision);
+                        }
+                        None if has_projector => {
+                            registry.update(&provider, &m.name, |caps| caps.vision = true);
+                        }
+                        None => {}
+                    }
+                    let has_vision = registry.get(&provider, &m.name).vision;
+                    result.push(RemoteModel {
+                        modalities: Some(if has_vision {
+                            vec!["text".to_string(), "image".to_string()]
+                        } else {
+                            vec!["text".to_string()]
+                        }),
+                        ..RemoteModel::new(m.name)
+                    });
+                }
                 Ok(result)
             }
             ListModelsRequestExecutor::OpenrouterListModelsRequestExecutor(client) => {
-                let response = OpenrouterModels::new(client).list().await.map_err(|err| {
-                    log::error!("OpenrouterListModelsRequestExecutor: {}", err);
-                    String::from("Failed to list models")
-                })?;
+                let response = OpenrouterModels::new(client).list().await?;
+                let provider: String = Providers::Openrouter.into();
                 let result = response
                     .data
                     .iter()
-                    .map(|m| RemoteModel { id: m.id.clone() })
+                    .map(|m| {
+                        let modalities = m
+                            .architecture
+                            .as_ref()
+                            .and_then(|architecture| architecture.input_modalities.clone());
+                        let max_output = m
+                            .top_provider
+                            .as_ref()
+                            .and_then(|top_provider| top_provider.max_completion_tokens);
+                        CapabilityRegistry::global().update(&provider, &m.id, |caps| {
+                            if let Some(modalities) = &modalities {
+                                caps.vision = modalities.iter().any(|modality| modality == "image");
+                            }
+                            caps.max_context = m.context_length.or(caps.max_context);
+                            caps.max_output = max_output.or(caps.max_output);
+                        });
+                        RemoteModel {
+                            display_name: Some(m.name.clone()),
+                            context_length: m.context_length,
+                            max_output,
+                            pricing: m.pricing.as_ref().map(|pricing| RemoteModelPricing {
+                                prompt: pricing.prompt.clone(),
+                                completion: pricing.completion.clone(),
+                            }),
+                            modalities,
+                            created: m.created,
+                            ..RemoteModel::new(m.id.clone())
+                        }
+                    })
                     .collect();
                 Ok(result)
             }
             ListModelsRequestExecutor::DeepseekListModelsRequestExecutor(client) => {
-                let response = DeepseekModels::new(client).list().await.map_err(|err| {
-                    log::error!("DeepseekListModelsRequestExecutor: {}", err);
-                    String::from("Failed to list models")
-                })?;
+                let response = DeepseekModels::new(client).list().await?;
                 let result = response
                     .data
                     .iter()
-                    .map(|m| RemoteModel { id: m.id.clone() })
+                    .map(|m| RemoteModel::new(m.id.clone()))
                     .collect();
                 Ok(result)
             }
             ListModelsRequestExecutor::XaiListModelsRequestExecutor(client) => {
-                let response = XaiModels::new(client).list().await.map_err(|err| {
-                    log::error!("XaiListModelsRequestExecutor: {}", err);
-                    String::from("Failed to list models")
-                })?;
+                let response = XaiModels::new(client).list().await?;
                 let result = response
                     .data
                     .iter()
-                    .map(|m| RemoteModel { id: m.id.clone() })
+                    .map(|m| RemoteModel {
+                        created: Some(m.created as i64),
+                        ..RemoteModel::new(m.id.clone())
+                    })
                     .collect();
                 Ok(result)
             }
             ListModelsRequestExecutor::ClaudeListModelsRequestExecutor(client) => {
-                let response = ClaudeModels::new(client).list().await.map_err(|err| {
-                    log::error!("ClaudeListModelsRequestExecutor: {}", err);
-                    String::from("Failed to list models")
-                })?;
+                let response = ClaudeModels::new(client).list().await?;
                 let result = response
                     .data
                     .iter()
-                    .map(|m| RemoteModel { id: m.display_name.clone() })
+                    .map(|m| RemoteModel {
+                        display_name: Some(m.display_name.clone()),
+                        created: chrono::DateTime::parse_from_rfc3339(&m.created_at)
+                            .ok()
+                            .map(|created_at| created_at.timestamp()),
+                        ..RemoteModel::new(m.id.clone())
+                    })
                     .collect();
                 Ok(result)
             }
             ListModelsRequestExecutor::GoogleListModelsRequestExecutor(client) => {
-                let response = GoogleModels::new(client).list().await.map_err(|err| {
-                    log::error!("GoogleListModelsRequestExecutor: {}", err);
-                    String::from("Failed to list models")
-                })?;
+                let response = GoogleModels::new(client).list().await?;
+                let provider: String = Providers::Google.into();
                 let result = response
                     .models
                     .iter()
-                    .map(|m| RemoteModel { 
-                        id: if m.name.starts_with("models/") {
+                    .map(|m| {
+                        let id = if m.name.starts_with("models/") {
                             m.name[7..].to_string()
                         } else {
                             m.name.clone()
+                        };
+                        CapabilityRegistry::global().update(&provider, &id, |caps| {
+                            caps.max_context = m.input_token_limit.or(caps.max_context);
+                            caps.max_output = m.output_token_limit.or(caps.max_output);
+                        });
+                        RemoteModel {
+                            display_name: m.display_name.clone(),
+                            context_length: m.input_token_limit,
+                            max_output: m.output_token_limit,
+                            ..RemoteModel::new(id)
                         }
                     })
                     .collect();
@@ -4,13 +4,14 @@ use async_openai::{config::Config, error::OpenAIError, Client};
 use entity::entities::{
     contents::ContentType,
     messages::{MessageDTO, Roles},
+    models::Providers,
 };
 use reqwest_eventsource::{Event, EventSource, RequestBuilderExt};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tokio_stream::{Stream, StreamExt};
 
 use crate::services::{
-    cache, 
+    attachments, cache,
     llm::providers::types::{ChatCompletionRequestCommon, ChatCompletionResponseCommon}
 };
 
@@ -35,12 +36,20 @@ pub struct ClaudeMessageContentPartImage {
     pub source: ClaudeImageSource,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ClaudeMessageContentPartDocument {
+    pub source: ClaudeImageSource,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(tag = "type")]
 #[serde(rename_all = "lowercase")]
 pub enum ClaudeMessageContentPart {
     Text(ClaudeMessageContentPartText),
     Image(ClaudeMessageContentPartImage),
+    Document(ClaudeMessageContentPartDocument),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -405,17 +414,38 @@ pub fn message_to_claude_request_message(message: MessageDTO) -> ClaudeMessage {
     let content_parts = message
         .content
         .into_iter()
-        .map(|item| {
+        .filter_map(|item| {
             let part: ClaudeMessageContentPart = match item.r#type {
                 ContentType::Image => {
+                    let (media_type, data) = attachments::read_image(
+                        item.data.as_str(),
+                        item.mimetype.as_deref(),
+                        &Providers::Claude,
+                    )
+                    .unwrap_or((
+                        item.mimetype
+                            .as_deref()
+                            .unwrap_or("image/jpeg")
+                            .to_string(),
+                        String::default(),
+                    ));
                     ClaudeMessageContentPart::Image(ClaudeMessageContentPartImage {
                         source: ClaudeImageSource {
                             r#type: "base64".to_string(),
-                            media_type: item
-                                .mimetype
-                                .as_deref()
-                                .unwrap_or("image/jpeg")
-                                .to_string(),
+                            media_type,
+                            data,
+                        },
+                    })
+                }
+                ContentType::Text => {
+                    ClaudeMessageContentPart::Text(ClaudeMessageContentPartText { text: item.data })
+                }
+                // PDFs are sent as document blocks, other files as their text
+                ContentType::Document if item.mimetype.as_deref() == Some("application/pdf") => {
+                    ClaudeMessageContentPart::Document(ClaudeMessageContentPartDocument {
+                        source: ClaudeImageSource {
+                            r#type: "base64".to_string(),
+                            media_type: "application/pdf".to_string(),
                             data: cache::read_as_base64_with_mime(
                                 item.data.as_str(),
                                 item.mimetype.as_deref(),
@@ -423,13 +453,18 @@ pub fn message_to_claude_request_message(message: MessageDTO) -> ClaudeMessage {
                             .map(|r| r.1)
                             .unwrap_or(String::default()),
                         },
+                        title: Some(attachments::display_name(&item.data).to_string()),
                     })
                 }
-                ContentType::Text => {
-                    ClaudeMessageContentPart::Text(ClaudeMessageContentPartText { text: item.data })
+                ContentType::Document | ContentType::File => {
+                    ClaudeMessageContentPart::Text(ClaudeMessageContentPartText {
+                        text: attachments::to_text_part(&item),
+                    })
                 }
+                // audio is replaced by its transcript
+                ContentType::Audio => return None,
             };
-            part
+            Some(part)
         })
         .collect::<Vec<ClaudeMessageContentPart>>();
     match message.role.into() {
@@ -23,11 +23,20 @@ pub struct GoogleChatCompletionContentPartFileData {
     pub file_uri: String,
 }
 
+/// Base64 encoded data sent or received inline
+#[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GoogleChatCompletionContentPartInlineData {
+    pub mime_type: String,
+    pub data: String,
+}
+
 #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum GoogleChatCompletionContentPart {
     Text(String),
     FileData(GoogleChatCompletionContentPartFileData),
+    InlineData(GoogleChatCompletionContentPartInlineData),
 }
 
 #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
@@ -48,6 +57,9 @@ pub struct GoogleChatCompletionRequestGenerationConfig {
     pub stop_sequences: Option<Vec<String>>,
     pub presence_penalty: Option<f32>,
     pub frequency_penalty: Option<f32>,
+    /// Modalities of the response, e.g. ["TEXT", "IMAGE"] for models generating images
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_modalities: Option<Vec<String>>,
 }
 
 #[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
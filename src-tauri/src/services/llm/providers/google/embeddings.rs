@@ -0,0 +1,63 @@
+use async_openai::{error::OpenAIError, Client};
+use serde::{Deserialize, Serialize};
+
+use super::{chat::GoogleChatCompletionContentPart, config::GoogleConfig};
+
+const GOOGLE_BATCH_EMBED_OPERATION: &str = "batchEmbedContents";
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct GoogleEmbedContent {
+    pub parts: Vec<GoogleChatCompletionContentPart>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct GoogleEmbedContentRequest {
+    /// Model name in the form of `models/{model}`
+    pub model: String,
+    pub content: GoogleEmbedContent,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct GoogleBatchEmbedContentsRequest {
+    pub requests: Vec<GoogleEmbedContentRequest>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct GoogleContentEmbedding {
+    pub values: Vec<f32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct GoogleBatchEmbedContentsResponse {
+    pub embeddings: Vec<GoogleContentEmbedding>,
+}
+
+/// Encapsulation of Gemini's embeddings API
+pub struct GoogleEmbeddings<'c> {
+    client: &'c Client<GoogleConfig>,
+}
+
+impl<'c> GoogleEmbeddings<'c> {
+    pub fn new(client: &'c Client<GoogleConfig>) -> Self {
+        Self { client }
+    }
+
+    /// Embeds a batch of texts with one request
+    pub async fn create(&self, texts: Vec<String>) -> Result<GoogleBatchEmbedContentsResponse, OpenAIError> {
+        let model = self.client.config().model.clone().ok_or(OpenAIError::InvalidArgument("Model is required".into()))?;
+
+        let request = GoogleBatchEmbedContentsRequest {
+            requests: texts
+                .into_iter()
+                .map(|text| GoogleEmbedContentRequest {
+                    model: format!("models/{}", model),
+                    content: GoogleEmbedContent {
+                        parts: vec![GoogleChatCompletionContentPart::Text(text)],
+                    },
+                })
+                .collect(),
+        };
+        let path = format!("/models/{}:{}", model, GOOGLE_BATCH_EMBED_OPERATION);
+        self.client.post(&path, request).await
+    }
+}
@@ -6,14 +6,15 @@ use super::config::GoogleConfig;
 const GOOGLE_LIST_MODELS_PATH: &str = "/models";
 
 #[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GoogleRemoteModel {
     pub name: String,
     // pub base_model_id: String,
     // pub version: String,
-    // pub display_name: String,
+    pub display_name: Option<String>,
     // pub description: String,
-    // pub input_token_limit: u32,
-    // pub output_token_limit: u32,
+    pub input_token_limit: Option<u32>,
+    pub output_token_limit: Option<u32>,
     // pub supported_generation_methods: Vec<String>,
     // pub temperature: u32,
     // pub max_temperature: u32,
@@ -5,12 +5,13 @@ use entity::entities::{
     contents::ContentType,
     conversations::OllamaOptions,
     messages::{MessageDTO, Roles},
+    models::Providers,
 };
 use serde::{Deserialize, Serialize};
 use tokio_stream::{Stream, StreamExt};
 
 use super::config::OllamaConfig;
-use crate::services::{cache, llm::providers::types::{ChatCompletionRequestCommon, ChatCompletionResponseCommon}};
+use crate::services::{attachments, llm::providers::types::{ChatCompletionRequestCommon, ChatCompletionResponseCommon}};
 
 const OLLAMA_CHAT_PATH: &str = "/api/chat";
 
@@ -177,14 +178,20 @@ pub fn message_to_ollama_request_message(message: MessageDTO) -> OllamaMessage {
                 content.images = Some(Vec::new());
             }
             content.images.as_mut().unwrap().push(
-                cache::read_as_base64_with_mime(c.data.as_str(), c.mimetype.as_deref())
+                attachments::read_image(c.data.as_str(), c.mimetype.as_deref(), &Providers::Ollama)
                     .map(|r| r.1)
                     .unwrap_or(String::default()),
             );
         }
         ContentType::Text => {
-            content.content = c.data;
+            push_text(&mut content.content, &c.data);
         }
+        // Ollama only accepts text and images, files are sent as their text
+        ContentType::Document | ContentType::File => {
+            push_text(&mut content.content, &attachments::to_text_part(&c));
+        }
+        // audio is replaced by its transcript
+        ContentType::Audio => {}
     });
     match message.role.into() {
         Roles::User => {
@@ -198,3 +205,10 @@ pub fn message_to_ollama_request_message(message: MessageDTO) -> OllamaMessage {
         }
     }
 }
+
+fn push_text(content: &mut String, text: &str) {
+    if !content.is_empty() {
+        content.push_str("\n\n");
+    }
+    content.push_str(text);
+}
@@ -0,0 +1,35 @@
+use async_openai::{error::OpenAIError, Client};
+use serde::{Deserialize, Serialize};
+
+use super::config::OllamaConfig;
+
+const OLLAMA_EMBED_PATH: &str = "/api/embed";
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct OllamaEmbedRequest {
+    pub model: String,
+    pub input: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct OllamaEmbedResponse {
+    pub model: String,
+    pub embeddings: Vec<Vec<f32>>,
+    pub prompt_eval_count: Option<u32>,
+}
+
+/// Encapsulation of Ollama's embeddings API
+pub struct OllamaEmbeddings<'c> {
+    client: &'c Client<OllamaConfig>,
+}
+
+impl<'c> OllamaEmbeddings<'c> {
+    pub fn new(client: &'c Client<OllamaConfig>) -> Self {
+        Self { client }
+    }
+
+    /// Embeds a batch of texts with one request
+    pub async fn create(&self, request: OllamaEmbedRequest) -> Result<OllamaEmbedResponse, OpenAIError> {
+        self.client.post(OLLAMA_EMBED_PATH, request).await
+    }
+}
@@ -3,6 +3,7 @@ use async_openai::{error::OpenAIError, Client};
 use serde::{Deserialize, Serialize};
 
 const OLLAMA_LIST_MODELS_PATH: &str = "/api/tags";
+const OLLAMA_SHOW_MODEL_PATH: &str = "/api/show";
 
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct OllamaRemoteModelDetails {
@@ -27,6 +28,17 @@ pub struct OllamaModelListResponse {
     pub models: Vec<OllamaRemoteModel>,
 }
 
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OllamaShowModelRequest {
+    pub model: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OllamaShowModelResponse {
+    /// e.g. completion, vision, tools; only returned by recent versions of Ollama
+    pub capabilities: Option<Vec<String>>,
+}
+
 /// Encapsulation of Ollama's models API
 pub struct OllamaModels<'c> {
     client: &'c Client<OllamaConfig>,
@@ -43,4 +55,15 @@ impl<'c> OllamaModels<'c> {
 
         Ok(response)
     }
+    /// Shows the details of a model
+    pub async fn show(&self, model: &str) -> Result<OllamaShowModelResponse, OpenAIError> {
+        self.client
+            .post(
+                OLLAMA_SHOW_MODEL_PATH,
+                OllamaShowModelRequest {
+                    model: model.to_string(),
+                },
+            )
+            .await
+    }
 }
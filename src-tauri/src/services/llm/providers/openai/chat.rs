@@ -47,6 +47,11 @@ pub struct OpenAIChatCompletionRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reasoning_effort: Option<ReasoningEffort>,
 
+    /// An upper bound for the number of tokens that can be generated for a completion, including reasoning tokens.
+    /// Replaces the deprecated `max_tokens` on servers that support it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_completion_tokens: Option<u32>,
+
     ///  This feature is in Beta.
     /// If specified, our system will make a best effort to sample deterministically, such that repeated requests
     /// with the same `seed` and parameters should return the same result.
@@ -0,0 +1,67 @@
+use async_openai::config::{Config, OpenAIConfig};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use secrecy::Secret;
+
+use super::profiles::OpenAICompatibleProfile;
+
+/// Configuration for OpenAI and OpenAI-compatible APIs.
+/// Wraps async-openai's [OpenAIConfig] and adds the profile of the target server,
+/// whose static headers and query parameters are sent with every request.
+#[derive(Clone, Debug)]
+pub struct OpenAICompatibleConfig {
+    inner: OpenAIConfig,
+    profile: OpenAICompatibleProfile,
+}
+
+impl OpenAICompatibleConfig {
+    pub fn new(inner: OpenAIConfig, profile: OpenAICompatibleProfile) -> Self {
+        Self { inner, profile }
+    }
+
+    pub fn profile(&self) -> &OpenAICompatibleProfile {
+        &self.profile
+    }
+}
+
+impl Config for OpenAICompatibleConfig {
+    fn headers(&self) -> HeaderMap {
+        let mut headers = self.inner.headers();
+        for (name, value) in &self.profile.headers {
+            match (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                (Ok(name), Ok(value)) => {
+                    headers.insert(name, value);
+                }
+                _ => {
+                    log::warn!("OpenAICompatibleConfig: skipping invalid header '{}'", name);
+                }
+            }
+        }
+        headers
+    }
+
+    fn url(&self, path: &str) -> String {
+        self.inner.url(path)
+    }
+
+    fn query(&self) -> Vec<(&str, &str)> {
+        let mut query = self.inner.query();
+        query.extend(
+            self.profile
+                .query
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str())),
+        );
+        query
+    }
+
+    fn api_base(&self) -> &str {
+        self.inner.api_base()
+    }
+
+    fn api_key(&self) -> &Secret<String> {
+        self.inner.api_key()
+    }
+}
@@ -0,0 +1,135 @@
+use async_openai::{
+    config::Config,
+    error::{ApiError, OpenAIError},
+    Client,
+};
+use reqwest::multipart::{Form, Part};
+use serde::{Deserialize, Serialize};
+
+const OPENAI_IMAGE_GENERATIONS_PATH: &str = "/images/generations";
+const OPENAI_IMAGE_EDITS_PATH: &str = "/images/edits";
+
+/// Request of `/images/generations`.
+/// Size, quality and style are plain strings because the values accepted depend on the model.
+#[derive(Clone, Serialize, Default, Debug, Deserialize, PartialEq)]
+pub struct OpenAIImageGenerationRequest {
+    pub prompt: String,
+    pub model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    /// Only accepted by DALL·E models, gpt-image models always reply with base64 data
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<String>,
+}
+
+/// Request of `/images/edits`, sent as a multipart form.
+/// Options are passed as they are, like in [OpenAIImageGenerationRequest],
+/// except that edits have no style.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct OpenAIImageEditRequest {
+    pub image_name: String,
+    pub image_mimetype: String,
+    pub image: Vec<u8>,
+    pub prompt: String,
+    pub model: String,
+    pub n: Option<u8>,
+    pub size: Option<String>,
+    pub quality: Option<String>,
+    pub response_format: Option<String>,
+}
+
+#[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+pub struct OpenAIImageData {
+    pub b64_json: Option<String>,
+    pub url: Option<String>,
+    pub revised_prompt: Option<String>,
+}
+
+#[derive(Clone, Serialize, Debug, Deserialize, PartialEq)]
+pub struct OpenAIImagesResponse {
+    pub created: u64,
+    pub data: Vec<OpenAIImageData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIImagesErrorResponse {
+    error: ApiError,
+}
+
+/// Encapsulation of OpenAI-compatible image generation API
+pub struct OpenAIImages<'c, C: Config> {
+    client: &'c Client<C>,
+}
+
+impl<'c, C: Config> OpenAIImages<'c, C> {
+    pub fn new(client: &'c Client<C>) -> Self {
+        Self { client }
+    }
+
+    /// Creates images given a prompt
+    pub async fn create(
+        &self,
+        request: OpenAIImageGenerationRequest,
+    ) -> Result<OpenAIImagesResponse, OpenAIError> {
+        self.client
+            .post(OPENAI_IMAGE_GENERATIONS_PATH, request)
+            .await
+    }
+    /// Edits an image given a prompt
+    pub async fn create_edit(
+        &self,
+        request: OpenAIImageEditRequest,
+    ) -> Result<OpenAIImagesResponse, OpenAIError> {
+        let image = Part::bytes(request.image)
+            .file_name(request.image_name)
+            .mime_str(&request.image_mimetype)
+            .map_err(OpenAIError::Reqwest)?;
+        // DALL·E 2 only edits at standard quality and rejects the parameter
+        let quality = request.quality.filter(|_| request.model != "dall-e-2");
+        let mut form = Form::new()
+            .part("image", image)
+            .text("prompt", request.prompt)
+            .text("model", request.model);
+        let fields = [
+            ("n", request.n.map(|n| n.to_string())),
+            ("size", request.size),
+            ("quality", quality),
+            ("response_format", request.response_format),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                form = form.text(name, value);
+            }
+        }
+        let config = self.client.config();
+        let response = self
+            .client
+            .http_client()
+            .post(config.url(OPENAI_IMAGE_EDITS_PATH))
+            .query(&config.query())
+            .headers(config.headers())
+            .multipart(form)
+            .send()
+            .await
+            .map_err(OpenAIError::Reqwest)?;
+        let status = response.status();
+        let bytes = response.bytes().await.map_err(OpenAIError::Reqwest)?;
+        if !status.is_success() {
+            return Err(match serde_json::from_slice::<OpenAIImagesErrorResponse>(&bytes) {
+                Ok(response) => OpenAIError::ApiError(response.error),
+                Err(_) => OpenAIError::InvalidArgument(format!(
+                    "{}: {}",
+                    status,
+                    String::from_utf8_lossy(&bytes)
+                )),
+            });
+        }
+        serde_json::from_slice(&bytes).map_err(OpenAIError::JSONDeserialize)
+    }
+}
@@ -1 +1,4 @@
-pub mod chat;
\ No newline at end of file
+pub mod chat;
+pub mod config;
+pub mod images;
+pub mod profiles;
@@ -0,0 +1,142 @@
+use serde::Deserialize;
+
+pub const PROFILE_OPENAI: &str = "openai";
+pub const PROFILE_GENERIC: &str = "generic";
+pub const PROFILE_GROQ: &str = "groq";
+pub const PROFILE_TOGETHER: &str = "together";
+pub const PROFILE_VLLM: &str = "vllm";
+pub const PROFILE_LMSTUDIO: &str = "lmstudio";
+pub const PROFILE_LLAMACPP: &str = "llamacpp";
+
+/// Capabilities and quirks of a server exposing an OpenAI-compatible chat API.
+/// Consulted when building requests so that fields a server doesn't accept are never sent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpenAICompatibleProfile {
+    pub name: String,
+    /// Whether `stream_options.include_usage` is accepted on streaming requests
+    pub supports_stream_options: bool,
+    /// Whether the token limit should be sent as `max_completion_tokens` instead of the legacy `max_tokens`
+    pub supports_max_completion_tokens: bool,
+    /// Whether `reasoning_effort` is accepted
+    pub supports_reasoning_effort: bool,
+    /// Whether user messages may contain image parts
+    pub supports_image_parts: bool,
+    /// Static headers added to every request
+    pub headers: Vec<(String, String)>,
+    /// Static query parameters added to every request
+    pub query: Vec<(String, String)>,
+}
+
+/// Per-model overrides of a profile's capabilities, as stored in the model config
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawProfileQuirks {
+    pub stream_options: Option<bool>,
+    pub max_completion_tokens: Option<bool>,
+    pub reasoning_effort: Option<bool>,
+    pub image_parts: Option<bool>,
+}
+
+impl Default for OpenAICompatibleProfile {
+    fn default() -> Self {
+        Self::generic()
+    }
+}
+
+impl OpenAICompatibleProfile {
+    fn new(
+        name: &str,
+        supports_stream_options: bool,
+        supports_max_completion_tokens: bool,
+        supports_reasoning_effort: bool,
+        supports_image_parts: bool,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            supports_stream_options,
+            supports_max_completion_tokens,
+            supports_reasoning_effort,
+            supports_image_parts,
+            headers: vec![],
+            query: vec![],
+        }
+    }
+
+    /// The official OpenAI API
+    pub fn openai() -> Self {
+        Self::new(PROFILE_OPENAI, true, true, true, true)
+    }
+
+    /// Unknown OpenAI-compatible servers, which keep the behavior of a plain OpenAI request
+    pub fn generic() -> Self {
+        Self::new(PROFILE_GENERIC, true, false, true, true)
+    }
+
+    pub fn groq() -> Self {
+        Self::new(PROFILE_GROQ, false, true, false, true)
+    }
+
+    pub fn together() -> Self {
+        Self::new(PROFILE_TOGETHER, false, false, false, true)
+    }
+
+    pub fn vllm() -> Self {
+        Self::new(PROFILE_VLLM, true, false, false, true)
+    }
+
+    pub fn lmstudio() -> Self {
+        Self::new(PROFILE_LMSTUDIO, false, false, false, true)
+    }
+
+    pub fn llamacpp() -> Self {
+        Self::new(PROFILE_LLAMACPP, false, false, false, false)
+    }
+
+    /// Look up a built-in profile by name, falling back to the generic profile
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            PROFILE_OPENAI => Self::openai(),
+            PROFILE_GROQ => Self::groq(),
+            PROFILE_TOGETHER => Self::together(),
+            PROFILE_VLLM => Self::vllm(),
+            PROFILE_LMSTUDIO => Self::lmstudio(),
+            PROFILE_LLAMACPP => Self::llamacpp(),
+            PROFILE_GENERIC => Self::generic(),
+            _ => {
+                log::warn!(
+                    "Unknown OpenAI-compatible profile '{}', using '{}'",
+                    name,
+                    PROFILE_GENERIC
+                );
+                Self::generic()
+            }
+        }
+    }
+
+    /// Apply quirks configured on the model on top of the profile
+    pub fn with_quirks(mut self, quirks: &RawProfileQuirks) -> Self {
+        if let Some(value) = quirks.stream_options {
+            self.supports_stream_options = value;
+        }
+        if let Some(value) = quirks.max_completion_tokens {
+            self.supports_max_completion_tokens = value;
+        }
+        if let Some(value) = quirks.reasoning_effort {
+            self.supports_reasoning_effort = value;
+        }
+        if let Some(value) = quirks.image_parts {
+            self.supports_image_parts = value;
+        }
+        self
+    }
+
+    pub fn with_headers<I: IntoIterator<Item = (String, String)>>(mut self, headers: I) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    pub fn with_query<I: IntoIterator<Item = (String, String)>>(mut self, query: I) -> Self {
+        self.query.extend(query);
+        self
+    }
+}
@@ -3,10 +3,35 @@ use serde::{Deserialize, Serialize};
 
 const OPENROUTER_LIST_MODELS_PATH: &str = "/models";
 
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OpenrouterModelPricing {
+    /// USD per prompt token
+    pub prompt: String,
+    /// USD per completion token
+    pub completion: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OpenrouterModelArchitecture {
+    pub input_modalities: Option<Vec<String>>,
+    pub output_modalities: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OpenrouterModelTopProvider {
+    pub context_length: Option<u32>,
+    pub max_completion_tokens: Option<u32>,
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct OpenrouterRemoteModel {
     pub id: String,
     pub name: String,
+    pub created: Option<i64>,
+    pub context_length: Option<u32>,
+    pub pricing: Option<OpenrouterModelPricing>,
+    pub architecture: Option<OpenrouterModelArchitecture>,
+    pub top_provider: Option<OpenrouterModelTopProvider>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
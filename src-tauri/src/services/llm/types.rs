@@ -1,8 +1,10 @@
+use std::collections::HashMap;
+
 use async_openai::config::{AzureConfig, OpenAIConfig};
 use serde::Deserialize;
 
 use super::providers::{
-        claude::config::ClaudeConfig, deepseek::config::DeepseekConfig, google::config::GoogleConfig, ollama::config::OllamaConfig, xai::config::XaiConfig
+        claude::config::ClaudeConfig, deepseek::config::DeepseekConfig, google::config::GoogleConfig, ollama::config::OllamaConfig, openai::{config::OpenAICompatibleConfig, profiles::{OpenAICompatibleProfile, RawProfileQuirks}}, xai::config::XaiConfig
     };
 
 #[derive(Debug, Deserialize)]
@@ -32,6 +34,32 @@ pub struct RawOpenAIConfig {
     pub model: Option<String>,
     pub endpoint: Option<String>,
     pub org_id: Option<String>,
+    /// Name of the OpenAI-compatible profile, defaults to "openai" for OpenAI models and "generic" for CUSTOM models
+    pub profile: Option<String>,
+    /// Overrides of the profile's capabilities
+    pub quirks: Option<RawProfileQuirks>,
+    /// Extra static headers sent with every request
+    pub extra_headers: Option<HashMap<String, String>>,
+    /// Extra static query parameters sent with every request
+    pub extra_query: Option<HashMap<String, String>>,
+}
+
+impl RawOpenAIConfig {
+    /// Build an OpenAI-compatible config, using `default_profile` when no profile is configured
+    pub fn into_compatible_config(mut self, default_profile: &str) -> OpenAICompatibleConfig {
+        let mut profile =
+            OpenAICompatibleProfile::from_name(self.profile.as_deref().unwrap_or(default_profile));
+        if let Some(quirks) = self.quirks.take() {
+            profile = profile.with_quirks(&quirks);
+        }
+        if let Some(headers) = self.extra_headers.take() {
+            profile = profile.with_headers(headers);
+        }
+        if let Some(query) = self.extra_query.take() {
+            profile = profile.with_query(query);
+        }
+        OpenAICompatibleConfig::new(self.into(), profile)
+    }
 }
 
 impl Into<OpenAIConfig> for RawOpenAIConfig {
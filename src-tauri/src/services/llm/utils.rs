@@ -10,14 +10,18 @@ use async_openai::{
     },
 };
 use entity::entities::{
-    contents::ContentType,
+    contents::{ContentDTO, ContentType},
     messages::{MessageDTO, Roles},
+    models::Providers,
     settings::ProxySetting,
 };
 
-use crate::{log_utils::warn, services::cache};
+use crate::{
+    log_utils::warn,
+    services::{attachments, cache},
+};
 
-use super::providers::google::chat::{GoogleChatCompletionContent, GoogleChatCompletionContentPart, GoogleChatCompletionContentPartFileData, GoogleRole};
+use super::providers::google::chat::{GoogleChatCompletionContent, GoogleChatCompletionContentPart, GoogleChatCompletionContentPartFileData, GoogleChatCompletionContentPartInlineData, GoogleRole};
 
 pub fn sum_option(a: Option<u32>, b: Option<u32>) -> Option<u32> {
     match (a, b) {
@@ -28,6 +32,77 @@ pub fn sum_option(a: Option<u32>, b: Option<u32>) -> Option<u32> {
     }
 }
 
+/// Remove contents of the given type from messages, returning the messages and the number of removed contents.
+/// Messages left without any content are kept with an empty text so that the conversation turns stay intact.
+pub fn remove_contents_of_type(
+    messages: Vec<MessageDTO>,
+    content_type: ContentType,
+) -> (Vec<MessageDTO>, usize) {
+    remove_contents(messages, |item| item.r#type == content_type)
+}
+
+/// Remove contents matching the predicate from messages, see [remove_contents_of_type]
+pub fn remove_contents<F: Fn(&ContentDTO) -> bool>(
+    messages: Vec<MessageDTO>,
+    predicate: F,
+) -> (Vec<MessageDTO>, usize) {
+    let mut removed = 0;
+    let messages = messages
+        .into_iter()
+        .map(|mut message| {
+            let before = message.content.len();
+            message.content.retain(|item| !predicate(item));
+            removed += before - message.content.len();
+            if message.content.is_empty() && before > 0 {
+                message.content.push(ContentDTO {
+                    r#type: ContentType::Text,
+                    mimetype: None,
+                    data: String::default(),
+                });
+            }
+            message
+        })
+        .collect();
+    (messages, removed)
+}
+
+/// Part of a user message for OpenAI, None for audio which is replaced by its transcript
+fn content_to_openai_part(
+    item: ContentDTO,
+) -> Result<Option<ChatCompletionRequestMessageContentPart>, OpenAIError> {
+    let part: ChatCompletionRequestMessageContentPart = match item.r#type {
+        ContentType::Image => ChatCompletionRequestMessageContentPartImageArgs::default()
+            .image_url(
+                ImageUrlArgs::default()
+                    .url(
+                        attachments::read_image(
+                            item.data.as_str(),
+                            item.mimetype.as_deref(),
+                            &Providers::OpenAI,
+                        )
+                        .map(|(mime_type, data)| format!("data:{};base64,{}", mime_type, data))
+                        .unwrap_or(String::default()),
+                    )
+                    .detail(ImageDetail::Auto)
+                    .build()?,
+            )
+            .build()?
+            .into(),
+        ContentType::Text => ChatCompletionRequestMessageContentPartTextArgs::default()
+            .text(item.data)
+            .build()?
+            .into(),
+        ContentType::Document | ContentType::File => {
+            ChatCompletionRequestMessageContentPartTextArgs::default()
+                .text(attachments::to_text_part(&item))
+                .build()?
+                .into()
+        }
+        ContentType::Audio => return Ok(None),
+    };
+    Ok(Some(part))
+}
+
 pub fn message_to_openai_request_message(message: MessageDTO) -> ChatCompletionRequestMessage {
     let log_tag = "utils::message_to_openai_request_message";
     match message.role.into() {
@@ -36,34 +111,7 @@ pub fn message_to_openai_request_message(message: MessageDTO) -> ChatCompletionR
                 .clone()
                 .content
                 .into_iter()
-                .map(|item| {
-                    let part: ChatCompletionRequestMessageContentPart = match item.r#type {
-                        ContentType::Image => {
-                            ChatCompletionRequestMessageContentPartImageArgs::default()
-                                .image_url(
-                                    ImageUrlArgs::default()
-                                        .url(
-                                            cache::read_as_data_url(
-                                                item.data.as_str(),
-                                                item.mimetype.as_deref(),
-                                            )
-                                            .unwrap_or(String::default()),
-                                        )
-                                        .detail(ImageDetail::Auto)
-                                        .build()?,
-                                )
-                                .build()?
-                                .into()
-                        }
-                        ContentType::Text => {
-                            ChatCompletionRequestMessageContentPartTextArgs::default()
-                                .text(item.data)
-                                .build()?
-                                .into()
-                        }
-                    };
-                    Ok(part)
-                })
+                .filter_map(|item| content_to_openai_part(item).transpose())
                 .collect::<Result<Vec<ChatCompletionRequestMessageContentPart>, OpenAIError>>()
                 .expect("Failed to build user message content");
             if content_parts.len() == 1 {
@@ -119,15 +167,45 @@ pub fn message_to_google_request_message(message: MessageDTO) -> GoogleChatCompl
         .map(|item| {
             let part: GoogleChatCompletionContentPart = match item.r#type {
                 ContentType::Image => {
-                    let mime_type = item.mimetype.unwrap_or(String::default());
+                    let (mime_type, data) = attachments::read_image(item.data.as_str(), item.mimetype.as_deref(), &Providers::Google)
+                        .unwrap_or((item.mimetype.unwrap_or(String::default()), String::default()));
                     GoogleChatCompletionContentPart::FileData(GoogleChatCompletionContentPartFileData {
                         mime_type: mime_type.clone(),
-                        file_uri: cache::read_as_data_url(item.data.as_str(), Some(&mime_type)).unwrap_or(String::default()),
+                        file_uri: format!("data:{};base64,{}", mime_type, data),
                     })
                 },
                 ContentType::Text => {
                     GoogleChatCompletionContentPart::Text(item.data)
                 }
+                // PDFs are sent inline, other files as their text
+                ContentType::Document if item.mimetype.as_deref() == Some("application/pdf") => {
+                    match cache::read_as_base64_with_mime(item.data.as_str(), Some("application/pdf")) {
+                        Ok((mime_type, data)) => {
+                            GoogleChatCompletionContentPart::InlineData(GoogleChatCompletionContentPartInlineData {
+                                mime_type,
+                                data,
+                            })
+                        }
+                        Err(err) => {
+                            warn(
+                                "utils::message_to_google_request_message",
+                                format!("Failed to read {}, sent as text: {}", item.data, err),
+                            );
+                            GoogleChatCompletionContentPart::Text(attachments::to_text_part(&item))
+                        }
+                    }
+                }
+                ContentType::Document | ContentType::File => {
+                    GoogleChatCompletionContentPart::Text(attachments::to_text_part(&item))
+                }
+                ContentType::Audio => {
+                    let (mime_type, data) = cache::read_as_base64_with_mime(item.data.as_str(), item.mimetype.as_deref())
+                        .unwrap_or((item.mimetype.unwrap_or(String::default()), String::default()));
+                    GoogleChatCompletionContentPart::InlineData(GoogleChatCompletionContentPartInlineData {
+                        mime_type,
+                        data,
+                    })
+                }
             };
             Ok(part)
         }).collect::<Result<Vec<GoogleChatCompletionContentPart>, OpenAIError>>()
@@ -1,3 +1,18 @@
+pub mod attachments;
 pub mod cache;
 pub mod db;
+pub mod diff;
+pub mod documents;
+pub mod gc;
+pub mod image_generation;
+pub mod knowledge;
 pub mod llm;
+pub mod presets;
+pub mod prompt_library;
+pub mod search;
+pub mod template;
+pub mod titling;
+pub mod transcription;
+pub mod trash;
+pub mod tts;
+pub mod workspaces;
@@ -0,0 +1,212 @@
+use chrono::{DateTime, Local};
+use entity::entities::presets::{CollectionIds, NewPreset, ToolNames};
+use entity::entities::prompts::PromptSort;
+use entity::import::{ImportConflict, ImportReport};
+use serde::{Deserialize, Serialize};
+
+use crate::log_utils::{info, warn};
+
+use super::db::Repository;
+use super::prompt_library::BundleFormat;
+
+/// Version of the format of preset bundles, bumped on breaking changes
+pub const PRESET_BUNDLE_VERSION: u32 = 1;
+
+/// A set of presets exported to a file.
+/// Models, prompts and collections are referred to by alias or name as ids differ between
+/// installations.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetBundle {
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported_at: Option<DateTime<Local>>,
+    pub presets: Vec<BundledPreset>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundledPreset {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Alias of the model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Alias of the prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<serde_json::Value>,
+    /// Names of the collections
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub collections: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+}
+
+pub fn parse_bundle(text: &str, format: BundleFormat) -> Result<PresetBundle, String> {
+    let bundle: PresetBundle = format
+        .deserialize(text)
+        .map_err(|err| format!("Invalid preset bundle: {}", err))?;
+    if bundle.version > PRESET_BUNDLE_VERSION {
+        return Err(format!(
+            "Preset bundle version {} is not supported, please update the app",
+            bundle.version
+        ));
+    }
+    Ok(bundle)
+}
+
+/// Write presets to a bundle file, whose format is chosen by its extension.
+/// Returns the number of exported presets.
+pub async fn export_presets(
+    repo: &Repository,
+    preset_ids: Vec<i32>,
+    path: &str,
+) -> Result<usize, String> {
+    let presets = repo.get_presets(preset_ids).await?;
+    let models = repo.list_models().await?;
+    let prompts = repo.list_prompts(None, PromptSort::Created).await?;
+    let collections = repo.list_collections().await?;
+    let bundle = PresetBundle {
+        version: PRESET_BUNDLE_VERSION,
+        exported_at: Some(Local::now()),
+        presets: presets
+            .into_iter()
+            .map(|preset| BundledPreset {
+                name: preset.name,
+                description: preset.description,
+                model: preset.model_id.and_then(|model_id| {
+                    models
+                        .iter()
+                        .find(|model| model.id == model_id)
+                        .map(|model| model.alias.clone())
+                }),
+                system_prompt: preset.system_prompt,
+                prompt: preset.prompt_id.and_then(|prompt_id| {
+                    prompts
+                        .iter()
+                        .find(|prompt| prompt.id == prompt_id)
+                        .map(|prompt| prompt.alias.clone())
+                }),
+                // options are written as a JSON object rather than a string, to be readable
+                options: preset.options.map(|options| {
+                    serde_json::from_str(&options).unwrap_or(serde_json::Value::String(options))
+                }),
+                collections: collections
+                    .iter()
+                    .filter(|collection| preset.collection_ids.0.contains(&collection.id))
+                    .map(|collection| collection.name.clone())
+                    .collect(),
+                tools: preset.tools.0,
+            })
+            .collect(),
+    };
+    let text = BundleFormat::from_path(path).serialize(&bundle)?;
+    std::fs::write(path, text).map_err(|err| format!("Failed to write {}: {}", path, err))?;
+    info(
+        "export_presets",
+        &format!("Exported {} presets to {}", bundle.presets.len(), path),
+    );
+    Ok(bundle.presets.len())
+}
+
+/// Import the presets of a bundle file.
+/// References to models, prompts and collections which don't exist here are dropped.
+pub async fn import_presets(
+    repo: &Repository,
+    path: &str,
+    on_conflict: ImportConflict,
+) -> Result<ImportReport, String> {
+    let text =
+        std::fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path, err))?;
+    let bundle = parse_bundle(&text, BundleFormat::from_path(path))?;
+    let models = repo.list_models().await?;
+    let prompts = repo.list_prompts(None, PromptSort::Created).await?;
+    let collections = repo.list_collections().await?;
+    let new_presets: Vec<NewPreset> = bundle
+        .presets
+        .into_iter()
+        .map(|preset| {
+            let model_id = preset.model.as_ref().and_then(|alias| {
+                models
+                    .iter()
+                    .find(|model| &model.alias == alias)
+                    .map(|model| model.id)
+            });
+            if preset.model.is_some() && model_id.is_none() {
+                warn(
+                    "import_presets",
+                    &format!("Model of preset {} not found", preset.name),
+                );
+            }
+            let prompt_id = preset.prompt.as_ref().and_then(|alias| {
+                prompts
+                    .iter()
+                    .find(|prompt| &prompt.alias == alias)
+                    .map(|prompt| prompt.id)
+            });
+            NewPreset {
+                name: preset.name,
+                description: preset.description,
+                model_id,
+                system_prompt: preset.system_prompt,
+                prompt_id,
+                options: preset.options.map(|options| match options {
+                    serde_json::Value::String(options) => options,
+                    options => options.to_string(),
+                }),
+                collection_ids: CollectionIds(
+                    collections
+                        .iter()
+                        .filter(|collection| preset.collections.contains(&collection.name))
+                        .map(|collection| collection.id)
+                        .collect(),
+                ),
+                tools: ToolNames(preset.tools),
+            }
+        })
+        .collect();
+    let report = repo.import_presets(new_presets, on_conflict).await?;
+    info(
+        "import_presets",
+        &format!("Imported presets from {}: {:?}", path, report),
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let bundle = PresetBundle {
+            version: PRESET_BUNDLE_VERSION,
+            exported_at: None,
+            presets: vec![BundledPreset {
+                name: "Translator".to_string(),
+                description: None,
+                model: Some("gpt-4o".to_string()),
+                system_prompt: Some("Translate to French".to_string()),
+                prompt: None,
+                options: Some(serde_json::json!({ "temperature": 0.2 })),
+                collections: vec!["Glossary".to_string()],
+                tools: vec![],
+            }],
+        };
+        for format in [BundleFormat::Json, BundleFormat::Yaml] {
+            let text = format.serialize(&bundle).unwrap();
+            assert_eq!(bundle, parse_bundle(&text, format).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_reject_newer_version() {
+        let text = "version: 2\npresets: []\n";
+        assert!(parse_bundle(text, BundleFormat::Yaml).is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use std::path::Path;
+
+use chrono::{DateTime, Local};
+use entity::entities::prompts::{NewPrompt, PromptTags};
+use entity::import::{ImportConflict, ImportReport};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::log_utils::info;
+
+use super::db::Repository;
+
+/// Version of the format of prompt bundles, bumped on breaking changes
+pub const PROMPT_BUNDLE_VERSION: u32 = 1;
+
+/// A set of prompts exported to a file
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptBundle {
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported_at: Option<DateTime<Local>>,
+    pub prompts: Vec<BundledPrompt>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundledPrompt {
+    pub alias: String,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl From<BundledPrompt> for NewPrompt {
+    fn from(value: BundledPrompt) -> Self {
+        NewPrompt {
+            alias: value.alias,
+            content: value.content,
+            category: value.category,
+            tags: PromptTags(value.tags),
+            description: value.description,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BundleFormat {
+    Json,
+    Yaml,
+}
+
+impl BundleFormat {
+    /// YAML for `.yaml` and `.yml` files, JSON otherwise
+    pub fn from_path(path: &str) -> Self {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        match extension.as_deref() {
+            Some("yaml") | Some("yml") => BundleFormat::Yaml,
+            _ => BundleFormat::Json,
+        }
+    }
+
+    pub fn serialize<T: Serialize>(&self, value: &T) -> Result<String, String> {
+        match self {
+            BundleFormat::Json => {
+                serde_json::to_string_pretty(value).map_err(|err| err.to_string())
+            }
+            BundleFormat::Yaml => serde_yaml::to_string(value).map_err(|err| err.to_string()),
+        }
+    }
+
+    pub fn deserialize<T: DeserializeOwned>(&self, text: &str) -> Result<T, String> {
+        match self {
+            BundleFormat::Json => serde_json::from_str(text).map_err(|err| err.to_string()),
+            BundleFormat::Yaml => serde_yaml::from_str(text).map_err(|err| err.to_string()),
+        }
+    }
+}
+
+pub fn serialize_bundle(bundle: &PromptBundle, format: BundleFormat) -> Result<String, String> {
+    format.serialize(bundle)
+}
+
+pub fn parse_bundle(text: &str, format: BundleFormat) -> Result<PromptBundle, String> {
+    let bundle: PromptBundle = format
+        .deserialize(text)
+        .map_err(|err| format!("Invalid prompt bundle: {}", err))?;
+    if bundle.version > PROMPT_BUNDLE_VERSION {
+        return Err(format!(
+            "Prompt bundle version {} is not supported, please update the app",
+            bundle.version
+        ));
+    }
+    Ok(bundle)
+}
+
+/// Write prompts to a bundle file, whose format is chosen by its extension.
+/// Returns the number of exported prompts.
+pub async fn export_prompts(
+    repo: &Repository,
+    prompt_ids: Vec<i32>,
+    path: &str,
+) -> Result<usize, String> {
+    let prompts = repo.get_prompts(prompt_ids).await?;
+    let bundle = PromptBundle {
+        version: PROMPT_BUNDLE_VERSION,
+        exported_at: Some(Local::now()),
+        prompts: prompts
+            .into_iter()
+            .map(|prompt| BundledPrompt {
+                alias: prompt.alias,
+                content: prompt.content,
+                category: prompt.category,
+                tags: prompt.tags.0,
+                description: prompt.description,
+            })
+            .collect(),
+    };
+    let text = serialize_bundle(&bundle, BundleFormat::from_path(path))?;
+    std::fs::write(path, text).map_err(|err| format!("Failed to write {}: {}", path, err))?;
+    info(
+        "export_prompts",
+        &format!("Exported {} prompts to {}", bundle.prompts.len(), path),
+    );
+    Ok(bundle.prompts.len())
+}
+
+/// Import the prompts of a bundle file
+pub async fn import_prompts(
+    repo: &Repository,
+    path: &str,
+    on_conflict: ImportConflict,
+) -> Result<ImportReport, String> {
+    let text =
+        std::fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path, err))?;
+    let bundle = parse_bundle(&text, BundleFormat::from_path(path))?;
+    let report = repo
+        .import_prompts(
+            bundle.prompts.into_iter().map(NewPrompt::from).collect(),
+            on_conflict,
+        )
+        .await?;
+    info(
+        "import_prompts",
+        &format!("Imported prompts from {}: {:?}", path, report),
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle() -> PromptBundle {
+        PromptBundle {
+            version: PROMPT_BUNDLE_VERSION,
+            exported_at: None,
+            prompts: vec![
+                BundledPrompt {
+                    alias: "COSTAR".to_string(),
+                    content: "# CONTEXT #\n{Context}\n".to_string(),
+                    category: Some("Writing".to_string()),
+                    tags: vec!["framework".to_string()],
+                    description: Some("Structured prompt".to_string()),
+                },
+                BundledPrompt {
+                    alias: "Plain".to_string(),
+                    content: "Hi".to_string(),
+                    category: None,
+                    tags: vec![],
+                    description: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            BundleFormat::Yaml,
+            BundleFormat::from_path("/tmp/prompts.YML")
+        );
+        assert_eq!(BundleFormat::Yaml, BundleFormat::from_path("prompts.yaml"));
+        assert_eq!(BundleFormat::Json, BundleFormat::from_path("prompts.json"));
+        assert_eq!(BundleFormat::Json, BundleFormat::from_path("prompts"));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for format in [BundleFormat::Json, BundleFormat::Yaml] {
+            let text = serialize_bundle(&bundle(), format).unwrap();
+            assert_eq!(bundle(), parse_bundle(&text, format).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_parse_minimal_bundle() {
+        let text = "version: 1\nprompts:\n  - alias: a\n    content: b\n";
+        let bundle = parse_bundle(text, BundleFormat::Yaml).unwrap();
+        assert_eq!(1, bundle.prompts.len());
+        assert!(bundle.prompts[0].tags.is_empty());
+    }
+
+    #[test]
+    fn test_reject_newer_version() {
+        let text = r#"{"version": 99, "prompts": []}"#;
+        assert!(parse_bundle(text, BundleFormat::Json).is_err());
+        assert!(parse_bundle("not a bundle", BundleFormat::Json).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use entity::entities::{
+    messages::MessageDTO,
+    models::GenericConfig,
+    settings::ProxySetting,
+};
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use tokio::sync::Notify;
+
+use crate::log_utils::{info, warn};
+
+use super::{
+    db::Repository,
+    llm::{
+        client::LLMClient,
+        embeddings::{cosine_similarity, decode_vector, encode_vector},
+    },
+};
+
+/// Number of messages embedded in one pass of the indexer
+const INDEX_BATCH_SIZE: u64 = 32;
+/// Max number of characters of a message sent to the embedding model
+const MAX_INDEXED_CHARS: usize = 8000;
+pub const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// A message matching a semantic search, with the messages around it
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticSearchResult {
+    pub message: MessageDTO,
+    pub conversation_id: i32,
+    pub conversation_subject: String,
+    pub score: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous: Option<MessageDTO>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<MessageDTO>,
+}
+
+/// Background task embedding messages with the model set in [entity::entities::settings::SETTING_SEARCH_EMBEDDING_MODEL].
+/// It indexes all pending messages when the app starts and every time it's notified.
+pub struct MessageIndexer {
+    notify: Notify,
+}
+
+impl MessageIndexer {
+    pub fn global() -> &'static MessageIndexer {
+        static INSTANCE: OnceCell<MessageIndexer> = OnceCell::new();
+
+        INSTANCE.get_or_init(|| MessageIndexer {
+            notify: Notify::new(),
+        })
+    }
+
+    /// Wake the indexer up to index new or updated messages
+    pub fn notify(&self) {
+        self.notify.notify_one();
+    }
+
+    pub async fn run(&self, repo: &Repository) {
+        let log_tag = "search::MessageIndexer";
+        loop {
+            match index_pending_messages(repo).await {
+                Ok(0) => {}
+                Ok(count) => info(log_tag, format!("Indexed {} messages", count)),
+                Err(err) => warn(log_tag, format!("Failed to index messages: {}", err)),
+            }
+            self.notify.notified().await;
+        }
+    }
+}
+
+async fn get_embedding_model_id(repo: &Repository) -> Option<i32> {
+    repo.get_settings().await.search_embedding_model
+}
+
+async fn get_proxy_setting(repo: &Repository) -> Option<ProxySetting> {
+    repo.get_settings().await.proxy.clone()
+}
+
+async fn build_client(repo: &Repository, model_id: i32) -> Result<LLMClient, String> {
+    let model = repo.get_model(model_id).await?;
+    LLMClient::new(
+        GenericConfig {
+            provider: model.provider,
+            config: model.config,
+        },
+        get_proxy_setting(repo).await,
+    )
+}
+
+/// Embed all messages that are not indexed by the embedding model yet.
+/// Each batch holds the workspace, so that it's read, embedded and written within the same workspace.
+async fn index_pending_messages(repo: &Repository) -> Result<usize, String> {
+    let mut count = 0;
+    loop {
+        let _workspace = repo.hold_workspace().await;
+        let model_id = match get_embedding_model_id(repo).await {
+            Some(model_id) => model_id,
+            None => break,
+        };
+        let batch = repo
+            .list_unindexed_messages(model_id, INDEX_BATCH_SIZE)
+            .await?;
+        if batch.is_empty() {
+            break;
+        }
+        let client = build_client(repo, model_id).await?;
+        let (message_ids, texts): (Vec<i32>, Vec<String>) = batch
+            .into_iter()
+            .map(|(message_id, text)| (message_id, text.chars().take(MAX_INDEXED_CHARS).collect()))
+            .unzip();
+        let embeddings = client.embed(texts).await?;
+        count += message_ids.len();
+        let embeddings = message_ids
+            .into_iter()
+            .zip(embeddings.vectors.iter())
+            .map(|(message_id, vector)| (message_id, encode_vector(vector)))
+            .collect();
+        repo.upsert_message_embeddings(model_id, embeddings).await?;
+    }
+    Ok(count)
+}
+
+/// Find the messages closest in meaning to a query
+pub async fn semantic_search(
+    repo: &Repository,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<SemanticSearchResult>, String> {
+    let model_id = get_embedding_model_id(repo)
+        .await
+        .ok_or("Embedding model for search is not set".to_string())?;
+    let client = build_client(repo, model_id).await?;
+    let embeddings = client.embed(vec![query.to_string()]).await?;
+    let query_vector = embeddings
+        .vectors
+        .first()
+        .ok_or("Failed to embed query".to_string())?;
+    // deleted messages are left out before the best ones are kept
+    let mut scores: Vec<(i32, f32)> = repo
+        .list_message_embeddings(model_id)
+        .await?
+        .into_iter()
+        .map(|embedding| {
+            (
+                embedding.message_id,
+                cosine_similarity(query_vector, &decode_vector(&embedding.embedding)),
+            )
+        })
+        .collect();
+    scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    scores.truncate(limit);
+    let mut messages = repo
+        .get_messages_with_conversations(scores.iter().map(|(message_id, _)| *message_id).collect())
+        .await?;
+    let mut results = vec![];
+    for (message_id, score) in scores {
+        let position = messages
+            .iter()
+            .position(|(message, _)| message.id == Some(message_id));
+        // the message or its conversation has been deleted meanwhile
+        let (message, conversation) = match position {
+            Some(position) => messages.swap_remove(position),
+            None => continue,
+        };
+        let (previous, next) = repo
+            .get_adjacent_messages(conversation.id, message_id)
+            .await?;
+        results.push(SemanticSearchResult {
+            message,
+            conversation_id: conversation.id,
+            conversation_subject: conversation.subject,
+            score,
+            previous,
+            next,
+        });
+    }
+    Ok(results)
+}
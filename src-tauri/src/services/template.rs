@@ -0,0 +1,439 @@
+//! Prompt templates.
+//!
+//! A placeholder is written `{name}`, optionally followed by a type, a default value and a
+//! description: `{name:type=default|description}`, e.g. `{Tone:text=friendly|Tone of the reply}`.
+//! Supported types are `text` (the default), `number`, `boolean` and `date` (`YYYY-MM-DD`).
+//! A variable without default value is required.
+//!
+//! Built-in variables start with `@` and are filled in by the app, see [`BUILTIN_VARIABLES`].
+//!
+//! Braces that don't enclose a valid placeholder, as in JSON or code, are kept as is.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use entity::entities::prompt_versions::Model as PromptVersion;
+use serde::Serialize;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::core::handle::Handle;
+use crate::log_utils::warn;
+
+use super::db::Repository;
+
+pub const BUILTIN_DATE: &str = "@date";
+pub const BUILTIN_TIME: &str = "@time";
+pub const BUILTIN_DATETIME: &str = "@datetime";
+pub const BUILTIN_MODEL: &str = "@model";
+pub const BUILTIN_CLIPBOARD: &str = "@clipboard";
+
+pub const BUILTIN_VARIABLES: [&str; 5] = [
+    BUILTIN_DATE,
+    BUILTIN_TIME,
+    BUILTIN_DATETIME,
+    BUILTIN_MODEL,
+    BUILTIN_CLIPBOARD,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VariableType {
+    Text,
+    Number,
+    Boolean,
+    Date,
+}
+
+impl VariableType {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "text" => Some(VariableType::Text),
+            "number" => Some(VariableType::Number),
+            "boolean" => Some(VariableType::Boolean),
+            "date" => Some(VariableType::Date),
+            _ => None,
+        }
+    }
+
+    fn validate(&self, value: &str) -> bool {
+        match self {
+            VariableType::Text => true,
+            VariableType::Number => value.trim().parse::<f64>().is_ok(),
+            VariableType::Boolean => {
+                matches!(value.trim().to_lowercase().as_str(), "true" | "false")
+            }
+            VariableType::Date => NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").is_ok(),
+        }
+    }
+}
+
+/// A variable of a template to be filled in by the user
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateVariable {
+    pub name: String,
+    pub r#type: VariableType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub required: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Variable(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    segments: Vec<Segment>,
+    variables: Vec<TemplateVariable>,
+}
+
+impl Template {
+    pub fn parse(content: &str) -> Self {
+        let mut segments: Vec<Segment> = vec![];
+        let mut variables: Vec<TemplateVariable> = vec![];
+        let mut text = String::new();
+        let mut rest = content;
+        while let Some(start) = rest.find('{') {
+            text.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let placeholder = after
+                .find(['}', '{', '\n'])
+                .filter(|end| after[*end..].starts_with('}'))
+                .and_then(|end| parse_placeholder(&after[..end]).map(|parsed| (end, parsed)));
+            match placeholder {
+                Some((end, (name, variable))) => {
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    if let Some(variable) = variable {
+                        // the first occurrence of a variable defines it
+                        if !variables.iter().any(|v| v.name == variable.name) {
+                            variables.push(variable);
+                        }
+                    }
+                    segments.push(Segment::Variable(name));
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    text.push('{');
+                    rest = after;
+                }
+            }
+        }
+        text.push_str(rest);
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        Template {
+            segments,
+            variables,
+        }
+    }
+
+    /// Variables to be filled in by the user, in order of appearance
+    pub fn variables(&self) -> &[TemplateVariable] {
+        &self.variables
+    }
+
+    /// Whether the template uses a built-in variable
+    pub fn uses_builtin(&self, name: &str) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::Variable(n) if n == name))
+    }
+
+    /// Render the template, failing if a required variable is missing or a value doesn't match
+    /// the type of its variable
+    pub fn render(
+        &self,
+        values: &HashMap<String, String>,
+        builtins: &HashMap<&str, String>,
+    ) -> Result<String, String> {
+        let mut resolved: HashMap<&str, String> = HashMap::new();
+        let mut missing: Vec<&str> = vec![];
+        let mut invalid: Vec<String> = vec![];
+        for variable in &self.variables {
+            let value = values
+                .get(&variable.name)
+                .filter(|value| !value.trim().is_empty())
+                .or(variable.default.as_ref());
+            match value {
+                Some(value) if variable.r#type.validate(value) => {
+                    resolved.insert(&variable.name, value.to_string());
+                }
+                Some(value) => invalid.push(format!(
+                    "{} (expected {:?}, got '{}')",
+                    variable.name, variable.r#type, value
+                )),
+                None => missing.push(&variable.name),
+            }
+        }
+        if !missing.is_empty() {
+            return Err(format!(
+                "Missing value of required variables: {}",
+                missing.join(", ")
+            ));
+        }
+        if !invalid.is_empty() {
+            return Err(format!(
+                "Invalid value of variables: {}",
+                invalid.join(", ")
+            ));
+        }
+
+        let mut result = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => result.push_str(text),
+                Segment::Variable(name) => {
+                    let value = resolved
+                        .get(name.as_str())
+                        .or(builtins.get(name.as_str()))
+                        .ok_or(format!(
+                            "Value of built-in variable {} is not available",
+                            name
+                        ))?;
+                    result.push_str(value);
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Parse the inside of braces, returning the name of the variable and, if it isn't built-in,
+/// its definition. None if it isn't a valid placeholder.
+fn parse_placeholder(spec: &str) -> Option<(String, Option<TemplateVariable>)> {
+    let (spec, description) = match spec.split_once('|') {
+        Some((spec, description)) => (spec, Some(description.trim().to_string())),
+        None => (spec, None),
+    };
+    let (spec, default) = match spec.split_once('=') {
+        Some((spec, default)) => (spec, Some(default.to_string())),
+        None => (spec, None),
+    };
+    let (name, r#type) = match spec.split_once(':') {
+        Some((name, r#type)) => (name.trim(), VariableType::parse(r#type.trim())?),
+        None => (spec.trim(), VariableType::Text),
+    };
+    if name.starts_with('@') {
+        // built-in variables take no definition
+        let is_plain = spec.trim() == name && default.is_none() && description.is_none();
+        return if is_plain && BUILTIN_VARIABLES.contains(&name) {
+            Some((name.to_string(), None))
+        } else {
+            None
+        };
+    }
+    if !is_identifier(name) {
+        return None;
+    }
+    let variable = TemplateVariable {
+        name: name.to_string(),
+        r#type,
+        required: default.is_none(),
+        default,
+        description: description.filter(|description| !description.is_empty()),
+    };
+    Some((name.to_string(), Some(variable)))
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => chars.all(|c| c.is_alphanumeric() || c == '_'),
+        _ => false,
+    }
+}
+
+/// Variables of a prompt to be filled in by the user
+pub async fn get_prompt_variables(
+    repo: &Repository,
+    prompt_id: i32,
+) -> Result<Vec<TemplateVariable>, String> {
+    let prompt = repo.get_prompt(prompt_id).await?;
+    Ok(Template::parse(&prompt.content).variables().to_vec())
+}
+
+/// Render a prompt, or one of its versions, with the values of its variables and count it as used.
+/// Without a version, the version pinned by the conversation is rendered if any.
+/// The alias of the model is only available when a model is given.
+pub async fn render_prompt(
+    repo: &Repository,
+    prompt_id: i32,
+    version: Option<i32>,
+    values: HashMap<String, String>,
+    model_id: Option<i32>,
+    conversation_id: Option<i32>,
+) -> Result<String, String> {
+    let content = match version {
+        Some(version) => repo.get_prompt_version(prompt_id, version).await?.content,
+        None => match get_pinned_version(repo, prompt_id, conversation_id).await? {
+            Some(prompt_version) => prompt_version.content,
+            None => repo.get_prompt(prompt_id).await?.content,
+        },
+    };
+    let template = Template::parse(&content);
+
+    let now = chrono::Local::now();
+    let mut builtins: HashMap<&str, String> = HashMap::new();
+    builtins.insert(BUILTIN_DATE, now.format("%Y-%m-%d").to_string());
+    builtins.insert(BUILTIN_TIME, now.format("%H:%M").to_string());
+    builtins.insert(BUILTIN_DATETIME, now.format("%Y-%m-%d %H:%M").to_string());
+    if let Some(model_id) = model_id {
+        if template.uses_builtin(BUILTIN_MODEL) {
+            builtins.insert(BUILTIN_MODEL, repo.get_model(model_id).await?.alias);
+        }
+    }
+    if template.uses_builtin(BUILTIN_CLIPBOARD) {
+        builtins.insert(BUILTIN_CLIPBOARD, read_clipboard_text()?);
+    }
+
+    let result = template.render(&values, &builtins)?;
+    repo.record_prompt_usage(prompt_id).await?;
+    Ok(result)
+}
+
+/// Version of a prompt pinned by a conversation.
+/// Pins have no foreign key, so a pin to a version that is gone or of another prompt is ignored.
+async fn get_pinned_version(
+    repo: &Repository,
+    prompt_id: i32,
+    conversation_id: Option<i32>,
+) -> Result<Option<PromptVersion>, String> {
+    let conversation_id = match conversation_id {
+        Some(conversation_id) => conversation_id,
+        None => return Ok(None),
+    };
+    let conversation = repo.get_conversation(conversation_id).await?;
+    let prompt_version_id = match conversation.prompt_version_id {
+        Some(prompt_version_id) => prompt_version_id,
+        None => return Ok(None),
+    };
+    match repo.get_prompt_version_by_id(prompt_version_id).await {
+        Ok(prompt_version) if prompt_version.prompt_id == prompt_id => Ok(Some(prompt_version)),
+        Ok(_) => Ok(None),
+        Err(err) => {
+            warn("template::get_pinned_version", err);
+            Ok(None)
+        }
+    }
+}
+
+fn read_clipboard_text() -> Result<String, String> {
+    let app_handle = Handle::global()
+        .app_handle
+        .lock()
+        .expect("Failed to lock app handle mutex")
+        .clone()
+        .expect("App handle is not initialized");
+    app_handle
+        .clipboard()
+        .read_text()
+        .map_err(|err| format!("Failed to read text of clipboard: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_variables() {
+        let template = Template::parse(
+            "Q:{Question}\n{Count:number=3|How many answers}{Question} {When:date} {@date}",
+        );
+        assert_eq!(
+            vec![
+                TemplateVariable {
+                    name: "Question".to_string(),
+                    r#type: VariableType::Text,
+                    default: None,
+                    description: None,
+                    required: true,
+                },
+                TemplateVariable {
+                    name: "Count".to_string(),
+                    r#type: VariableType::Number,
+                    default: Some("3".to_string()),
+                    description: Some("How many answers".to_string()),
+                    required: false,
+                },
+                TemplateVariable {
+                    name: "When".to_string(),
+                    r#type: VariableType::Date,
+                    default: None,
+                    description: None,
+                    required: true,
+                },
+            ],
+            template.variables()
+        );
+        assert!(template.uses_builtin(BUILTIN_DATE));
+        assert!(!template.uses_builtin(BUILTIN_CLIPBOARD));
+    }
+
+    #[test]
+    fn test_keep_braces_which_are_not_placeholders() {
+        let content = r#"{"a": {"b": 1}} {x:unknown} {@unknown} { } {not closed"#;
+        let template = Template::parse(content);
+        assert!(template.variables().is_empty());
+        assert_eq!(
+            Ok(content.to_string()),
+            template.render(&HashMap::new(), &HashMap::new())
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let template = Template::parse("{Tone=friendly}: {Question}? ({Question}, {@model})");
+        let mut builtins = HashMap::new();
+        builtins.insert(BUILTIN_MODEL, "gpt".to_string());
+        assert_eq!(
+            Ok("friendly: why? (why, gpt)".to_string()),
+            template.render(&values(&[("Question", "why")]), &builtins)
+        );
+        assert_eq!(
+            Ok("calm: why? (why, gpt)".to_string()),
+            template.render(&values(&[("Question", "why"), ("Tone", "calm")]), &builtins)
+        );
+    }
+
+    #[test]
+    fn test_render_validates_values() {
+        let template = Template::parse("{A} {B} {N:number} {F:boolean=false}");
+        let err = template
+            .render(&values(&[("A", "a"), ("B", " ")]), &HashMap::new())
+            .unwrap_err();
+        assert!(err.contains("B, N"));
+        let err = template
+            .render(
+                &values(&[("A", "a"), ("B", "b"), ("N", "x"), ("F", "true")]),
+                &HashMap::new(),
+            )
+            .unwrap_err();
+        assert!(err.contains("N (expected Number"));
+        assert_eq!(
+            Ok("a b 1.5 true".to_string()),
+            template.render(
+                &values(&[("A", "a"), ("B", "b"), ("N", "1.5"), ("F", "true")]),
+                &HashMap::new()
+            )
+        );
+        // built-in variable without value
+        assert!(Template::parse("{@clipboard}")
+            .render(&HashMap::new(), &HashMap::new())
+            .is_err());
+    }
+}
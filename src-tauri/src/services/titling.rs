@@ -0,0 +1,233 @@
+use entity::entities::{
+    contents::{ContentDTO, ContentType},
+    conversations::GenericOptions,
+    messages::{MessageDTO, Roles},
+    models::GenericConfig,
+};
+use serde::Serialize;
+
+use crate::log_utils::info;
+
+use super::{
+    db::{provider_options, Repository},
+    llm::{chat::GlobalSettings, client::LLMClient},
+};
+
+/// Titles are cut to this number of characters
+const MAX_TITLE_CHARS: usize = 80;
+/// Messages are cut to this number of characters when asking for a title
+const MAX_EXCERPT_CHARS: usize = 2000;
+
+/// Payload of the event emitted when a conversation is titled
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationTitle {
+    pub conversation_id: i32,
+    pub subject: String,
+}
+
+/// English name of the languages the app is translated to, other tags are used as they are
+fn language_name(tag: &str) -> &str {
+    match tag {
+        "en" => "English",
+        "zh-Hans" => "Simplified Chinese",
+        "zh-Hant" => "Traditional Chinese",
+        _ => tag,
+    }
+}
+
+fn excerpt(text: &str) -> String {
+    text.chars().take(MAX_EXCERPT_CHARS).collect()
+}
+
+/// Title out of the reply of a model, which may be quoted or prefixed despite being asked not to.
+/// None if the reply is blank.
+fn clean_title(reply: &str) -> Option<String> {
+    let line = reply.lines().map(str::trim).find(|line| !line.is_empty())?;
+    let line = line.trim_start_matches('#').trim_start();
+    let line = match line.get(..6) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("title:") => line[6..].trim_start(),
+        _ => line,
+    };
+    let title: String = line
+        .trim_matches(|c| {
+            matches!(c, '"' | '\'' | '`' | '*' | '“' | '”' | '「' | '」' | '.' | '。')
+        })
+        .trim()
+        .chars()
+        .take(MAX_TITLE_CHARS)
+        .collect();
+    Some(title.trim_end().to_string()).filter(|title| !title.is_empty())
+}
+
+/// Whether a message is the only bot reply of its conversation, later replies aren't titled
+fn is_first_reply(messages: &[MessageDTO], message: &MessageDTO) -> bool {
+    let mut replies = messages
+        .iter()
+        .filter(|message| Roles::from(message.role) == Roles::Bot);
+    message.id.is_some()
+        && replies.next().and_then(|reply| reply.id) == message.id
+        && replies.next().is_none()
+}
+
+fn text_message(conversation_id: i32, role: Roles, text: String) -> MessageDTO {
+    MessageDTO {
+        conversation_id,
+        role: role.into(),
+        content: vec![ContentDTO {
+            r#type: ContentType::Text,
+            mimetype: None,
+            data: text,
+        }],
+        ..Default::default()
+    }
+}
+
+/// Title a conversation once its first bot reply is saved, with the model set in
+/// [entity::entities::settings::SETTING_TITLE_MODEL] or the model of the conversation.
+/// Subjects edited by the user are kept.
+/// Returns the new subject, None if the conversation isn't titled.
+pub async fn title_after_first_reply(
+    repo: &Repository,
+    message: &MessageDTO,
+) -> Result<Option<String>, String> {
+    let log_tag = "titling::title_after_first_reply";
+    let settings = repo.get_settings().await;
+    if !settings.auto_title || Roles::from(message.role) != Roles::Bot {
+        return Ok(None);
+    }
+    let conversation_id = message.conversation_id;
+    let conversation = repo.get_conversation(conversation_id).await?;
+    if conversation.subject_edited {
+        return Ok(None);
+    }
+    let messages = repo.list_messages(conversation_id).await?;
+    if !is_first_reply(&messages, message) {
+        return Ok(None);
+    }
+    let question = messages
+        .iter()
+        .find(|message| Roles::from(message.role) == Roles::User)
+        .and_then(|message| message.get_text())
+        .unwrap_or_default();
+    let answer = match message.get_text().filter(|text| !text.trim().is_empty()) {
+        Some(answer) => answer,
+        None => return Ok(None),
+    };
+    let title_model = match settings.title_model {
+        Some(model_id) => repo
+            .get_model(model_id)
+            .await
+            .ok()
+            .filter(|model| model.deleted_at.is_none()),
+        None => None,
+    };
+    let model = match (title_model, conversation.model_id) {
+        (Some(model), _) => model,
+        (None, Some(model_id)) => repo.get_model(model_id).await?,
+        (None, None) => return Ok(None),
+    };
+    let language = settings.display_language.as_deref().unwrap_or("en");
+    let context = vec![
+        text_message(
+            conversation_id,
+            Roles::System,
+            format!(
+                "You write titles of conversations. Reply with a title of at most 6 words in {}, \
+                 summarizing the topic of the conversation. Reply with the title only, without \
+                 quotes nor a final period.",
+                language_name(language)
+            ),
+        ),
+        text_message(
+            conversation_id,
+            Roles::User,
+            format!(
+                "User: {}\n\nAssistant: {}",
+                excerpt(&question),
+                excerpt(&answer)
+            ),
+        ),
+    ];
+    // the title is used once complete, whether the conversation streams or not
+    let options = GenericOptions {
+        options: provider_options(
+            &model.provider,
+            &[model.default_options.as_deref(), Some(r#"{"stream":false}"#)],
+        ),
+        provider: model.provider.clone(),
+    };
+    let client = LLMClient::new(
+        GenericConfig {
+            provider: model.provider,
+            config: model.config,
+        },
+        settings.proxy.clone(),
+    )?;
+    let reply = client
+        .chat(
+            context,
+            options,
+            GlobalSettings {
+                max_tokens: settings.max_tokens,
+            },
+        )
+        .await?;
+    let title = match clean_title(&reply.message) {
+        Some(title) => title,
+        None => return Ok(None),
+    };
+    let subject = repo
+        .update_conversation_subject(conversation_id, title, true)
+        .await?;
+    info(
+        log_tag,
+        format!("Titled conversation {}: {}", conversation_id, subject),
+    );
+    Ok(Some(subject))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_title() {
+        assert_eq!(
+            Some("Rust lifetimes explained".to_string()),
+            clean_title("\n  \"Rust lifetimes explained.\"\nThis covers...")
+        );
+        assert_eq!(
+            Some("Trip to Kyoto".to_string()),
+            clean_title("## Title: **Trip to Kyoto**")
+        );
+        assert_eq!(Some("旅行计划".to_string()), clean_title("「旅行计划」。"));
+        assert_eq!(None, clean_title("  \n \"\" "));
+        assert_eq!(MAX_TITLE_CHARS, clean_title(&"a".repeat(200)).unwrap().len());
+    }
+
+    #[test]
+    fn test_is_first_reply() {
+        let message = |id: i32, role: Roles| MessageDTO {
+            id: Some(id),
+            ..text_message(1, role, String::default())
+        };
+        let question = message(1, Roles::User);
+        let reply = message(2, Roles::Bot);
+        let follow_up = message(3, Roles::User);
+        let second_reply = message(4, Roles::Bot);
+        assert!(is_first_reply(&[question.clone(), reply.clone()], &reply));
+        assert!(!is_first_reply(
+            &[question.clone(), reply.clone(), follow_up, second_reply.clone()],
+            &second_reply
+        ));
+        assert!(!is_first_reply(&[question.clone()], &reply));
+        assert!(!is_first_reply(&[question], &MessageDTO::default()));
+    }
+
+    #[test]
+    fn test_language_name() {
+        assert_eq!("Simplified Chinese", language_name("zh-Hans"));
+        assert_eq!("fr", language_name("fr"));
+    }
+}
@@ -0,0 +1,68 @@
+use entity::entities::{
+    contents::{ContentDTO, ContentType, MIMETYPE_TRANSCRIPT},
+    messages::MessageDTO,
+    models::GenericConfig,
+    settings::ProxySetting,
+};
+
+use super::{
+    cache,
+    db::Repository,
+    llm::{audio::AudioFile, capabilities::is_transcript, client::LLMClient},
+};
+
+/// Transcribe the audio contents of a message with the model set in [entity::entities::settings::SETTING_AUDIO_TRANSCRIPTION_MODEL].
+/// Each transcript is stored as a text content right after its audio, replacing previous transcripts.
+pub async fn transcribe_message(
+    repo: &Repository,
+    message_id: i32,
+    proxy_setting: Option<ProxySetting>,
+) -> Result<MessageDTO, String> {
+    let mut message = repo.get_message(message_id).await?;
+    if !message
+        .content
+        .iter()
+        .any(|item| item.r#type == ContentType::Audio)
+    {
+        return Err(format!("Message with id {} has no audio", message_id));
+    }
+    let model_id = repo
+        .get_settings()
+        .await
+        .audio_transcription_model
+        .ok_or("Transcription model is not set".to_string())?;
+    let model = repo.get_model(model_id).await?;
+    let client = LLMClient::new(
+        GenericConfig {
+            provider: model.provider,
+            config: model.config,
+        },
+        proxy_setting,
+    )?;
+    let mut content = vec![];
+    for item in message.content {
+        if is_transcript(&item) {
+            continue;
+        }
+        let is_audio = item.r#type == ContentType::Audio;
+        let audio = if is_audio {
+            Some(AudioFile {
+                file_name: item.data.clone(),
+                mimetype: item.mimetype.clone().unwrap_or("audio/wav".to_string()),
+                bytes: cache::read_bytes(&item.data)?,
+            })
+        } else {
+            None
+        };
+        content.push(item);
+        if let Some(audio) = audio {
+            content.push(ContentDTO {
+                r#type: ContentType::Text,
+                mimetype: Some(MIMETYPE_TRANSCRIPT.to_string()),
+                data: client.transcribe(audio).await?,
+            });
+        }
+    }
+    message.content = content;
+    repo.update_message(message).await
+}
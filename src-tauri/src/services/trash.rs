@@ -0,0 +1,74 @@
+use entity::entities::{conversations::ConversationDTO, models::Model};
+use serde::Serialize;
+
+use crate::log_utils::info;
+
+use super::{db::Repository, gc};
+
+/// Soft deleted conversations and models
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Trash {
+    pub conversations: Vec<ConversationDTO>,
+    pub models: Vec<Model>,
+}
+
+/// Result of a purge of the trash
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeReport {
+    pub purged_conversations: usize,
+    pub purged_models: usize,
+    pub removed_files: usize,
+    pub reclaimed_bytes: u64,
+}
+
+pub async fn list_trash(repo: &Repository) -> Result<Trash, String> {
+    Ok(Trash {
+        conversations: repo.list_deleted_conversations().await?,
+        models: repo.list_deleted_models().await?,
+    })
+}
+
+/// Number of days deleted items are kept in the trash, None if they are kept until purged manually
+pub async fn get_retention_days(repo: &Repository) -> Option<i64> {
+    repo.get_settings().await.trash_retention_days
+}
+
+/// Permanently delete items of the trash, optionally only those deleted before a date,
+/// then remove the files of their contents from cache unless other contents reference them.
+/// Speech of purged messages is left to the cache gc.
+pub async fn purge_trash(
+    repo: &Repository,
+    older_than: Option<chrono::DateTime<chrono::Local>>,
+) -> Result<PurgeReport, String> {
+    let (conversation_ids, file_names) = repo.purge_conversations(older_than).await?;
+    // conversations go first as models still used by one are kept
+    let model_ids = repo.purge_models(older_than).await?;
+    let gc_report = gc::remove_unreferenced_files(repo, file_names).await?;
+    let report = PurgeReport {
+        purged_conversations: conversation_ids.len(),
+        purged_models: model_ids.len(),
+        removed_files: gc_report.removed_files,
+        reclaimed_bytes: gc_report.reclaimed_bytes,
+    };
+    info(
+        "trash::purge_trash",
+        format!(
+            "Purged {} conversations and {} models, removed {} files",
+            report.purged_conversations, report.purged_models, report.removed_files
+        ),
+    );
+    Ok(report)
+}
+
+/// Purge items deleted longer ago than the retention period, if one is set
+pub async fn purge_expired_trash(repo: &Repository) -> Result<Option<PurgeReport>, String> {
+    match get_retention_days(repo).await {
+        Some(days) => {
+            let older_than = chrono::Local::now() - chrono::Duration::days(days);
+            purge_trash(repo, Some(older_than)).await.map(Some)
+        }
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,189 @@
+use std::path::PathBuf;
+
+use entity::entities::{
+    contents::ContentType,
+    messages::Roles,
+    models::GenericConfig,
+    settings::ProxySetting,
+};
+use sha2::{Digest, Sha256};
+
+use crate::log_utils::info;
+
+use super::{
+    cache,
+    db::Repository,
+    knowledge::chunk_text,
+    llm::{audio::SpeechRequest, client::LLMClient},
+};
+
+/// Max number of characters of the input of one speech request
+const MAX_SPEECH_INPUT_CHARS: usize = 4096;
+/// Formats whose files can be joined when a message is synthesized in several requests
+const CONCATENABLE_FORMATS: &[&str] = &["mp3", "aac", "opus", "pcm"];
+/// Sub-directory of the cache dir storing synthesized speech
+pub const TTS_CACHE_DIR: &str = "tts";
+
+/// Speech settings stored in `settings`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpeechSettings {
+    pub model_id: i32,
+    pub voice: String,
+    pub format: String,
+    pub speed: f32,
+}
+
+impl SpeechSettings {
+    pub async fn load(repo: &Repository) -> Result<Self, String> {
+        let settings = repo.get_settings().await;
+        let model_id = settings
+            .tts_model
+            .ok_or("Speech model is not set".to_string())?;
+        Ok(SpeechSettings {
+            model_id,
+            voice: settings.tts_voice.clone(),
+            format: settings.tts_format.clone(),
+            speed: settings.tts_speed,
+        })
+    }
+}
+
+/// Name of the cached speech of a message, changing whenever the text or the settings change
+pub fn speech_file_name(message_id: i32, text: &str, settings: &SpeechSettings) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(text.as_bytes());
+    hasher.update(
+        format!(
+            "\n{}:{}:{}:{}",
+            settings.model_id, settings.voice, settings.format, settings.speed
+        )
+        .as_bytes(),
+    );
+    let hash = format!("{:x}", hasher.finalize());
+    format!("{}_{}.{}", message_id, &hash[..16], settings.format)
+}
+
+/// Id of the message a cached speech file belongs to
+pub fn speech_message_id(file_name: &str) -> Option<i32> {
+    file_name
+        .split_once('_')
+        .and_then(|(message_id, _)| message_id.parse::<i32>().ok())
+}
+
+/// Synthesize the text of a bot message, returning the path of the cached audio file.
+/// The file is only synthesized again when the message or the speech settings have changed.
+pub async fn synthesize_message(
+    repo: &Repository,
+    message_id: i32,
+    proxy_setting: Option<ProxySetting>,
+) -> Result<PathBuf, String> {
+    let log_tag = "tts::synthesize_message";
+    let message = repo.get_message(message_id).await?;
+    if Roles::from(message.role) != Roles::Bot {
+        return Err(format!("Message with id {} is not a bot message", message_id));
+    }
+    let text = message
+        .content
+        .iter()
+        .filter(|item| item.r#type == ContentType::Text)
+        .map(|item| item.data.as_str())
+        .collect::<Vec<&str>>()
+        .join("\n\n");
+    if text.trim().is_empty() {
+        return Err(format!(
+            "Message with id {} has no text to speak",
+            message_id
+        ));
+    }
+    let settings = SpeechSettings::load(repo).await?;
+    let mut dir = cache::get_cache_dir()?;
+    dir.push(TTS_CACHE_DIR);
+    let path = dir.join(speech_file_name(message_id, &text, &settings));
+    if path.exists() {
+        return Ok(path);
+    }
+    let inputs = chunk_text(&text, MAX_SPEECH_INPUT_CHARS, 0);
+    if inputs.len() > 1 && !CONCATENABLE_FORMATS.contains(&settings.format.as_str()) {
+        return Err(format!(
+            "Message is too long to be synthesized as {}, choose one of {}",
+            settings.format,
+            CONCATENABLE_FORMATS.join(", ")
+        ));
+    }
+    let model = repo.get_model(settings.model_id).await?;
+    let client = LLMClient::new(
+        GenericConfig {
+            provider: model.provider,
+            config: model.config,
+        },
+        proxy_setting,
+    )?;
+    let mut audio = vec![];
+    for input in inputs {
+        let bytes = client
+            .speak(SpeechRequest {
+                model: String::default(),
+                input,
+                voice: settings.voice.clone(),
+                response_format: settings.format.clone(),
+                speed: settings.speed,
+            })
+            .await?;
+        audio.extend(bytes);
+    }
+    // drop speech of previous versions of the message
+    remove_cached_speech(message_id);
+    std::fs::create_dir_all(&dir).map_err(|_| "Failed to create speech cache dir".to_string())?;
+    std::fs::write(&path, audio)
+        .map_err(|_| format!("Failed to write speech of message {} to cache", message_id))?;
+    info(
+        log_tag,
+        format!("Synthesized message {} to {}", message_id, path.display()),
+    );
+    Ok(path)
+}
+
+/// Remove the cached speech of a message
+pub fn remove_cached_speech(message_id: i32) {
+    let dir = match cache::get_cache_dir() {
+        Ok(mut dir) => {
+            dir.push(TTS_CACHE_DIR);
+            dir
+        }
+        Err(_) => return,
+    };
+    let prefix = format!("{}_", message_id);
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entity::entities::settings::{DEFAULT_TTS_FORMAT, DEFAULT_TTS_SPEED, DEFAULT_TTS_VOICE};
+
+    #[test]
+    fn test_speech_file_name() {
+        let settings = SpeechSettings {
+            model_id: 1,
+            voice: DEFAULT_TTS_VOICE.to_string(),
+            format: DEFAULT_TTS_FORMAT.to_string(),
+            speed: DEFAULT_TTS_SPEED,
+        };
+        let name = speech_file_name(42, "Hello", &settings);
+        assert!(name.starts_with("42_") && name.ends_with(".mp3"));
+        assert_eq!(name, speech_file_name(42, "Hello", &settings));
+        assert_ne!(name, speech_file_name(42, "Hello!", &settings));
+        let faster = SpeechSettings {
+            speed: 1.5,
+            ..settings.clone()
+        };
+        assert_ne!(name, speech_file_name(42, "Hello", &faster));
+        assert_eq!(speech_message_id(&name), Some(42));
+    }
+}
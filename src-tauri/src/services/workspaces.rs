@@ -0,0 +1,363 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use chrono::{DateTime, Local};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::core::handle::Handle;
+use crate::log_utils::{info, warn};
+
+use super::db::{Builder as RepoBuilder, Repository};
+
+/// Id of the workspace whose database and cache dir are those of the app data dir,
+/// as they were before workspaces existed
+pub const DEFAULT_WORKSPACE_ID: u32 = 0;
+/// File of the app data dir listing the workspaces
+const WORKSPACES_FILE: &str = "workspaces.json";
+/// Sub-directory of the app data dir storing the other workspaces, one dir per workspace
+const WORKSPACES_DIR: &str = "workspaces";
+const DB_FILE: &str = "database.sqlite";
+const CACHE_DIR: &str = "cache";
+
+/// A separate set of conversations, models and settings, with its own database and cache dir
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Workspace {
+    pub id: u32,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Local>>,
+}
+
+/// Workspaces along with the active one, stored in the app data dir
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceList {
+    pub active_id: u32,
+    pub workspaces: Vec<Workspace>,
+    /// Ids aren't reused, so that a new workspace never opens files left by a deleted one
+    #[serde(default)]
+    next_id: u32,
+}
+
+impl Default for WorkspaceList {
+    fn default() -> Self {
+        WorkspaceList {
+            active_id: DEFAULT_WORKSPACE_ID,
+            workspaces: vec![Workspace {
+                id: DEFAULT_WORKSPACE_ID,
+                name: "Default".to_string(),
+                created_at: None,
+            }],
+            next_id: DEFAULT_WORKSPACE_ID + 1,
+        }
+    }
+}
+
+impl WorkspaceList {
+    /// Read the list of an app data dir, the default one if there is none yet
+    pub fn load(app_data_dir: &Path) -> Self {
+        let path = app_data_dir.join(WORKSPACES_FILE);
+        if !path.exists() {
+            return WorkspaceList::default();
+        }
+        let result = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| {
+                serde_json::from_str::<WorkspaceList>(&text).map_err(|err| err.to_string())
+            });
+        match result {
+            Ok(mut list) => {
+                if !list.workspaces.iter().any(|w| w.id == DEFAULT_WORKSPACE_ID) {
+                    list.workspaces
+                        .insert(0, WorkspaceList::default().workspaces.remove(0));
+                }
+                if list.get(list.active_id).is_err() {
+                    list.active_id = DEFAULT_WORKSPACE_ID;
+                }
+                list
+            }
+            Err(err) => {
+                warn(
+                    "workspaces",
+                    format!(
+                        "Invalid {}, using the default workspace: {}",
+                        path.display(),
+                        err
+                    ),
+                );
+                WorkspaceList::default()
+            }
+        }
+    }
+
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        let path = app_data_dir.join(WORKSPACES_FILE);
+        let text = serde_json::to_string_pretty(self).map_err(|err| err.to_string())?;
+        fs::write(&path, text).map_err(|err| format!("Failed to write {}: {}", path.display(), err))
+    }
+
+    pub fn get(&self, id: u32) -> Result<&Workspace, String> {
+        self.workspaces
+            .iter()
+            .find(|workspace| workspace.id == id)
+            .ok_or(format!("Workspace with id {} doesn't exist", id))
+    }
+
+    /// Names are trimmed and must be unique, regardless of case
+    fn check_name(&self, id: Option<u32>, name: &str) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Workspace name is empty".to_string());
+        }
+        if self
+            .workspaces
+            .iter()
+            .any(|workspace| Some(workspace.id) != id && workspace.name.eq_ignore_ascii_case(name))
+        {
+            return Err(format!("Workspace {} already exists", name));
+        }
+        Ok(name.to_string())
+    }
+
+    pub fn create(&mut self, name: &str) -> Result<Workspace, String> {
+        let name = self.check_name(None, name)?;
+        let id = self
+            .workspaces
+            .iter()
+            .map(|workspace| workspace.id + 1)
+            .fold(self.next_id, u32::max);
+        self.next_id = id + 1;
+        let workspace = Workspace {
+            id,
+            name,
+            created_at: Some(Local::now()),
+        };
+        self.workspaces.push(workspace.clone());
+        Ok(workspace)
+    }
+
+    pub fn rename(&mut self, id: u32, name: &str) -> Result<Workspace, String> {
+        let name = self.check_name(Some(id), name)?;
+        let workspace = self
+            .workspaces
+            .iter_mut()
+            .find(|workspace| workspace.id == id)
+            .ok_or(format!("Workspace with id {} doesn't exist", id))?;
+        workspace.name = name;
+        Ok(workspace.clone())
+    }
+
+    /// Remove a workspace from the list, neither the default nor the active one can be removed
+    pub fn remove(&mut self, id: u32) -> Result<Workspace, String> {
+        if id == DEFAULT_WORKSPACE_ID {
+            return Err("The default workspace can't be deleted".to_string());
+        }
+        if id == self.active_id {
+            return Err(
+                "The active workspace can't be deleted, switch to another one first".to_string(),
+            );
+        }
+        let index = self
+            .workspaces
+            .iter()
+            .position(|workspace| workspace.id == id)
+            .ok_or(format!("Workspace with id {} doesn't exist", id))?;
+        Ok(self.workspaces.remove(index))
+    }
+}
+
+/// Dir of the database and cache of a workspace
+pub fn workspace_dir(app_data_dir: &Path, id: u32) -> PathBuf {
+    if id == DEFAULT_WORKSPACE_ID {
+        app_data_dir.to_path_buf()
+    } else {
+        app_data_dir.join(WORKSPACES_DIR).join(id.to_string())
+    }
+}
+
+pub fn db_path(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join(DB_FILE)
+}
+
+pub fn cache_dir(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join(CACHE_DIR)
+}
+
+/// Dir of the workspace in use, set when the app starts and when switching workspace
+pub struct ActiveWorkspace {
+    dir: RwLock<Option<PathBuf>>,
+}
+
+impl ActiveWorkspace {
+    pub fn global() -> &'static ActiveWorkspace {
+        static INSTANCE: OnceCell<ActiveWorkspace> = OnceCell::new();
+
+        INSTANCE.get_or_init(|| ActiveWorkspace {
+            dir: RwLock::new(None),
+        })
+    }
+
+    pub fn set(&self, dir: PathBuf) {
+        *self.dir.write().expect("Failed to lock active workspace") = Some(dir);
+    }
+
+    pub fn dir(&self) -> Option<PathBuf> {
+        self.dir
+            .read()
+            .expect("Failed to lock active workspace")
+            .clone()
+    }
+}
+
+fn get_app_data_dir() -> Result<PathBuf, String> {
+    let app_handle = Handle::global()
+        .app_handle
+        .lock()
+        .expect("Failed to lock app handle mutex")
+        .clone()
+        .expect("App handle is not initialized");
+    app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("App data path does't exist! {}", err))
+}
+
+/// Create the database and cache dirs of a workspace if they don't exist yet.
+/// Returns the path of its database.
+pub fn prepare_workspace_dir(workspace_dir: &Path) -> Result<PathBuf, String> {
+    let cache_dir = cache_dir(workspace_dir);
+    fs::create_dir_all(&cache_dir)
+        .map_err(|err| format!("Failed to create {}: {}", cache_dir.display(), err))?;
+    Ok(db_path(workspace_dir))
+}
+
+pub fn list_workspaces() -> Result<WorkspaceList, String> {
+    Ok(WorkspaceList::load(&get_app_data_dir()?))
+}
+
+pub fn create_workspace(name: &str) -> Result<Workspace, String> {
+    let app_data_dir = get_app_data_dir()?;
+    let mut list = WorkspaceList::load(&app_data_dir);
+    let workspace = list.create(name)?;
+    prepare_workspace_dir(&workspace_dir(&app_data_dir, workspace.id))?;
+    list.save(&app_data_dir)?;
+    info(
+        "workspaces",
+        format!("Created workspace {} ({})", workspace.name, workspace.id),
+    );
+    Ok(workspace)
+}
+
+pub fn rename_workspace(id: u32, name: &str) -> Result<Workspace, String> {
+    let app_data_dir = get_app_data_dir()?;
+    let mut list = WorkspaceList::load(&app_data_dir);
+    let workspace = list.rename(id, name)?;
+    list.save(&app_data_dir)?;
+    Ok(workspace)
+}
+
+/// Remove a workspace along with its database and cache
+pub fn delete_workspace(id: u32) -> Result<Workspace, String> {
+    let app_data_dir = get_app_data_dir()?;
+    let mut list = WorkspaceList::load(&app_data_dir);
+    let workspace = list.remove(id)?;
+    list.save(&app_data_dir)?;
+    let dir = workspace_dir(&app_data_dir, id);
+    if let Err(err) = fs::remove_dir_all(&dir) {
+        warn(
+            "workspaces",
+            format!("Failed to remove {}: {}", dir.display(), err),
+        );
+    }
+    info(
+        "workspaces",
+        format!("Deleted workspace {} ({})", workspace.name, workspace.id),
+    );
+    Ok(workspace)
+}
+
+/// Open and migrate the database of a workspace, then make the repository use it
+pub async fn switch_workspace(repo: &Repository, id: u32) -> Result<Workspace, String> {
+    let app_data_dir = get_app_data_dir()?;
+    let mut list = WorkspaceList::load(&app_data_dir);
+    let workspace = list.get(id)?.clone();
+    let dir = workspace_dir(&app_data_dir, id);
+    let db_path = prepare_workspace_dir(&dir)?
+        .to_str()
+        .ok_or("Workspace path is not a valid string!")?
+        .to_string();
+    // building and migrating block on the async runtime, which can't be done from one of its tasks
+    let new_repo = tauri::async_runtime::spawn_blocking(move || {
+        let repo = RepoBuilder::default().set_db_url(db_path).build()?;
+        repo.migrate()?;
+        Ok::<Repository, String>(repo)
+    })
+    .await
+    .map_err(|err| format!("Failed to open workspace {}: {}", workspace.name, err))??;
+    {
+        // background jobs of the previous workspace end before its database and cache dir are swapped
+        let _workspace = repo.lock_workspace().await;
+        repo.replace(new_repo);
+        ActiveWorkspace::global().set(dir);
+    }
+    list.active_id = id;
+    list.save(&app_data_dir)?;
+    info(
+        "workspaces",
+        format!(
+            "Switched to workspace {} ({})",
+            workspace.name, workspace.id
+        ),
+    );
+    Ok(workspace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_and_rename() {
+        let mut list = WorkspaceList::default();
+        let work = list.create(" Work ").unwrap();
+        assert_eq!((1, "Work"), (work.id, work.name.as_str()));
+        assert!(list.create("work").is_err());
+        assert!(list.create("  ").is_err());
+        assert_eq!(2, list.create("Personal").unwrap().id);
+        assert!(list.rename(work.id, "personal").is_err());
+        assert_eq!("WORK", list.rename(work.id, "WORK").unwrap().name);
+        assert!(list.rename(9, "Other").is_err());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut list = WorkspaceList::default();
+        let work = list.create("Work").unwrap();
+        assert!(list.remove(DEFAULT_WORKSPACE_ID).is_err());
+        list.active_id = work.id;
+        assert!(list.remove(work.id).is_err());
+        list.active_id = DEFAULT_WORKSPACE_ID;
+        assert_eq!(work, list.remove(work.id).unwrap());
+        assert!(list.remove(work.id).is_err());
+        assert_eq!(2, list.create("Work").unwrap().id);
+    }
+
+    #[test]
+    fn test_workspace_dir() {
+        let app_data_dir = Path::new("/data");
+        assert_eq!(
+            PathBuf::from("/data/cache"),
+            cache_dir(&workspace_dir(app_data_dir, DEFAULT_WORKSPACE_ID))
+        );
+        assert_eq!(
+            PathBuf::from("/data/workspaces/3/database.sqlite"),
+            db_path(&workspace_dir(app_data_dir, 3))
+        );
+    }
+}
@@ -11,6 +11,7 @@ import { HTML5Backend } from 'react-dnd-html5-backend';
 import { useTranslation } from 'react-i18next';
 
 import cache from '@/lib/cache';
+import { invokeStoreAttachment } from '@/lib/commands';
 import {
   CONTENT_ITEM_TYPE_IMAGE,
   CONTENT_ITEM_TYPE_TEXT,
@@ -91,11 +92,13 @@ const PromptInput = forwardRef<PromptInputHandler, Props>(
     }, []);
 
     const onClick = useCallback(async () => {
-      // Save files to cache
+      // Save files to cache, then store them by content so that identical images are kept once
       const tasks = files.map(async (file, index) => {
         const filename = `${Date.now()}-${index}.${getFileExt(file.fileName)}`;
         await cache.write(filename, file.fileData);
-        file.fileName = filename;
+        const stored = await invokeStoreAttachment(filename, file.fileType);
+        file.fileName = stored.data;
+        file.fileType = stored.mimetype ?? file.fileType;
       });
       await Promise.all(tasks);
       const promptStr = promptRef.current?.value ?? '';
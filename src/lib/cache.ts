@@ -10,7 +10,21 @@ import {
   writeFile,
 } from '@tauri-apps/plugin-fs';
 
-const BASE_PATH = `cache${sep()}`;
+import { DEFAULT_WORKSPACE_ID } from './constants';
+
+// Cache dir of the active workspace, relative to App Data
+let BASE_PATH = `cache${sep()}`;
+
+/**
+ * Use the cache dir of a workspace
+ * @param workspaceId id of the active workspace
+ */
+function setWorkspace(workspaceId: number) {
+  BASE_PATH =
+    workspaceId === DEFAULT_WORKSPACE_ID
+      ? `cache${sep()}`
+      : `workspaces${sep()}${workspaceId}${sep()}cache${sep()}`;
+}
 
 /**
  * List all files in a directory
@@ -63,6 +77,7 @@ async function readObjectUrl(
 }
 
 export default {
+  setWorkspace,
   list,
   write,
   read,
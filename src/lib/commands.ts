@@ -1,22 +1,49 @@
 import { invoke } from '@tauri-apps/api/core';
 
 import type {
+  Collection,
+  ConnectionDiagnostic,
+  ContentItem,
   Conversation,
+  ConversationCursor,
   ConversationDetails,
+  ConversationFilter,
+  DiffLine,
+  Folder,
+  GcReport,
   GenericConfig,
   GenericModel,
   GenericOptions,
+  ImportConflict,
+  ImportReport,
+  KnowledgeDocument,
   Message,
+  MessageRevision,
   Model,
+  NewCollection,
   NewConversation,
+  NewFolder,
   NewMessage,
   NewModel,
+  NewPreset,
   NewPrompt,
+  NewTag,
   Options,
+  Page,
+  Preset,
   Prompt,
+  PromptSort,
+  PromptVersion,
+  PurgeReport,
   RemoteModel,
+  SemanticSearchResult,
   Setting,
+  Tag,
+  TemplateVariable,
+  Trash,
   UpdateConversation,
+  Workspace,
+  WorkspaceList,
 } from './types';
 import {
   fromGenericChatOptions,
@@ -47,6 +74,22 @@ export async function invokeUpdateModel(model: Model): Promise<Model> {
   return fromGenericModel(result);
 }
 
+export async function invokeUpdateModelDefaultOptions(
+  modelId: number,
+  defaultOptions?: Options
+): Promise<Model> {
+  let serialized: string | undefined;
+  if (defaultOptions) {
+    const { provider: ignored, ...rest } = defaultOptions;
+    serialized = JSON.stringify(rest);
+  }
+  const result = await invoke<GenericModel>('update_model_default_options', {
+    modelId,
+    defaultOptions: serialized,
+  });
+  return fromGenericModel(result);
+}
+
 export async function invokeDeleteModel(modelId: number): Promise<Model> {
   const result = await invoke<GenericModel>('delete_model', {
     modelId,
@@ -55,11 +98,22 @@ export async function invokeDeleteModel(modelId: number): Promise<Model> {
 }
 
 export async function invokeListRemoteModels(
-  config: GenericConfig
+  config: GenericConfig,
+  forceRefresh?: boolean
 ): Promise<RemoteModel[]> {
   console.log('invokeListRemoteModels', config);
   const result = await invoke<RemoteModel[]>('list_remote_models', {
     config,
+    forceRefresh,
+  });
+  return result;
+}
+
+export async function invokeTestModelConfig(
+  config: GenericConfig
+): Promise<ConnectionDiagnostic> {
+  const result = await invoke<ConnectionDiagnostic>('test_model_config', {
+    config,
   });
   return result;
 }
@@ -74,6 +128,45 @@ export async function invokeUpsertSetting(setting: Setting): Promise<Setting> {
   return result;
 }
 
+export async function invokeListWorkspaces(): Promise<WorkspaceList> {
+  const result = await invoke<WorkspaceList>('list_workspaces');
+  return result;
+}
+
+export async function invokeCreateWorkspace(name: string): Promise<Workspace> {
+  const result = await invoke<Workspace>('create_workspace', { name });
+  return result;
+}
+
+export async function invokeRenameWorkspace(
+  workspaceId: number,
+  name: string
+): Promise<Workspace> {
+  const result = await invoke<Workspace>('rename_workspace', {
+    workspaceId,
+    name,
+  });
+  return result;
+}
+
+/**
+ * Switch to another workspace, whose data replaces that of the current one.
+ * Windows reload when notified of the switch.
+ */
+export async function invokeSwitchWorkspace(
+  workspaceId: number
+): Promise<Workspace> {
+  const result = await invoke<Workspace>('switch_workspace', { workspaceId });
+  return result;
+}
+
+export async function invokeDeleteWorkspace(
+  workspaceId: number
+): Promise<Workspace> {
+  const result = await invoke<Workspace>('delete_workspace', { workspaceId });
+  return result;
+}
+
 export async function invokeCreateConversation(
   newConversation: NewConversation
 ): Promise<Conversation> {
@@ -96,10 +189,12 @@ export async function invokeCreateBlankConversation(
   return result;
 }
 
-export async function invokeListConversations(): Promise<
-  ConversationDetails[]
-> {
-  const result = await invoke<ConversationDetails[]>('list_conversations');
+export async function invokeListConversations(
+  filter?: ConversationFilter
+): Promise<ConversationDetails[]> {
+  const result = await invoke<ConversationDetails[]>('list_conversations', {
+    filter,
+  });
   return result;
 }
 
@@ -112,6 +207,102 @@ export async function invokeDeleteConversation(
   return result;
 }
 
+export async function invokeListConversationsPage(
+  filter?: ConversationFilter,
+  cursor?: ConversationCursor,
+  pageSize?: number
+): Promise<Page<ConversationDetails, ConversationCursor>> {
+  const result = await invoke<Page<ConversationDetails, ConversationCursor>>(
+    'list_conversations_page',
+    { filter, cursor, pageSize }
+  );
+  return result;
+}
+
+export async function invokeMoveConversation(
+  conversationId: number,
+  folderId?: number
+): Promise<void> {
+  await invoke('move_conversation', { conversationId, folderId });
+}
+
+export async function invokePinConversation(
+  conversationId: number,
+  pinned: boolean
+): Promise<void> {
+  await invoke('pin_conversation', { conversationId, pinned });
+}
+
+export async function invokeArchiveConversation(
+  conversationId: number,
+  archived: boolean
+): Promise<void> {
+  await invoke('archive_conversation', { conversationId, archived });
+}
+
+export async function invokeCreateFolder(
+  newFolder: NewFolder
+): Promise<Folder> {
+  const result = await invoke<Folder>('create_folder', { newFolder });
+  return result;
+}
+
+export async function invokeListFolders(): Promise<Folder[]> {
+  const result = await invoke<Folder[]>('list_folders');
+  return result;
+}
+
+export async function invokeRenameFolder(
+  folderId: number,
+  name: string
+): Promise<Folder> {
+  const result = await invoke<Folder>('rename_folder', { folderId, name });
+  return result;
+}
+
+export async function invokeDeleteFolder(folderId: number): Promise<void> {
+  await invoke('delete_folder', { folderId });
+}
+
+export async function invokeCreateTag(newTag: NewTag): Promise<Tag> {
+  const result = await invoke<Tag>('create_tag', { newTag });
+  return result;
+}
+
+export async function invokeListTags(): Promise<Tag[]> {
+  const result = await invoke<Tag[]>('list_tags');
+  return result;
+}
+
+export async function invokeUpdateTag(tag: Tag): Promise<Tag> {
+  const result = await invoke<Tag>('update_tag', { tag });
+  return result;
+}
+
+export async function invokeDeleteTag(tagId: number): Promise<void> {
+  await invoke('delete_tag', { tagId });
+}
+
+export async function invokeSetConversationTags(
+  conversationId: number,
+  tagIds: number[]
+): Promise<Tag[]> {
+  const result = await invoke<Tag[]>('set_conversation_tags', {
+    conversationId,
+    tagIds,
+  });
+  return result;
+}
+
+export async function invokeListConversationTags(
+  conversationId: number
+): Promise<Tag[]> {
+  const result = await invoke<Tag[]>('list_conversation_tags', {
+    conversationId,
+  });
+  return result;
+}
+
 export async function invokeUpdateConversationModel({
   conversationId,
   modelId,
@@ -175,6 +366,50 @@ export async function invokeUpdateSubject({
   });
 }
 
+export async function invokeImportAttachment(
+  path: string
+): Promise<ContentItem> {
+  const result = await invoke<ContentItem>('import_attachment', { path });
+  return result;
+}
+
+export async function invokeStoreAttachment(
+  fileName: string,
+  mimetype: string
+): Promise<ContentItem> {
+  const result = await invoke<ContentItem>('store_attachment', {
+    fileName,
+    mimetype,
+  });
+  return result;
+}
+
+export async function invokeGenerateImage(
+  conversationId: number,
+  beforeMessageId?: number
+): Promise<Message> {
+  const result = await invoke<Message>('generate_image', {
+    conversationId,
+    beforeMessageId,
+  });
+  return result;
+}
+
+export async function invokeTranscribeMessageAudio(
+  messageId: number
+): Promise<Message> {
+  const result = await invoke<Message>('transcribe_message_audio', {
+    messageId,
+  });
+  return result;
+}
+
+// Returns the path of the cached audio file
+export async function invokeGetMessageSpeech(messageId: number): Promise<string> {
+  const result = await invoke<string>('get_message_speech', { messageId });
+  return result;
+}
+
 export async function invokeListMessages(
   conversationId: number
 ): Promise<Message[]> {
@@ -182,6 +417,19 @@ export async function invokeListMessages(
   return result;
 }
 
+export async function invokeListMessagesPage(
+  conversationId: number,
+  beforeMessageId?: number,
+  pageSize?: number
+): Promise<Page<Message, number>> {
+  const result = await invoke<Page<Message, number>>('list_messages_page', {
+    conversationId,
+    beforeMessageId,
+    pageSize,
+  });
+  return result;
+}
+
 export async function invokeCreateMessage(
   message: NewMessage
 ): Promise<Message> {
@@ -214,6 +462,26 @@ export async function invokeUpdateMessage(message: Message): Promise<Message> {
   return result;
 }
 
+export async function invokeListMessageRevisions(
+  messageId: number
+): Promise<MessageRevision[]> {
+  const result = await invoke<MessageRevision[]>('list_message_revisions', {
+    messageId,
+  });
+  return result;
+}
+
+export async function invokeRestoreMessageRevision(
+  messageId: number,
+  revision: number
+): Promise<Message> {
+  const result = await invoke<Message>('restore_message_revision', {
+    messageId,
+    revision,
+  });
+  return result;
+}
+
 export async function invokeHardDeleteMessages(
   conversationId: number
 ): Promise<void> {
@@ -247,6 +515,17 @@ export async function invokeCallBot({
   });
 }
 
+export async function invokeSemanticSearch(
+  query: string,
+  limit?: number
+): Promise<SemanticSearchResult[]> {
+  const result = await invoke<SemanticSearchResult[]>('semantic_search', {
+    query,
+    limit,
+  });
+  return result;
+}
+
 export async function invokeCreatePrompt(
   newPrompt: NewPrompt
 ): Promise<Prompt> {
@@ -256,8 +535,117 @@ export async function invokeCreatePrompt(
   return result;
 }
 
-export async function invokeListPrompts(): Promise<Prompt[]> {
-  const result = await invoke<Prompt[]>('list_prompts');
+export async function invokeListPrompts(
+  category?: string,
+  sort?: PromptSort
+): Promise<Prompt[]> {
+  const result = await invoke<Prompt[]>('list_prompts', { category, sort });
+  return result;
+}
+
+export async function invokeListPromptVersions(
+  promptId: number
+): Promise<PromptVersion[]> {
+  const result = await invoke<PromptVersion[]>('list_prompt_versions', {
+    promptId,
+  });
+  return result;
+}
+
+export async function invokeGetPromptVersion(
+  promptVersionId: number
+): Promise<PromptVersion> {
+  const result = await invoke<PromptVersion>('get_prompt_version', {
+    promptVersionId,
+  });
+  return result;
+}
+
+export async function invokeDiffPromptVersions(
+  promptId: number,
+  fromVersion: number,
+  toVersion: number
+): Promise<DiffLine[]> {
+  const result = await invoke<DiffLine[]>('diff_prompt_versions', {
+    promptId,
+    fromVersion,
+    toVersion,
+  });
+  return result;
+}
+
+export async function invokeRollbackPrompt(
+  promptId: number,
+  version: number
+): Promise<Prompt> {
+  const result = await invoke<Prompt>('rollback_prompt', {
+    promptId,
+    version,
+  });
+  return result;
+}
+
+export async function invokePinPromptVersion(
+  conversationId: number,
+  promptVersionId?: number
+): Promise<void> {
+  await invoke<void>('pin_prompt_version', {
+    conversationId,
+    promptVersionId,
+  });
+}
+
+export async function invokeListPromptCategories(): Promise<string[]> {
+  const result = await invoke<string[]>('list_prompt_categories');
+  return result;
+}
+
+export async function invokeRecordPromptUsage(promptId: number): Promise<void> {
+  await invoke<void>('record_prompt_usage', { promptId });
+}
+
+export async function invokeExportPrompts(
+  promptIds: number[],
+  path: string
+): Promise<number> {
+  const result = await invoke<number>('export_prompts', { promptIds, path });
+  return result;
+}
+
+export async function invokeImportPrompts(
+  path: string,
+  onConflict?: ImportConflict
+): Promise<ImportReport> {
+  const result = await invoke<ImportReport>('import_prompts', {
+    path,
+    onConflict,
+  });
+  return result;
+}
+
+export async function invokeGetPromptVariables(
+  promptId: number
+): Promise<TemplateVariable[]> {
+  const result = await invoke<TemplateVariable[]>('get_prompt_variables', {
+    promptId,
+  });
+  return result;
+}
+
+export async function invokeRenderPrompt(
+  promptId: number,
+  values: Record<string, string>,
+  modelId?: number,
+  version?: number,
+  conversationId?: number
+): Promise<string> {
+  const result = await invoke<string>('render_prompt', {
+    promptId,
+    version,
+    values,
+    modelId,
+    conversationId,
+  });
   return result;
 }
 
@@ -271,7 +659,161 @@ export async function invokeDeletePrompt(promptId: number): Promise<Prompt> {
   return result;
 }
 
+export async function invokeCreatePreset(
+  newPreset: NewPreset
+): Promise<Preset> {
+  const result = await invoke<Preset>('create_preset', { newPreset });
+  return result;
+}
+
+export async function invokeListPresets(): Promise<Preset[]> {
+  const result = await invoke<Preset[]>('list_presets');
+  return result;
+}
+
+export async function invokeUpdatePreset(preset: Preset): Promise<Preset> {
+  const result = await invoke<Preset>('update_preset', { preset });
+  return result;
+}
+
+export async function invokeDeletePreset(presetId: number): Promise<void> {
+  await invoke<void>('delete_preset', { presetId });
+}
+
+export async function invokeCreateConversationFromPreset(
+  presetId: number,
+  subject?: string
+): Promise<Conversation> {
+  const result = await invoke<Conversation>(
+    'create_conversation_from_preset',
+    { presetId, subject }
+  );
+  return result;
+}
+
+export async function invokeExportPresets(
+  presetIds: number[],
+  path: string
+): Promise<number> {
+  const result = await invoke<number>('export_presets', { presetIds, path });
+  return result;
+}
+
+export async function invokeImportPresets(
+  path: string,
+  onConflict?: ImportConflict
+): Promise<ImportReport> {
+  const result = await invoke<ImportReport>('import_presets', {
+    path,
+    onConflict,
+  });
+  return result;
+}
+
+export async function invokeCreateCollection(
+  newCollection: NewCollection
+): Promise<Collection> {
+  const result = await invoke<Collection>('create_collection', {
+    newCollection,
+  });
+  return result;
+}
+
+export async function invokeListCollections(): Promise<Collection[]> {
+  const result = await invoke<Collection[]>('list_collections');
+  return result;
+}
+
+export async function invokeDeleteCollection(
+  collectionId: number
+): Promise<void> {
+  await invoke('delete_collection', { collectionId });
+}
+
+export async function invokeAddDocumentsToCollection(
+  collectionId: number,
+  paths: string[]
+): Promise<KnowledgeDocument[]> {
+  const result = await invoke<KnowledgeDocument[]>(
+    'add_documents_to_collection',
+    { collectionId, paths }
+  );
+  return result;
+}
+
+export async function invokeListDocuments(
+  collectionId: number
+): Promise<KnowledgeDocument[]> {
+  const result = await invoke<KnowledgeDocument[]>('list_documents', {
+    collectionId,
+  });
+  return result;
+}
+
+export async function invokeDeleteDocument(documentId: number): Promise<void> {
+  await invoke('delete_document', { documentId });
+}
+
+export async function invokeSetConversationCollections(
+  conversationId: number,
+  collectionIds: number[]
+): Promise<Collection[]> {
+  const result = await invoke<Collection[]>('set_conversation_collections', {
+    conversationId,
+    collectionIds,
+  });
+  return result;
+}
+
+export async function invokeListConversationCollections(
+  conversationId: number
+): Promise<Collection[]> {
+  const result = await invoke<Collection[]>('list_conversation_collections', {
+    conversationId,
+  });
+  return result;
+}
+
 export async function invokeGetSysInfo(): Promise<Record<string, string>> {
   const result = await invoke<Record<string, string>>('get_sys_info');
   return result;
 }
+
+export async function invokeCollectCacheGarbage(): Promise<GcReport> {
+  const result = await invoke<GcReport>('collect_cache_garbage');
+  return result;
+}
+
+export async function invokeListTrash(): Promise<Trash> {
+  const result = await invoke<{
+    conversations: Conversation[];
+    models: GenericModel[];
+  }>('list_trash');
+  return {
+    conversations: result.conversations,
+    models: result.models.map(fromGenericModel),
+  };
+}
+
+export async function invokeRestoreConversation(
+  conversationId: number
+): Promise<Conversation> {
+  const result = await invoke<Conversation>('restore_conversation', {
+    conversationId,
+  });
+  return result;
+}
+
+export async function invokeRestoreModel(modelId: number): Promise<Model> {
+  const result = await invoke<GenericModel>('restore_model', {
+    modelId,
+  });
+  return fromGenericModel(result);
+}
+
+export async function invokePurgeTrash(
+  olderThan?: string
+): Promise<PurgeReport> {
+  const result = await invoke<PurgeReport>('purge_trash', { olderThan });
+  return result;
+}
@@ -135,9 +135,15 @@ export const MESSAGE_SYSTEM = 2;
 // Message content item type
 export const CONTENT_ITEM_TYPE_TEXT = 0;
 export const CONTENT_ITEM_TYPE_IMAGE = 1;
+export const CONTENT_ITEM_TYPE_DOCUMENT = 2;
+export const CONTENT_ITEM_TYPE_FILE = 3;
+export const CONTENT_ITEM_TYPE_AUDIO = 4;
 export const CONTENT_ITEM_TYPES = [
   CONTENT_ITEM_TYPE_TEXT,
   CONTENT_ITEM_TYPE_IMAGE,
+  CONTENT_ITEM_TYPE_DOCUMENT,
+  CONTENT_ITEM_TYPE_FILE,
+  CONTENT_ITEM_TYPE_AUDIO,
 ] as const;
 
 // Stream keywords
@@ -157,8 +163,22 @@ export const SETTING_PROFILE_NAME = 'profile:name';
 export const SETTING_MODELS_CONTEXT_LENGTH = 'models:context_length';
 export const SETTING_MODELS_MAX_TOKENS = 'models:max_tokens';
 export const SETTING_NETWORK_PROXY = 'network:proxy';
+export const SETTING_TTS_MODEL = 'tts:model';
+export const SETTING_TTS_VOICE = 'tts:voice';
+export const SETTING_TTS_FORMAT = 'tts:format';
+export const SETTING_TTS_SPEED = 'tts:speed';
+export const SETTING_CACHE_GC_ON_STARTUP = 'cache:gc_on_startup';
+export const SETTING_TRASH_RETENTION_DAYS = 'trash:retention_days';
+export const SETTING_TITLE_AUTO = 'title:auto';
+export const SETTING_TITLE_MODEL = 'title:model';
+
+// Events
+export const SETTINGS_CHANGED_EVENT = 'settings-changed';
+export const WORKSPACE_SWITCHED_EVENT = 'workspace-switched';
+export const CONVERSATION_TITLED_EVENT = 'conversation-titled';
 
 // Defaults
+export const DEFAULT_WORKSPACE_ID = 0;
 export const DEFAULT_DATE_FORMAT = 'MMM D, YYYY';
 export const DEFAULT_DATETIME_FORMAT = 'HH:mm MMM D, YYYY';
 export const DEFAULT_PROFILE_NAME = 'ME';
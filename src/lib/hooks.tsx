@@ -212,7 +212,7 @@ export function useListConversationsQuery(): UseQueryResult<
 > {
   return useQuery({
     queryKey: LIST_CONVERSATIONS_KEY,
-    queryFn: invokeListConversations,
+    queryFn: () => invokeListConversations(),
   });
 }
 
@@ -543,7 +543,7 @@ export function usePromptCreator(
 export function useListPromptsQuery(): UseQueryResult<Prompt[], CommandError> {
   return useQuery({
     queryKey: LIST_PROMPTS_KEY,
-    queryFn: invokeListPrompts,
+    queryFn: () => invokeListPrompts(),
   });
 }
 
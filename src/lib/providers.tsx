@@ -1,9 +1,23 @@
-import { QueryClient, QueryClientProvider } from '@tanstack/react-query';
+import {
+  QueryClient,
+  QueryClientProvider,
+  useQueryClient,
+} from '@tanstack/react-query';
+import { listen } from '@tauri-apps/api/event';
+import { produce } from 'immer';
 import { useTheme } from 'next-themes';
 import React, { useEffect, useMemo, useState } from 'react';
 import { useTranslation } from 'react-i18next';
 
-import { SETTING_DISPLAY_LANGUAGE, SETTING_DISPLAY_THEME } from './constants';
+import cache from './cache';
+import { invokeListWorkspaces } from './commands';
+import {
+  CONVERSATION_TITLED_EVENT,
+  SETTING_DISPLAY_LANGUAGE,
+  SETTING_DISPLAY_THEME,
+  SETTINGS_CHANGED_EVENT,
+  WORKSPACE_SWITCHED_EVENT,
+} from './constants';
 import {
   ConversationsContext,
   FileUploaderContext,
@@ -12,14 +26,18 @@ import {
 } from './contexts';
 import { AppError, ERROR_TYPE_APP_STATE } from './error';
 import {
+  LIST_CONVERSATIONS_KEY,
   useListConversationsQuery,
   useListModelsQuery,
   useListSettingsQuery,
 } from './hooks';
 import { useAppStateStore } from './store';
 import type {
+  ConversationDetails,
+  ConversationTitle,
   FileData,
   FilledPrompt,
+  Setting,
   TConversationsContext,
   TMessageListContext,
 } from './types';
@@ -48,7 +66,8 @@ export function InitializationProvider({
   const [initialized, setInitialized] = useState(false);
   const { i18n } = useTranslation();
   const { setTheme } = useTheme();
-  const { setModels, setSettings } = useAppStateStore();
+  const { setModels, setSettings, updateSetting } = useAppStateStore();
+  const queryClient = useQueryClient();
   const {
     data: modelList,
     isSuccess: isModelsSuccess,
@@ -86,6 +105,52 @@ export function InitializationProvider({
     }
   }, [i18n, isSettingsSuccess, setModels, setSettings, setTheme, settingList]);
 
+  // Keep settings in sync with those written elsewhere, eg. in another window
+  useEffect(() => {
+    const unlisten = listen<Setting>(SETTINGS_CHANGED_EVENT, (event) => {
+      updateSetting(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [updateSetting]);
+
+  // Conversations are titled in the background after their first reply
+  useEffect(() => {
+    const unlisten = listen<ConversationTitle>(
+      CONVERSATION_TITLED_EVENT,
+      (event) => {
+        const { conversationId, subject } = event.payload;
+        queryClient.setQueryData<ConversationDetails[]>(
+          LIST_CONVERSATIONS_KEY,
+          (old) =>
+            produce(old, (draft) => {
+              const conversation = draft?.find((c) => c.id === conversationId);
+              if (conversation) conversation.subject = subject;
+            })
+        );
+      }
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [queryClient]);
+
+  // Files are cached in the dir of the active workspace
+  useEffect(() => {
+    invokeListWorkspaces().then((list) => cache.setWorkspace(list.activeId));
+  }, []);
+
+  // Everything is loaded again from the workspace switched to
+  useEffect(() => {
+    const unlisten = listen(WORKSPACE_SWITCHED_EVENT, () => {
+      window.location.reload();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   useEffect(() => {
     if (isModelsSuccess && isSettingsSuccess) {
       setInitialized(true);
@@ -213,6 +213,9 @@ export const conversationFormSchema = z.object({
 export const newPromptFormSchema = z.object({
   alias: z.string(),
   content: z.string(),
+  category: z.string().nullish(),
+  tags: z.array(z.string()).optional(),
+  description: z.string().nullish(),
 });
 
 export const editPromptFormSchema = newPromptFormSchema.extend({
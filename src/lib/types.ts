@@ -52,8 +52,42 @@ export type NewModel =
   | NewOllamaModel
   | NewGoogleModel;
 
+export type RemoteModelPricing = {
+  prompt: string;
+  completion: string;
+};
+
 export type RemoteModel = {
   id: string;
+  displayName?: string;
+  contextLength?: number;
+  maxOutput?: number;
+  pricing?: RemoteModelPricing;
+  modalities?: string[];
+  created?: number;
+};
+
+export type DiagnosticKind =
+  | 'ok'
+  | 'dns'
+  | 'tls'
+  | 'proxy'
+  | 'connection'
+  | 'timeout'
+  | 'auth'
+  | 'unknownModel'
+  | 'region'
+  | 'rateLimit'
+  | 'server'
+  | 'unknown';
+
+export type ConnectionDiagnostic = {
+  ok: boolean;
+  kind: DiagnosticKind;
+  message?: string;
+  latencyMs: number;
+  models?: RemoteModel[];
+  modelFound?: boolean;
 };
 
 type SavedModelAttrs = {
@@ -61,6 +95,8 @@ type SavedModelAttrs = {
   createdAt?: string;
   updatedAt?: string;
   deletedAt?: string;
+  // JSON of the options merged into new conversations of the model
+  defaultOptions?: string;
 };
 
 export type Model = NewModel & SavedModelAttrs;
@@ -73,6 +109,7 @@ export type GenericModel = {
   createdAt?: string;
   updatedAt?: string;
   deletedAt?: string;
+  defaultOptions?: string;
 };
 
 export type RawOpenAIConfig = {
@@ -136,6 +173,17 @@ export type Conversation = {
   createdAt: string;
   updatedAt?: string;
   deletedAt?: string;
+  folderId?: number;
+  pinned?: boolean;
+  archived?: boolean;
+  // Whether the subject was set by the user rather than generated
+  subjectEdited?: boolean;
+};
+
+// Title generated for a conversation after its first reply
+export type ConversationTitle = {
+  conversationId: number;
+  subject: string;
 };
 
 export type ConversationDetails = {
@@ -149,6 +197,52 @@ export type ConversationDetails = {
   lastMessageAt?: string;
   messageCount?: number;
   modelProvider?: AllProviders;
+  folderId?: number;
+  pinned: boolean;
+  archived: boolean;
+  // Version of a prompt pinned to the conversation
+  promptVersionId?: number;
+  // Preset the conversation was created from
+  presetId?: number;
+};
+
+export type ConversationFilter = {
+  folderId?: number;
+  tagIds?: number[];
+  pinned?: boolean;
+  archived?: boolean;
+};
+
+export type ConversationCursor = {
+  pinned: boolean;
+  lastMessageAt: string;
+  id: number;
+};
+
+export type Page<T, C> = {
+  items: T[];
+  total: number;
+  nextCursor?: C;
+};
+
+export type NewFolder = {
+  name: string;
+};
+
+export type Folder = NewFolder & {
+  id: number;
+  createdAt: string;
+  updatedAt?: string;
+};
+
+export type NewTag = {
+  name: string;
+  color?: string;
+};
+
+export type Tag = NewTag & {
+  id: number;
+  createdAt: string;
 };
 
 export type UpdateConversation = Omit<
@@ -181,10 +275,28 @@ export type Message = NewMessage & {
   updatedAt?: string;
   deletedAt?: string;
   modelId?: number;
+  // Incremented each time the message is edited
+  revision?: number;
+  // Revision of the user message a bot reply was generated from
+  promptRevision?: number;
   isReceiving?: boolean;
   isError?: boolean;
 };
 
+// A previous version of a message
+export type MessageRevision = {
+  id: number;
+  messageId: number;
+  revision: number;
+  content: ContentItem[];
+  reasoning?: string;
+  promptToken?: number;
+  completionToken?: number;
+  reasoningToken?: number;
+  totalToken?: number;
+  createdAt: string;
+};
+
 export type BotReply = {
   message: string;
   reasoning?: string;
@@ -194,6 +306,14 @@ export type BotReply = {
   totalToken?: number;
 };
 
+// Stored under the `imageGeneration` key of a conversation's options
+export type ImageGenerationOptions = {
+  size?: string;
+  quality?: string;
+  n?: number;
+  style?: string;
+};
+
 export type AzureOptions = z.infer<typeof azureOptionsFormSchema>;
 export type OpenAIOptions = z.infer<typeof openAIOptionsFormSchema>;
 export type ClaudeOptions = z.infer<typeof claudeOptionsFormSchema>;
@@ -214,6 +334,9 @@ export type NewPrompt = z.infer<typeof newPromptFormSchema>;
 
 export type Prompt = NewPrompt & {
   id: number;
+  version?: number;
+  usageCount?: number;
+  lastUsedAt?: string;
   createdAt?: string;
   updatedAt?: string;
   deletedAt?: string;
@@ -221,6 +344,99 @@ export type Prompt = NewPrompt & {
 
 export type FilledPrompt = z.infer<typeof usePromptFormSchema>;
 
+// A recorded wording of a prompt
+export type PromptVersion = {
+  id: number;
+  promptId: number;
+  version: number;
+  alias: string;
+  content: string;
+  createdAt: string;
+};
+
+export type DiffLine = {
+  op: 'equal' | 'insert' | 'delete';
+  text: string;
+  oldLine?: number;
+  newLine?: number;
+};
+
+// Reusable setup of conversations
+export type NewPreset = {
+  name: string;
+  description?: string;
+  modelId?: number;
+  // Takes precedence over the prompt
+  systemPrompt?: string;
+  promptId?: number;
+  // JSON of the options of the provider
+  options?: string;
+  collectionIds?: number[];
+  tools?: string[];
+};
+
+export type Preset = NewPreset & {
+  id: number;
+  collectionIds: number[];
+  tools: string[];
+  createdAt: string;
+  updatedAt?: string;
+};
+
+export type PromptSort = 'created' | 'alias' | 'popularity' | 'lastUsed';
+
+// What to do with an imported prompt whose alias is already used
+export type ImportConflict = 'skip' | 'rename' | 'overwrite';
+
+export type ImportReport = {
+  imported: number;
+  renamed: number;
+  overwritten: number;
+  skipped: number;
+};
+
+export type TemplateVariableType = 'text' | 'number' | 'boolean' | 'date';
+
+// A `{name:type=default|description}` placeholder of a prompt
+export type TemplateVariable = {
+  name: string;
+  type: TemplateVariableType;
+  default?: string;
+  description?: string;
+  required: boolean;
+};
+
+export type SemanticSearchResult = {
+  message: Message;
+  conversationId: number;
+  conversationSubject: string;
+  score: number;
+  previous?: Message;
+  next?: Message;
+};
+
+export type NewCollection = {
+  name: string;
+  description?: string;
+  modelId: number;
+};
+
+export type Collection = NewCollection & {
+  id: number;
+  createdAt: string;
+  updatedAt?: string;
+};
+
+export type KnowledgeDocument = {
+  id: number;
+  collectionId: number;
+  name: string;
+  path: string;
+  mimetype?: string;
+  chunkCount: number;
+  createdAt: string;
+};
+
 export type ProxySetting = z.infer<typeof proxySchema>;
 
 export type ProviderStyles = {
@@ -296,12 +512,13 @@ export type PromptInputHandler = {
 // Functions
 export function toGenericModel(model: NewModel | Model): GenericModel {
   if ('id' in model) {
-    const { id, alias, provider, ...config } = model;
+    const { id, alias, provider, defaultOptions, ...config } = model;
     return {
       id,
       alias,
       provider,
       config: JSON.stringify(config),
+      defaultOptions,
     };
   }
   const { alias, provider, ...config } = model;
@@ -335,3 +552,33 @@ export function toGenericConfig(config: RawConfig): GenericConfig {
     config: JSON.stringify(config),
   };
 }
+
+// Separate set of conversations, models and settings
+export type Workspace = {
+  id: number;
+  name: string;
+  createdAt?: string;
+};
+
+export type WorkspaceList = {
+  activeId: number;
+  workspaces: Workspace[];
+};
+
+export type GcReport = {
+  removedFiles: number;
+  reclaimedBytes: number;
+  keptFiles: number;
+};
+
+export type Trash = {
+  conversations: Conversation[];
+  models: Model[];
+};
+
+export type PurgeReport = {
+  purgedConversations: number;
+  purgedModels: number;
+  removedFiles: number;
+  reclaimedBytes: number;
+};
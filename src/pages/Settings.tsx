@@ -294,14 +294,14 @@ function SettingContextLength() {
     const validation = z.coerce
       .number()
       .int()
-      .min(0)
+      .min(1)
       .max(65535)
       .safeParse(ctxLengthRef.current?.value);
     if (!validation.success) {
       setError(
         t('error:validation:invalid-int-minmax', {
           value: ctxLengthLabel,
-          min: 0,
+          min: 1,
           max: 65535,
         })
       );